@@ -0,0 +1,80 @@
+//! Approve or deny a pending tool call
+
+use anyhow::Result;
+use console::style;
+use namra_storage::{ApprovalRecord, ApprovalStatus, SqliteStorage};
+
+/// Allow a pending tool call to run
+pub fn approve(id: &str) -> Result<()> {
+    decide(id, ApprovalStatus::Approved, None)
+}
+
+/// Refuse a pending tool call; the reason is passed on to the agent
+pub fn deny(id: &str, reason: Option<&str>) -> Result<()> {
+    decide(id, ApprovalStatus::Denied, reason)
+}
+
+fn decide(id: &str, status: ApprovalStatus, reason: Option<&str>) -> Result<()> {
+    let storage = SqliteStorage::open_default()?;
+
+    let Some(approval) = find_approval(&storage, id)? else {
+        println!("{}", style(format!("Approval not found: {}", id)).red());
+        return Ok(());
+    };
+
+    if approval.status != ApprovalStatus::Pending {
+        anyhow::bail!("Approval {} was already {}", approval.id, approval.status);
+    }
+
+    storage.decide_approval(&approval.id, status, reason)?;
+
+    println!(
+        "{} {} {} for run {}",
+        style("✓").green(),
+        style(&approval.tool_name).cyan(),
+        status,
+        style(&approval.run_id).cyan()
+    );
+
+    let waiting = storage
+        .list_approvals(Some(ApprovalStatus::Pending))?
+        .into_iter()
+        .filter(|a| a.run_id == approval.run_id)
+        .count();
+    if waiting == 0 {
+        println!(
+            "{}",
+            style(format!(
+                "Resume the run with `namra run <FILE> --resume {}`",
+                approval.run_id
+            ))
+            .dim()
+        );
+    } else {
+        println!(
+            "{}",
+            style(format!("{} more approval(s) pending for this run", waiting)).dim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Find an approval by full ID or unique prefix
+fn find_approval(storage: &SqliteStorage, id: &str) -> Result<Option<ApprovalRecord>> {
+    if let Some(approval) = storage.get_approval(id)? {
+        return Ok(Some(approval));
+    }
+
+    let mut matches: Vec<ApprovalRecord> = storage
+        .list_approvals(None)?
+        .into_iter()
+        .filter(|a| a.id.starts_with(id))
+        .collect();
+
+    match matches.len() {
+        0 => Ok(None),
+        1 => Ok(matches.pop()),
+        _ => anyhow::bail!("Approval ID prefix {} is ambiguous", id),
+    }
+}
@@ -0,0 +1,58 @@
+//! List approvals command
+
+use anyhow::Result;
+use console::style;
+use namra_storage::{ApprovalStatus, SqliteStorage};
+
+pub fn execute(all: bool) -> Result<()> {
+    let storage = SqliteStorage::open_default()?;
+
+    let status = if all {
+        None
+    } else {
+        Some(ApprovalStatus::Pending)
+    };
+    let approvals = storage.list_approvals(status)?;
+
+    if approvals.is_empty() {
+        println!("{}", style("No approvals waiting.").dim());
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "  {:<10} {:<10} {:<20} {:<20} {:<10}",
+        style("ID").bold(),
+        style("RUN").bold(),
+        style("AGENT").bold(),
+        style("TOOL").bold(),
+        style("STATUS").bold(),
+    );
+    println!("{}", style("─".repeat(75)).dim());
+
+    for approval in &approvals {
+        let status = match approval.status {
+            ApprovalStatus::Pending => style("pending").yellow().to_string(),
+            ApprovalStatus::Approved => style("approved").green().to_string(),
+            ApprovalStatus::Denied => style("denied").red().to_string(),
+        };
+
+        println!(
+            "  {:<10} {:<10} {:<20} {:<20} {:<10}",
+            style(&approval.id[..8]).cyan(),
+            &approval.run_id[..8.min(approval.run_id.len())],
+            approval.agent_name,
+            approval.tool_name,
+            status,
+        );
+        println!("    {}", style(approval.arguments.to_string()).dim());
+    }
+
+    println!("{}", style("─".repeat(75)).dim());
+    println!(
+        "{}",
+        style("Decide with `namra approvals approve|deny <ID>`, then resume the run with `namra run <FILE> --resume <RUN>`.").dim()
+    );
+
+    Ok(())
+}
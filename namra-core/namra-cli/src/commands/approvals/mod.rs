@@ -0,0 +1,7 @@
+//! Approvals subcommand - review tool calls waiting for a human decision
+
+mod decide;
+mod list;
+
+pub use decide::{approve, deny};
+pub use list::execute as list;
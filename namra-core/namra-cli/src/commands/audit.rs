@@ -0,0 +1,24 @@
+//! Audit command implementation
+
+use anyhow::Result;
+use console::style;
+use namra_middleware::audit;
+use std::path::Path;
+
+/// Check that an audit log's hash chain is intact
+pub fn verify(path: &Path) -> Result<()> {
+    print!("Verifying {}... ", path.display());
+
+    match audit::verify(path) {
+        Ok(summary) => {
+            println!("{}", style("✓ intact").green().bold());
+            println!("  Records:   {}", summary.records);
+            println!("  Last hash: {}", style(&summary.last_hash).dim());
+            Ok(())
+        }
+        Err(e) => {
+            println!("{}", style("✗ invalid").red().bold());
+            anyhow::bail!(e);
+        }
+    }
+}
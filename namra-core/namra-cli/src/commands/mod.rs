@@ -1,4 +1,7 @@
+pub mod approvals;
+pub mod audit;
 pub mod init;
 pub mod run;
 pub mod runs;
 pub mod validate;
+pub mod workflow;
@@ -1,26 +1,43 @@
 //! Run command implementation
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use chrono::Utc;
 use console::style;
-use namra_config::{parse_agent_config, validate_config, AgentConfig};
-use namra_llm::{AnthropicAdapter, LLMAdapter};
+use namra_config::{load_agent_config, validate_config, AgentConfig};
 use namra_middleware::observability::{NamraTracer, ObservabilityConfig};
 use namra_runtime::{
-    AgentExecutorBuilder, ExecutionResult, ReActStrategy, StopReason, ToolFactory,
+    AgentExecutorBuilder, ApprovalDecision, ApprovalHandler, ApprovalRequest, ExecutionResult,
+    LLMFactory, PendingApprovals, StopReason, StrategyRegistry, StreamEvent, ToolFactory,
 };
 use namra_storage::{
     RunRecord, SqliteStorage, StopReason as StoredStopReason, ThoughtEntry, ToolCallEntry,
 };
 use std::env;
+use std::io::{self, IsTerminal, Write};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// What `namra run` should do
+pub enum RunMode {
+    /// Start a new run with the given input
+    New {
+        input: String,
+        /// Pause on tool calls that need approval instead of prompting
+        defer_approvals: bool,
+        /// Conversation session to continue
+        session: Option<String>,
+    },
+    /// Continue a run that paused for approvals
+    Resume(String),
+}
 
-pub async fn execute(config_path: &Path, input: &str, _stream: bool) -> Result<()> {
+pub async fn execute(config_path: &Path, mode: RunMode, stream: bool) -> Result<()> {
     println!("{}", style("Loading agent configuration...").cyan());
 
     // Parse configuration
-    let config = parse_agent_config(config_path)
+    let config = load_agent_config(config_path)
         .with_context(|| format!("Failed to load config from {}", config_path.display()))?;
 
     // Validate configuration
@@ -40,21 +57,10 @@ pub async fn execute(config_path: &Path, input: &str, _stream: bool) -> Result<(
     );
     println!();
 
-    // Get API key from environment
-    let api_key = match config.llm.provider.as_str() {
-        "anthropic" => env::var("ANTHROPIC_API_KEY")
-            .context("ANTHROPIC_API_KEY environment variable not set")?,
-        "openai" => {
-            env::var("OPENAI_API_KEY").context("OPENAI_API_KEY environment variable not set")?
-        }
-        provider => anyhow::bail!("Unsupported LLM provider: {}", provider),
-    };
-
-    // Create LLM adapter
-    let adapter: Arc<dyn LLMAdapter> = match config.llm.provider.as_str() {
-        "anthropic" => Arc::new(AnthropicAdapter::new(api_key)),
-        _ => anyhow::bail!("Unsupported provider: {}", config.llm.provider),
-    };
+    // Create LLM adapter, reading the API key from the environment
+    let adapter = LLMFactory::new()
+        .build(&config.llm)
+        .context("Failed to create LLM adapter")?;
 
     println!(
         "{}",
@@ -80,23 +86,95 @@ pub async fn execute(config_path: &Path, input: &str, _stream: bool) -> Result<(
     );
     println!();
 
-    // Build agent executor with ReAct strategy
-    let executor = AgentExecutorBuilder::new()
+    // Build agent executor with the configured strategy
+    let strategy = StrategyRegistry::new()
+        .create(&config)
+        .context("Failed to create execution strategy")?;
+
+    let mut builder = AgentExecutorBuilder::new()
         .config(config.clone())
         .llm(adapter)
         .tools(tools)
-        .strategy(Box::new(ReActStrategy::new()))
-        .build()
-        .context("Failed to build agent executor")?;
+        .strategy(strategy);
+
+    // Past spend counts towards the hourly cost budget
+    let history = match SqliteStorage::open_default() {
+        Ok(storage) => {
+            let storage = Arc::new(Mutex::new(storage));
+            builder = builder.run_history(storage.clone());
+            Some(storage)
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                style(format!("Warning: Could not open run history: {}", e)).yellow()
+            );
+            None
+        }
+    };
+
+    // Ask at the terminal, or record approvals for later and pause the run
+    let deferred = match (&mode, &history) {
+        (
+            RunMode::New {
+                defer_approvals: false,
+                ..
+            },
+            _,
+        ) => {
+            builder = builder.approval_handler(Arc::new(TerminalApprover));
+            None
+        }
+        (_, Some(storage)) => {
+            let approvals = Arc::new(PendingApprovals::new(storage.clone()));
+            builder = builder.approval_handler(approvals.clone());
+            Some(approvals)
+        }
+        (_, None) => anyhow::bail!("Deferred approvals need the run history database"),
+    };
+
+    // Print streamed output as it arrives
+    let printer = if stream {
+        let (tx, rx) = mpsc::unbounded_channel();
+        builder = builder.stream_events(tx);
+        Some(tokio::spawn(print_stream_events(rx)))
+    } else {
+        None
+    };
+
+    let executor = builder.build().context("Failed to build agent executor")?;
 
     println!("{}", style("Agent is thinking...").cyan().dim());
     println!();
 
     // Execute
-    let result = executor
-        .execute(input)
-        .await
-        .context("Agent execution failed")?;
+    let (input, result, resumed) = match mode {
+        RunMode::New { input, session, .. } => {
+            let result = executor.execute(&input, session.as_deref()).await;
+            (input, result, None)
+        }
+        RunMode::Resume(run_id) => {
+            let paused = deferred
+                .as_ref()
+                .map(|approvals| approvals.paused_run(&run_id))
+                .transpose()?
+                .flatten()
+                .with_context(|| format!("No paused run found with ID {}", run_id))?;
+            let input = paused.input.clone();
+            (input, executor.resume(paused).await, Some(run_id))
+        }
+    };
+    let input = input.as_str();
+
+    // Dropping the executor closes the event channel so the printer finishes
+    drop(executor);
+    if let Some(printer) = printer {
+        printer.await.ok();
+        println!();
+        println!();
+    }
+
+    let result = result.context("Agent execution failed")?;
 
     // Save to run history
     if let Err(e) = save_run_history(&config, input, &result) {
@@ -106,8 +184,15 @@ pub async fn execute(config_path: &Path, input: &str, _stream: bool) -> Result<(
         );
     }
 
-    // Display intermediate thoughts/reasoning
-    if !result.thoughts.is_empty() {
+    // The paused state is only dropped once the resumed run is done with it
+    if let (Some(run_id), Some(approvals)) = (&resumed, &deferred) {
+        if result.stop_reason != StopReason::AwaitingApproval {
+            approvals.finish_paused_run(run_id)?;
+        }
+    }
+
+    // Display intermediate thoughts/reasoning (already shown live when streaming)
+    if !stream && !result.thoughts.is_empty() {
         println!("{}", style("═".repeat(60)).dim());
         println!("{}", style("Agent Reasoning:").cyan().bold());
         println!();
@@ -130,8 +215,22 @@ pub async fn execute(config_path: &Path, input: &str, _stream: bool) -> Result<(
 
     if result.success {
         println!("{}", style("✓ Execution completed successfully").green());
+    } else if result.stop_reason == StopReason::AwaitingApproval {
+        println!("{}", style("⏸ Waiting for approval").yellow());
+        println!(
+            "  {}",
+            style(format!(
+                "Review with `namra approvals list`, then `namra run {} --resume {}`",
+                config_path.display(),
+                result.id
+            ))
+            .dim()
+        );
     } else {
         println!("{}", style("✗ Execution failed").red());
+        if let Some(ref error) = result.error {
+            println!("  {}", style(error).red());
+        }
     }
 
     // Display run ID for reference
@@ -196,8 +295,90 @@ pub async fn execute(config_path: &Path, input: &str, _stream: bool) -> Result<(
     Ok(())
 }
 
+/// Asks at the terminal whether a tool call may run
+struct TerminalApprover;
+
+#[async_trait]
+impl ApprovalHandler for TerminalApprover {
+    async fn review(&self, request: &ApprovalRequest) -> namra_runtime::Result<ApprovalDecision> {
+        if !io::stdin().is_terminal() {
+            return Ok(ApprovalDecision::Denied(Some(
+                "no terminal to ask for approval; use --defer-approvals".to_string(),
+            )));
+        }
+
+        println!();
+        println!(
+            "{} {} {}",
+            style("Approval needed:").yellow().bold(),
+            style(&request.tool_name).cyan(),
+            style(&request.arguments).dim()
+        );
+        print!("Allow this call? [y/N, or a reason to deny] ");
+        io::stdout().flush().ok();
+
+        let answer = tokio::task::spawn_blocking(|| {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line).map(|_| line)
+        })
+        .await
+        .map_err(|e| namra_runtime::RuntimeError::Other(e.to_string()))?
+        .map_err(|e| namra_runtime::RuntimeError::Other(e.to_string()))?;
+
+        Ok(match answer.trim() {
+            "y" | "Y" | "yes" => ApprovalDecision::Approved,
+            "" | "n" | "N" | "no" => ApprovalDecision::Denied(None),
+            reason => ApprovalDecision::Denied(Some(reason.to_string())),
+        })
+    }
+}
+
+/// Print live events from a streaming run
+async fn print_stream_events(mut events: mpsc::UnboundedReceiver<StreamEvent>) {
+    while let Some(event) = events.recv().await {
+        match event {
+            StreamEvent::Token(text) => {
+                print!("{}", text);
+                io::stdout().flush().ok();
+            }
+            StreamEvent::ToolCall { name, arguments } => {
+                println!();
+                println!(
+                    "{} {} {}",
+                    style("→").cyan(),
+                    style(name).cyan(),
+                    style(arguments).dim()
+                );
+            }
+            StreamEvent::ToolResult { name, success } => {
+                let status = if success {
+                    style("✓").green()
+                } else {
+                    style("✗").red()
+                };
+                println!("{} {}", status, style(name).dim());
+            }
+            StreamEvent::Usage(usage) => {
+                println!();
+                println!(
+                    "{}",
+                    style(format!(
+                        "[{} in / {} out tokens]",
+                        usage.input_tokens, usage.output_tokens
+                    ))
+                    .dim()
+                );
+            }
+        }
+    }
+}
+
 /// Save the execution result to run history
-fn save_run_history(config: &AgentConfig, input: &str, result: &ExecutionResult) -> Result<()> {
+pub(crate) fn save_run_history(
+    config: &AgentConfig,
+    input: &str,
+    result: &ExecutionResult,
+) -> Result<()> {
     let storage = SqliteStorage::open_default()?;
 
     let now = Utc::now();
@@ -211,10 +392,7 @@ fn save_run_history(config: &AgentConfig, input: &str, result: &ExecutionResult)
         response: Some(result.response.clone()),
         success: result.success,
         stop_reason: convert_stop_reason(&result.stop_reason),
-        error_message: match &result.stop_reason {
-            StopReason::Error(e) => Some(e.clone()),
-            _ => None,
-        },
+        error_message: result.error.clone(),
         iterations: result.iterations,
         total_tokens: result.total_tokens,
         total_cost: result.total_cost,
@@ -252,6 +430,7 @@ fn save_run_history(config: &AgentConfig, input: &str, result: &ExecutionResult)
                 timestamp: now,
             })
             .collect(),
+        metadata: result.metadata.clone(),
     };
 
     storage.save_run(&run_record)?;
@@ -266,6 +445,9 @@ fn convert_stop_reason(reason: &StopReason) -> StoredStopReason {
         StopReason::Timeout => StoredStopReason::Timeout,
         StopReason::Error(_) => StoredStopReason::Error,
         StopReason::UserStop => StoredStopReason::UserStop,
+        StopReason::Truncated => StoredStopReason::Truncated,
+        StopReason::BudgetExceeded => StoredStopReason::BudgetExceeded,
+        StopReason::AwaitingApproval => StoredStopReason::AwaitingApproval,
     }
 }
 
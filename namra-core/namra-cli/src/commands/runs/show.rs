@@ -51,6 +51,9 @@ pub fn execute(id: &str, verbose: bool) -> Result<()> {
         style("✗ Failed").red()
     };
     println!("{:<15} {}", style("Status:").dim(), status);
+    if let Some(session) = run.metadata.get("session_id").and_then(|s| s.as_str()) {
+        println!("{:<15} {}", style("Session:").dim(), session);
+    }
 
     println!();
 
@@ -87,6 +90,12 @@ pub fn execute(id: &str, verbose: bool) -> Result<()> {
         format!("{:.2}s", run.execution_time_ms as f64 / 1000.0)
     };
     println!("  Duration:      {}", style(time).yellow());
+    if let Some(hits) = run.metadata.get("cache_hits").and_then(|h| h.as_u64()) {
+        println!("  Cache Hits:    {}", style(hits.to_string()).yellow());
+    }
+    if let Some(summaries) = run.metadata.get("summarizations").and_then(|s| s.as_u64()) {
+        println!("  Summaries:     {}", style(summaries.to_string()).yellow());
+    }
     println!("  Stop Reason:   {:?}", run.stop_reason);
 
     if let Some(ref provider) = run.llm_provider {
@@ -143,6 +152,45 @@ pub fn execute(id: &str, verbose: bool) -> Result<()> {
         }
     }
 
+    // Plan (plan_execute strategy)
+    if let Some(plan) = run.metadata.get("plan") {
+        print_plan(plan);
+    }
+
+    // Security guard detections
+    if let Some(detections) = run
+        .metadata
+        .get("security_detections")
+        .and_then(|d| d.as_array())
+    {
+        println!();
+        println!(
+            "{}",
+            style(format!("Security Detections ({}):", detections.len())).dim()
+        );
+        for detection in detections {
+            let source = detection["source"]
+                .as_str()
+                .map(|s| format!(" from {}", style(s).cyan()))
+                .unwrap_or_default();
+            let action = if detection["blocked"].as_bool().unwrap_or(false) {
+                style("blocked").red()
+            } else if detection["guard"] == "redaction" {
+                style("redacted").yellow()
+            } else {
+                style("flagged").yellow()
+            };
+            println!(
+                "  {} {} in {}{} ({})",
+                action,
+                detection["rule"].as_str().unwrap_or("unknown"),
+                detection["stage"].as_str().unwrap_or("unknown"),
+                source,
+                detection["guard"].as_str().unwrap_or("unknown")
+            );
+        }
+    }
+
     // Timestamps
     println!();
     println!("{}", style("Timestamps:").dim());
@@ -160,6 +208,47 @@ pub fn execute(id: &str, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+fn print_plan(plan: &serde_json::Value) {
+    let revisions = plan["revisions"].as_array().cloned().unwrap_or_default();
+    let results = plan["results"].as_array().cloned().unwrap_or_default();
+
+    for revision in &revisions {
+        println!();
+        match revision["reason"].as_str() {
+            Some(reason) => println!(
+                "{}",
+                style(format!(
+                    "Plan (revision {}: {}):",
+                    revision["revision"],
+                    truncate_string(reason, 80)
+                ))
+                .dim()
+            ),
+            None => println!("{}", style("Plan:").dim()),
+        }
+
+        for step in revision["steps"].as_array().into_iter().flatten() {
+            let result = results.iter().find(|r| r["step"] == step["id"]);
+            let status_icon = match result.and_then(|r| r["success"].as_bool()) {
+                Some(true) => style("✓").green(),
+                Some(false) => style("✗").red(),
+                None => style("·").dim(),
+            };
+            let tool = step["tool"]
+                .as_str()
+                .map(|t| format!(" [{}]", style(t).cyan()))
+                .unwrap_or_default();
+            println!(
+                "  {}. {} {}{}",
+                step["id"],
+                status_icon,
+                truncate_string(step["description"].as_str().unwrap_or_default(), 80),
+                tool
+            );
+        }
+    }
+}
+
 fn truncate_string(s: &str, max_len: usize) -> String {
     // Replace newlines with spaces for display
     let s = s.replace('\n', " ").replace("  ", " ");
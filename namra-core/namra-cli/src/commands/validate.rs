@@ -2,7 +2,10 @@
 
 use anyhow::Result;
 use console::style;
-use namra_config::{parse_agent_config, validate_config};
+use namra_config::{
+    parse_agent_config, parse_workflow_config, validate_config, validate_workflow_config,
+    ConfigParser, ConfigValidator,
+};
 use std::path::Path;
 
 pub fn execute(files: &[impl AsRef<Path>]) -> Result<()> {
@@ -20,8 +23,11 @@ pub fn execute(files: &[impl AsRef<Path>]) -> Result<()> {
         print!("Validating {}... ", path.display());
 
         match validate_file(path) {
-            Ok(_) => {
+            Ok(warnings) => {
                 println!("{}", style("✓ valid").green().bold());
+                for warning in warnings {
+                    eprintln!("  Warning: {}", style(warning).yellow());
+                }
                 valid_count += 1;
             }
             Err(e) => {
@@ -59,12 +65,28 @@ pub fn execute(files: &[impl AsRef<Path>]) -> Result<()> {
     }
 }
 
-fn validate_file(path: &Path) -> Result<()> {
+/// Validate one file, returning its warnings
+fn validate_file(path: &Path) -> Result<Vec<String>> {
+    if ConfigParser::new().is_workflow(path)? {
+        return validate_workflow_file(path);
+    }
+
     // Parse the configuration
     let config = parse_agent_config(path)?;
 
     // Validate the configuration
     validate_config(&config)?;
 
-    Ok(())
+    Ok(ConfigValidator::new().warnings(&config))
+}
+
+/// Validate a workflow file and the agent configs its nodes use
+fn validate_workflow_file(path: &Path) -> Result<Vec<String>> {
+    let config = parse_workflow_config(path)?;
+
+    // Agent configs are relative to the workflow file
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    validate_workflow_config(&config, base_dir)?;
+
+    Ok(ConfigValidator::new().workflow_warnings(&config))
 }
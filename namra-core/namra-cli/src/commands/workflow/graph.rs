@@ -0,0 +1,19 @@
+//! Workflow graph command implementation
+
+use anyhow::{Context, Result};
+use namra_config::{parse_workflow_config, WorkflowGraph};
+use std::path::Path;
+
+pub fn execute(workflow_path: &Path, format: &str) -> Result<()> {
+    let config = parse_workflow_config(workflow_path)
+        .with_context(|| format!("Failed to load workflow from {}", workflow_path.display()))?;
+    let graph = WorkflowGraph::new(&config);
+
+    let diagram = match format.to_lowercase().as_str() {
+        "mermaid" => graph.to_mermaid(),
+        "dot" => graph.to_dot(),
+        _ => anyhow::bail!("Unsupported format: {}. Use: mermaid or dot", format),
+    };
+    print!("{}", diagram);
+    Ok(())
+}
@@ -0,0 +1,9 @@
+//! Workflow subcommand - run multi-agent workflows
+
+mod graph;
+mod resume;
+mod run;
+
+pub use graph::execute as graph;
+pub use resume::execute as resume;
+pub use run::execute as run;
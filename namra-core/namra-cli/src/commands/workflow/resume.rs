@@ -0,0 +1,51 @@
+//! Workflow resume command implementation
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use namra_runtime::{WorkflowCheckpoint, WorkflowExecutorBuilder};
+use namra_storage::SqliteStorage;
+use std::sync::{Arc, Mutex};
+
+use super::run::{print_result, save_node_runs};
+
+pub async fn execute(run_id: &str) -> Result<()> {
+    let storage = Arc::new(Mutex::new(
+        SqliteStorage::open_default().context("Failed to open run history")?,
+    ));
+    let Some(checkpoint) = WorkflowCheckpoint::load(&storage, run_id)? else {
+        bail!(
+            "No checkpoint for workflow run {}. Runs that completed have none to resume.",
+            run_id
+        );
+    };
+
+    let executor = WorkflowExecutorBuilder::new()
+        .workflow(checkpoint.workflow.clone())
+        .base_dir(&checkpoint.base_dir)
+        .run_history(storage)
+        .build()
+        .context("Failed to build workflow executor")?;
+
+    let completed = checkpoint.nodes.len();
+    println!(
+        "{}",
+        style(format!(
+            "✓ Resuming workflow: {} ({} of its nodes already ran)",
+            checkpoint.workflow.name, completed
+        ))
+        .green()
+    );
+    println!();
+    println!("{}", style("Running workflow...").cyan().dim());
+    println!();
+
+    let result = executor
+        .resume(checkpoint)
+        .await
+        .context("Workflow execution failed")?;
+
+    // A run that crashed saved none of its nodes, so save them all
+    save_node_runs(&executor, &result.nodes);
+    print_result(&executor, &result);
+    Ok(())
+}
@@ -0,0 +1,174 @@
+//! Workflow run command implementation
+
+use anyhow::{Context, Result};
+use console::style;
+use namra_config::parse_workflow_config;
+use namra_runtime::{NodeResult, WorkflowExecutor, WorkflowExecutorBuilder, WorkflowResult};
+use namra_storage::SqliteStorage;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::commands::run::save_run_history;
+
+pub async fn execute(workflow_path: &Path, input: &str) -> Result<()> {
+    println!("{}", style("Loading workflow configuration...").cyan());
+
+    let config = parse_workflow_config(workflow_path)
+        .with_context(|| format!("Failed to load workflow from {}", workflow_path.display()))?;
+
+    // Agent configs are relative to the workflow file. Keep the directory
+    // absolute so that a checkpointed run can be resumed from anywhere.
+    let base_dir = match workflow_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let base_dir = std::fs::canonicalize(base_dir).unwrap_or_else(|_| base_dir.to_path_buf());
+    let mut builder = WorkflowExecutorBuilder::new()
+        .workflow(config)
+        .base_dir(base_dir);
+    match SqliteStorage::open_default() {
+        Ok(storage) => builder = builder.run_history(Arc::new(Mutex::new(storage))),
+        Err(e) => eprintln!(
+            "{}",
+            style(format!("Warning: Could not open run history: {}", e)).yellow()
+        ),
+    }
+    let executor = builder
+        .build()
+        .context("Failed to build workflow executor")?;
+
+    let workflow = executor.config();
+    println!(
+        "{}",
+        style(format!(
+            "✓ Loaded workflow: {} ({} nodes)",
+            workflow.name,
+            workflow.nodes.len()
+        ))
+        .green()
+    );
+    println!();
+    println!("{}", style("Running workflow...").cyan().dim());
+    println!();
+
+    let result = executor
+        .execute(input)
+        .await
+        .context("Workflow execution failed")?;
+
+    save_node_runs(&executor, &result.nodes);
+    print_result(&executor, &result);
+    Ok(())
+}
+
+/// Save each node's run to run history
+///
+/// Runs already in the history are skipped, so a resumed run can save all of
+/// its nodes whether or not the interrupted run got to save the earlier ones.
+pub(super) fn save_node_runs(executor: &WorkflowExecutor, nodes: &[NodeResult]) {
+    let history = SqliteStorage::open_default().ok();
+    let saved = |id: &str| {
+        history
+            .as_ref()
+            .is_some_and(|h| h.get_run(id).is_ok_and(|run| run.is_some()))
+    };
+    for node in nodes {
+        let Some(agent) = executor.agent(&node.node_id) else {
+            continue;
+        };
+        for attempt in node.attempts.iter().filter(|a| !saved(&a.id)) {
+            if let Err(e) = save_run_history(agent.config(), &node.input, attempt) {
+                eprintln!(
+                    "{}",
+                    style(format!("Warning: Could not save run history: {}", e)).yellow()
+                );
+                return;
+            }
+        }
+    }
+}
+
+pub(super) fn print_result(executor: &WorkflowExecutor, result: &WorkflowResult) {
+    println!("{}", style("Nodes:").cyan().bold());
+    for (idx, node) in result.nodes.iter().enumerate() {
+        let status = if node.success() {
+            style("✓").green()
+        } else {
+            style("✗").red()
+        };
+        let run = node.result();
+        let retries = match node.attempts.len() {
+            1 => String::new(),
+            n => format!(", {} attempts", n),
+        };
+        println!(
+            "  {}. {} {} ({}ms, {} tokens{})",
+            idx + 1,
+            status,
+            style(&node.node_id).cyan(),
+            run.execution_time_ms,
+            node.total_tokens(),
+            retries
+        );
+        if let Some(ref error) = run.error {
+            println!("     {}", style(error).red());
+        }
+    }
+    println!();
+
+    println!("{}", style("Output:").cyan().bold());
+    println!("{}", result.output);
+    println!();
+
+    println!("{}", style("─".repeat(60)).dim());
+    if result.success {
+        println!("{}", style("✓ Workflow completed successfully").green());
+    } else {
+        println!("{}", style("✗ Workflow failed").red());
+        if let Some(ref error) = result.error {
+            println!("  {}", style(error).red());
+        }
+        if checkpointed(executor) {
+            println!(
+                "  {} namra workflow resume {}",
+                style("Resume with:").dim(),
+                &result.id[..8]
+            );
+        }
+    }
+
+    println!(
+        "{} {}",
+        style("Workflow run ID:").dim(),
+        style(&result.id[..8]).cyan()
+    );
+    println!(
+        "{} {} tokens",
+        style("Tokens:").dim(),
+        style(result.total_tokens).yellow()
+    );
+    if result.total_cost > 0.0 {
+        println!(
+            "{} {}",
+            style("Cost:").dim(),
+            style(format!("${:.4}", result.total_cost)).yellow()
+        );
+    }
+    let time_str = if result.execution_time_ms < 1000 {
+        format!("{}ms", result.execution_time_ms)
+    } else {
+        format!("{:.2}s", result.execution_time_ms as f64 / 1000.0)
+    };
+    println!("{} {}", style("Time:").dim(), style(time_str).yellow());
+    println!("{}", style("─".repeat(60)).dim());
+}
+
+/// Whether the workflow saves checkpoints
+fn checkpointed(executor: &WorkflowExecutor) -> bool {
+    executor
+        .config()
+        .config
+        .checkpoint
+        .as_ref()
+        .is_some_and(|c| c.enabled)
+}
@@ -27,7 +27,7 @@ enum Commands {
         namespace: Option<String>,
     },
 
-    /// Validate agent configuration files
+    /// Validate agent and workflow configuration files
     Validate {
         /// Path to configuration file(s)
         #[arg(value_name = "FILE")]
@@ -41,12 +41,42 @@ enum Commands {
         config: PathBuf,
 
         /// Input prompt for the agent
-        #[arg(short, long)]
-        input: String,
+        #[arg(short, long, required_unless_present = "resume")]
+        input: Option<String>,
 
         /// Enable streaming output
         #[arg(short, long)]
         stream: bool,
+
+        /// Pause on tool calls that need approval instead of prompting
+        #[arg(long)]
+        defer_approvals: bool,
+
+        /// Continue a conversation session kept in the agent's memory
+        #[arg(long, value_name = "SESSION_ID", conflicts_with = "resume")]
+        session: Option<String>,
+
+        /// Resume a run paused for approvals (run ID)
+        #[arg(long, value_name = "RUN_ID", conflicts_with = "input")]
+        resume: Option<String>,
+    },
+
+    /// Run multi-agent workflows
+    Workflow {
+        #[command(subcommand)]
+        command: WorkflowCommand,
+    },
+
+    /// Review tool calls waiting for approval
+    Approvals {
+        #[command(subcommand)]
+        command: ApprovalsCommand,
+    },
+
+    /// Inspect audit logs
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
     },
 
     /// View and manage run history
@@ -143,6 +173,72 @@ enum RunsCommand {
     },
 }
 
+#[derive(Subcommand)]
+enum WorkflowCommand {
+    /// Run a workflow
+    Run {
+        /// Path to workflow configuration file
+        #[arg(value_name = "FILE")]
+        config: PathBuf,
+
+        /// Input for the workflow's entry point
+        #[arg(short, long)]
+        input: String,
+    },
+
+    /// Continue a workflow run from its last checkpoint
+    Resume {
+        /// Workflow run ID (or its first characters)
+        id: String,
+    },
+
+    /// Print a workflow's diagram
+    Graph {
+        /// Path to workflow configuration file
+        #[arg(value_name = "FILE")]
+        config: PathBuf,
+
+        /// Diagram format: mermaid or dot
+        #[arg(short, long, default_value = "mermaid")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditCommand {
+    /// Check that an audit log has not been altered
+    Verify {
+        /// Audit log file
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ApprovalsCommand {
+    /// List tool calls waiting for approval
+    List {
+        /// Include approvals that were already decided
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Allow a pending tool call to run
+    Approve {
+        /// Approval ID (or prefix)
+        id: String,
+    },
+
+    /// Refuse a pending tool call
+    Deny {
+        /// Approval ID (or prefix)
+        id: String,
+
+        /// Reason passed on to the agent
+        #[arg(long)]
+        reason: Option<String>,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -160,10 +256,53 @@ async fn main() -> Result<()> {
             config,
             input,
             stream,
+            defer_approvals,
+            session,
+            resume,
         } => {
-            commands::run::execute(&config, &input, stream).await?;
+            let mode = match resume {
+                Some(run_id) => commands::run::RunMode::Resume(run_id),
+                None => commands::run::RunMode::New {
+                    input: input.unwrap_or_default(),
+                    defer_approvals,
+                    session,
+                },
+            };
+            commands::run::execute(&config, mode, stream).await?;
         }
 
+        Commands::Workflow { command } => match command {
+            WorkflowCommand::Run { config, input } => {
+                commands::workflow::run(&config, &input).await?;
+            }
+            WorkflowCommand::Resume { id } => {
+                commands::workflow::resume(&id).await?;
+            }
+            WorkflowCommand::Graph { config, format } => {
+                commands::workflow::graph(&config, &format)?;
+            }
+        },
+
+        Commands::Approvals { command } => match command {
+            ApprovalsCommand::List { all } => {
+                commands::approvals::list(all)?;
+            }
+
+            ApprovalsCommand::Approve { id } => {
+                commands::approvals::approve(&id)?;
+            }
+
+            ApprovalsCommand::Deny { id, reason } => {
+                commands::approvals::deny(&id, reason.as_deref())?;
+            }
+        },
+
+        Commands::Audit { command } => match command {
+            AuditCommand::Verify { file } => {
+                commands::audit::verify(&file)?;
+            }
+        },
+
         Commands::Runs { command } => match command {
             RunsCommand::List {
                 agent,
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use validator::Validate;
 
+use crate::SecretValue;
+
 /// Complete agent configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct AgentConfig {
@@ -76,18 +78,50 @@ pub struct LLMConfig {
     /// Retry configuration
     #[serde(default)]
     pub retry: Option<RetryConfig>,
+
+    /// Custom API base URL (e.g. a local OpenAI-compatible server)
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// API key, usually a reference such as `${env:ANTHROPIC_API_KEY}`
+    ///
+    /// Defaults to the provider's environment variable.
+    #[serde(default)]
+    pub api_key: Option<SecretValue>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryConfig {
+    /// Total attempts, including the first request
     #[serde(default = "default_max_attempts")]
     pub max_attempts: u32,
 
+    /// "exponential", "linear" or "constant"
     #[serde(default = "default_backoff")]
     pub backoff: String,
 
     #[serde(default = "default_initial_delay")]
     pub initial_delay: String,
+
+    /// Upper bound for a single backoff delay
+    #[serde(default = "default_max_delay")]
+    pub max_delay: String,
+
+    /// Randomize delays so concurrent agents don't retry in lockstep
+    #[serde(default = "default_true")]
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            backoff: default_backoff(),
+            initial_delay: default_initial_delay(),
+            max_delay: default_max_delay(),
+            jitter: true,
+        }
+    }
 }
 
 /// Tool configuration
@@ -149,9 +183,10 @@ pub struct HttpToolConfig {
     pub url: String,
     pub method: String,
     #[serde(default)]
-    pub headers: HashMap<String, String>,
+    pub headers: HashMap<String, SecretValue>,
+    /// `Authorization` header value; a bare token is sent as `Bearer <token>`
     #[serde(default)]
-    pub auth: Option<String>,
+    pub auth: Option<SecretValue>,
     #[serde(default = "default_timeout")]
     pub timeout: String,
     #[serde(default)]
@@ -192,7 +227,7 @@ pub enum FileSystemBackend {
         #[serde(default)]
         prefix: Option<String>,
         #[serde(default)]
-        credentials: Option<String>,
+        credentials: Option<SecretValue>,
     },
     GCS {
         bucket: String,
@@ -201,7 +236,7 @@ pub enum FileSystemBackend {
         #[serde(default)]
         prefix: Option<String>,
         #[serde(default)]
-        credentials: Option<String>,
+        credentials: Option<SecretValue>,
     },
     Azure {
         container: String,
@@ -209,7 +244,7 @@ pub enum FileSystemBackend {
         #[serde(default)]
         prefix: Option<String>,
         #[serde(default)]
-        credentials: Option<String>,
+        credentials: Option<SecretValue>,
     },
     SFTP {
         host: String,
@@ -219,7 +254,7 @@ pub enum FileSystemBackend {
         #[serde(default)]
         base_path: Option<String>,
         #[serde(default)]
-        credentials: Option<String>,
+        credentials: Option<SecretValue>,
     },
 }
 
@@ -341,6 +376,19 @@ pub struct SecurityConfig {
     #[serde(default)]
     pub detect_prompt_injection: bool,
 
+    /// Wrap tool outputs in delimiters that mark them as untrusted data
+    #[serde(default)]
+    pub wrap_tool_outputs: bool,
+
+    /// Prompt-injection patterns checked in addition to the built-in ones
+    #[serde(default)]
+    pub injection_rules: Vec<GuardRule>,
+
+    /// Secret and PII patterns redacted in addition to the built-in ones
+    #[serde(default)]
+    pub redaction_rules: Vec<GuardRule>,
+
+    /// Backend for secret references without one, like `${TOKEN}` (default `env`)
     #[serde(default)]
     pub secrets_backend: Option<String>,
 
@@ -349,6 +397,17 @@ pub struct SecurityConfig {
 
     #[serde(default)]
     pub blocked_domains: Vec<String>,
+
+    /// Let HTTP tools reach private, loopback, and link-local addresses
+    #[serde(default)]
+    pub allow_private_networks: bool,
+}
+
+/// Named regular expression used by the security guards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardRule {
+    pub name: String,
+    pub pattern: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -390,21 +449,50 @@ pub struct ResilienceConfig {
     #[serde(default)]
     pub circuit_breaker: Option<CircuitBreakerConfig>,
 
+    /// Time limit for each LLM request and tool call
     #[serde(default = "default_timeout")]
     pub timeout: String,
 
+    /// "fallback_llm" routes LLM requests to `fallback_llm` when the primary
+    /// provider fails or its circuit is open; "fail_fast" returns the error.
+    /// Defaults to "fallback_llm" when `fallback_llm` is set.
     #[serde(default)]
     pub fallback_strategy: Option<String>,
+
+    /// Secondary provider/model used by the "fallback_llm" strategy
+    #[serde(default)]
+    pub fallback_llm: Option<LLMConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CircuitBreakerConfig {
+    #[serde(default = "default_true")]
     pub enabled: bool,
+
+    /// Consecutive failures that open the circuit
+    #[serde(default = "default_failure_threshold")]
     pub failure_threshold: u32,
+
+    /// Calls slower than this are abandoned and count as failures
+    #[serde(default = "default_breaker_timeout")]
     pub timeout: String,
+
+    /// How long the circuit stays open before letting a trial call through
+    #[serde(default = "default_half_open_timeout")]
     pub half_open_timeout: String,
 }
 
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_threshold: default_failure_threshold(),
+            timeout: default_breaker_timeout(),
+            half_open_timeout: default_half_open_timeout(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachingConfig {
     #[serde(default)]
@@ -420,6 +508,10 @@ pub struct CachingConfig {
 
     #[serde(default)]
     pub similarity_threshold: Option<f32>,
+
+    /// Database file for the `sqlite` backend; defaults to the run history
+    #[serde(default)]
+    pub path: Option<String>,
 }
 
 /// Execution configuration
@@ -437,8 +529,52 @@ pub struct ExecutionConfig {
     #[serde(default)]
     pub parallel_tool_calls: bool,
 
+    /// Maximum tool calls run at once when `parallel_tool_calls` is enabled
+    #[serde(default = "default_max_parallel_tool_calls")]
+    pub max_parallel_tool_calls: u32,
+
     #[serde(default)]
     pub stop_sequences: Vec<String>,
+
+    /// Follow-up requests made when a response hits `max_tokens`; 0 disables
+    #[serde(default = "default_max_continuations")]
+    pub max_continuations: u32,
+
+    /// Settings for the `tree_of_thoughts` strategy
+    #[serde(default)]
+    pub tree_of_thoughts: Option<TreeOfThoughtsConfig>,
+
+    /// Settings for the `plan_execute` strategy
+    #[serde(default)]
+    pub plan_execute: Option<PlanExecuteConfig>,
+}
+
+/// Plan-and-execute strategy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanExecuteConfig {
+    /// Run steps whose dependencies are satisfied concurrently
+    #[serde(default)]
+    pub parallel_steps: bool,
+
+    /// Times the plan may be revised after a failed step
+    #[serde(default = "default_max_revisions")]
+    pub max_revisions: u32,
+}
+
+/// Tree-of-thoughts search configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeOfThoughtsConfig {
+    /// Candidate thoughts generated from each state
+    #[serde(default = "default_branching_factor")]
+    pub branching_factor: u32,
+
+    /// Number of reasoning steps to search
+    #[serde(default = "default_tot_depth")]
+    pub max_depth: u32,
+
+    /// Best-scoring states kept at each depth
+    #[serde(default = "default_beam_width")]
+    pub beam_width: u32,
 }
 
 /// Multi-tenancy configuration
@@ -475,6 +611,18 @@ fn default_backoff() -> String {
 fn default_initial_delay() -> String {
     "1s".to_string()
 }
+fn default_max_delay() -> String {
+    "30s".to_string()
+}
+fn default_failure_threshold() -> u32 {
+    5
+}
+fn default_breaker_timeout() -> String {
+    "120s".to_string()
+}
+fn default_half_open_timeout() -> String {
+    "30s".to_string()
+}
 fn default_timeout() -> String {
     "30s".to_string()
 }
@@ -505,6 +653,24 @@ fn default_strategy() -> String {
 fn default_max_iterations() -> u32 {
     10
 }
+fn default_max_parallel_tool_calls() -> u32 {
+    4
+}
+fn default_max_continuations() -> u32 {
+    3
+}
+fn default_branching_factor() -> u32 {
+    3
+}
+fn default_tot_depth() -> u32 {
+    3
+}
+fn default_beam_width() -> u32 {
+    1
+}
+fn default_max_revisions() -> u32 {
+    2
+}
 
 impl Default for ExecutionConfig {
     fn default() -> Self {
@@ -513,7 +679,30 @@ impl Default for ExecutionConfig {
             max_iterations: default_max_iterations(),
             timeout: default_timeout(),
             parallel_tool_calls: false,
+            max_parallel_tool_calls: default_max_parallel_tool_calls(),
             stop_sequences: vec![],
+            max_continuations: default_max_continuations(),
+            tree_of_thoughts: None,
+            plan_execute: None,
+        }
+    }
+}
+
+impl Default for PlanExecuteConfig {
+    fn default() -> Self {
+        Self {
+            parallel_steps: false,
+            max_revisions: default_max_revisions(),
+        }
+    }
+}
+
+impl Default for TreeOfThoughtsConfig {
+    fn default() -> Self {
+        Self {
+            branching_factor: default_branching_factor(),
+            max_depth: default_tot_depth(),
+            beam_width: default_beam_width(),
         }
     }
 }
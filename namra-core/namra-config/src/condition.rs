@@ -0,0 +1,553 @@
+//! Routing rules for `expression` edges
+//!
+//! An `expression` edge lists rules, one per line, each naming the node to run
+//! next when its condition holds about the node the edge starts from:
+//!
+//! ```text
+//! json.category == "billing" => billing
+//! output matches "(?i)refund" => refunds
+//! !success || cost > 0.5 => review
+//! else => general
+//! ```
+//!
+//! Rules are tried in order and the first that holds wins. Conditions can read
+//! the node's `output` (its response), `json` (the response parsed as JSON,
+//! with `.field` and `[index]` access), `success`, `cost` (in USD) and
+//! `tokens`; the workflow executor supplies their values. They support `==`, `!=`, `<`, `<=`, `>`, `>=`, `contains`,
+//! `matches` (a regex), `&&`, `||`, `!` and parentheses.
+//!
+//! The language has no assignments, loops or function calls, regexes are
+//! compiled when the workflow is built, and comparing values of different
+//! types is simply false, so evaluating a condition cannot fail or run long.
+
+use anyhow::Result;
+use regex::Regex;
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+
+/// Variables a condition can read
+const VARIABLES: [&str; 5] = ["output", "json", "success", "cost", "tokens"];
+
+/// Symbols of the language, longest first
+const SYMBOLS: [&str; 15] = [
+    "==", "!=", "<=", ">=", "&&", "||", "=>", "<", ">", "!", "(", ")", "[", "]", ".",
+];
+
+/// Deepest nesting of `!` and parentheses in a condition
+const MAX_DEPTH: usize = 32;
+
+/// Ordered rules choosing the node that runs next
+#[derive(Debug)]
+pub struct RouteRules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// `None` for `else`
+    condition: Option<Expr>,
+    target: String,
+}
+
+impl RouteRules {
+    /// Parse the rules of an edge's `logic`
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn parse(logic: &str) -> Result<Self> {
+        let rules = rule_lines(logic)
+            .map(|line| {
+                Rule::parse(line).map_err(|e| anyhow::anyhow!("Invalid rule '{}': {}", line, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if rules.is_empty() {
+            anyhow::bail!("Expression edge has no rules");
+        }
+        Ok(Self { rules })
+    }
+
+    /// Nodes the rules can choose
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|r| r.target.as_str())
+    }
+
+    /// The node chosen when the conditions' variables have `variables`
+    /// (an object keyed by variable name), if any rule holds
+    pub fn route(&self, variables: &Value) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| match &rule.condition {
+                Some(condition) => truthy(&condition.evaluate(variables)),
+                None => true,
+            })
+            .map(|rule| rule.target.as_str())
+    }
+}
+
+/// Lines of `logic` holding rules: not blank, and not a `#` comment
+pub(crate) fn rule_lines(logic: &str) -> impl Iterator<Item = &str> {
+    logic
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+impl Rule {
+    fn parse(line: &str) -> std::result::Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(line)?,
+            pos: 0,
+            depth: 0,
+        };
+
+        let condition = if parser.eat("else") {
+            None
+        } else {
+            Some(parser.or()?)
+        };
+        if !parser.eat("=>") {
+            return Err("expected '=>' followed by a node ID".to_string());
+        }
+        let target = match parser.next() {
+            Some(Token::Ident(node)) => node,
+            _ => return Err("expected a node ID after '=>'".to_string()),
+        };
+        if let Some(token) = parser.next() {
+            return Err(format!("unexpected {} after the node ID", token));
+        }
+
+        Ok(Self { condition, target })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Sym(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Str(text) => write!(f, "\"{}\"", text),
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Sym(sym) => write!(f, "'{}'", sym),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '"' || c == '\'' {
+            let (text, len) = string_literal(rest, c)?;
+            tokens.push(Token::Str(text));
+            len
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|d: char| d.is_ascii_digit()))
+        {
+            let len = 1 + rest[1..]
+                .find(|d: char| !d.is_ascii_digit() && d != '.')
+                .unwrap_or(rest.len() - 1);
+            let number = rest[..len]
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", &rest[..len]))?;
+            tokens.push(Token::Num(number));
+            len
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|d: char| !d.is_alphanumeric() && d != '_' && d != '-')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_string()));
+            len
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(|| format!("unexpected character '{}'", c))?;
+            tokens.push(Token::Sym(symbol));
+            symbol.len()
+        };
+        rest = rest[len..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// A quoted string at the start of `source` and its length in bytes
+fn string_literal(source: &str, quote: char) -> std::result::Result<(String, usize), String> {
+    let mut text = String::new();
+    let mut chars = source.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, 'n')) => text.push('\n'),
+                Some((_, 't')) => text.push('\t'),
+                Some((_, escaped)) if escaped == quote || escaped == '\\' => text.push(escaped),
+                // Other escapes, such as `\d` in a regex, are kept as written
+                Some((_, escaped)) => {
+                    text.push('\\');
+                    text.push(escaped);
+                }
+                None => break,
+            },
+            c if c == quote => return Ok((text, i + 1)),
+            c => text.push(c),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value),
+    Variable(Vec<Segment>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    Matches(Box<Expr>, Regex),
+}
+
+#[derive(Debug)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl Expr {
+    fn evaluate(&self, variables: &Value) -> Value {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Variable(path) => path
+                .iter()
+                .try_fold(variables, |value, segment| match segment {
+                    Segment::Field(name) => value.get(name),
+                    Segment::Index(index) => value.get(index),
+                })
+                .cloned()
+                .unwrap_or(Value::Null),
+            Expr::Not(expr) => Value::Bool(!truthy(&expr.evaluate(variables))),
+            Expr::And(left, right) => {
+                Value::Bool(truthy(&left.evaluate(variables)) && truthy(&right.evaluate(variables)))
+            }
+            Expr::Or(left, right) => {
+                Value::Bool(truthy(&left.evaluate(variables)) || truthy(&right.evaluate(variables)))
+            }
+            Expr::Compare(left, op, right) => Value::Bool(compare(
+                &left.evaluate(variables),
+                *op,
+                &right.evaluate(variables),
+            )),
+            Expr::Matches(expr, regex) => Value::Bool(
+                expr.evaluate(variables)
+                    .as_str()
+                    .is_some_and(|text| regex.is_match(text)),
+            ),
+        }
+    }
+}
+
+/// Whether a value counts as true: `false`, `null`, `0` and empty strings,
+/// arrays and objects do not
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+
+    match op {
+        CompareOp::Eq => ordering.map_or(left == right, |o| o == Ordering::Equal),
+        CompareOp::Ne => !compare(left, CompareOp::Eq, right),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        CompareOp::Contains => match (left, right) {
+            (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+            (Value::Array(items), value) => {
+                items.iter().any(|item| compare(item, CompareOp::Eq, value))
+            }
+            (Value::Object(fields), Value::String(key)) => fields.contains_key(key),
+            _ => false,
+        },
+    }
+}
+
+/// Recursive-descent parser over the tokens of one rule
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+type ParseResult = std::result::Result<Expr, String>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume the next token if it is the symbol or keyword `expected`
+    fn eat(&mut self, expected: &str) -> bool {
+        let found = match self.peek() {
+            Some(Token::Sym(sym)) => *sym == expected,
+            Some(Token::Ident(word)) => word == expected,
+            _ => false,
+        };
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn nest(&mut self) -> std::result::Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("condition is nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> ParseResult {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> ParseResult {
+        let mut left = self.not()?;
+        while self.eat("&&") {
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> ParseResult {
+        if self.eat("!") {
+            self.nest()?;
+            let expr = Expr::Not(Box::new(self.not()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> ParseResult {
+        let left = self.operand()?;
+        let op = match self.peek() {
+            Some(Token::Sym("==")) => CompareOp::Eq,
+            Some(Token::Sym("!=")) => CompareOp::Ne,
+            Some(Token::Sym("<")) => CompareOp::Lt,
+            Some(Token::Sym("<=")) => CompareOp::Le,
+            Some(Token::Sym(">")) => CompareOp::Gt,
+            Some(Token::Sym(">=")) => CompareOp::Ge,
+            Some(Token::Ident(word)) if word == "contains" => CompareOp::Contains,
+            Some(Token::Ident(word)) if word == "matches" => {
+                self.pos += 1;
+                let pattern = match self.next() {
+                    Some(Token::Str(pattern)) => pattern,
+                    _ => return Err("'matches' needs a quoted regex".to_string()),
+                };
+                let regex = Regex::new(&pattern)
+                    .map_err(|e| format!("invalid regex '{}': {}", pattern, e))?;
+                return Ok(Expr::Matches(Box::new(left), regex));
+            }
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.operand()?;
+        Ok(Expr::Compare(Box::new(left), op, Box::new(right)))
+    }
+
+    fn operand(&mut self) -> ParseResult {
+        match self.next() {
+            Some(Token::Sym("(")) => {
+                self.nest()?;
+                let expr = self.or()?;
+                if !self.eat(")") {
+                    return Err("expected ')'".to_string());
+                }
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Some(Token::Str(text)) => Ok(Expr::Literal(Value::String(text))),
+            Some(Token::Num(n)) => Ok(Expr::Literal(
+                Number::from_f64(n).map_or(Value::Null, Value::Number),
+            )),
+            Some(Token::Ident(word)) => match word.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                name if VARIABLES.contains(&name) => self.variable(word),
+                name => Err(format!(
+                    "unknown variable '{}' (expected one of: {})",
+                    name,
+                    VARIABLES.join(", ")
+                )),
+            },
+            Some(token) => Err(format!("expected a value, found {}", token)),
+            None => Err("expected a value".to_string()),
+        }
+    }
+
+    fn variable(&mut self, root: String) -> ParseResult {
+        let mut path = vec![Segment::Field(root)];
+        loop {
+            if self.eat(".") {
+                match self.next() {
+                    Some(Token::Ident(field)) => path.push(Segment::Field(field)),
+                    _ => return Err("expected a field name after '.'".to_string()),
+                }
+            } else if self.eat("[") {
+                match self.next() {
+                    Some(Token::Num(n)) if n >= 0.0 && n.fract() == 0.0 => {
+                        path.push(Segment::Index(n as usize))
+                    }
+                    _ => return Err("expected an index after '['".to_string()),
+                }
+                if !self.eat("]") {
+                    return Err("expected ']'".to_string());
+                }
+            } else {
+                return Ok(Expr::Variable(path));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rules_route_on_variables() {
+        let rules = RouteRules::parse(
+            r#"
+            # Specialists first
+            json.category == "billing" && json.priority >= 2 => billing
+            output matches "(?i)\brefund" => refunds
+            json.tags contains 'outage' || json.items[1].kind == "bug" => technical
+            !success || cost > 0.5 => review
+            else => general
+            "#,
+        )
+        .unwrap();
+
+        let route = |output: &str, data: Value, success: bool, cost: f64| {
+            let variables = json!({
+                "output": output,
+                "json": data,
+                "success": success,
+                "cost": cost,
+                "tokens": 10,
+            });
+            rules.route(&variables).unwrap().to_string()
+        };
+        assert_eq!(
+            route("", json!({"category": "billing", "priority": 2}), true, 0.0),
+            "billing"
+        );
+        assert_eq!(
+            route("", json!({"category": "billing", "priority": 1}), true, 0.0),
+            "general"
+        );
+        assert_eq!(
+            route("I want a Refund now", Value::Null, true, 0.0),
+            "refunds"
+        );
+        assert_eq!(
+            route("", json!({"tags": ["outage"]}), true, 0.0),
+            "technical"
+        );
+        assert_eq!(
+            route(
+                "",
+                json!({"items": [{"kind": "x"}, {"kind": "bug"}]}),
+                true,
+                0.0
+            ),
+            "technical"
+        );
+        assert_eq!(route("anything", Value::Null, false, 0.0), "review");
+        assert_eq!(route("anything", Value::Null, true, 0.9), "review");
+        assert_eq!(route("anything", Value::Null, true, 0.1), "general");
+
+        let targets: Vec<&str> = rules.targets().collect();
+        assert_eq!(
+            targets,
+            ["billing", "refunds", "technical", "review", "general"]
+        );
+    }
+
+    #[test]
+    fn test_no_rule_holds() {
+        let rules = RouteRules::parse("tokens > 1000 => summarize").unwrap();
+        assert!(rules.route(&json!({"tokens": 10})).is_none());
+
+        // Mismatched types and missing fields never compare as true
+        let rules = RouteRules::parse("output > 5 => a\njson > 5 => b\njson.n == 0 => c").unwrap();
+        assert_eq!(rules.route(&json!({"output": "10", "json": 10})), Some("b"));
+        assert!(rules.route(&json!({"output": "text"})).is_none());
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let invalid = [
+            "",
+            "success",
+            "success =>",
+            "succes => a",
+            "output matches \"(\" => a",
+            "output matches other => a",
+            "(success => a",
+            "\"open => a",
+            "success => a b",
+            "output ~ 'x' => a",
+            "json.x = 1 => a",
+        ];
+        for logic in invalid {
+            assert!(RouteRules::parse(logic).is_err(), "{}", logic);
+        }
+
+        let deep = format!("{}success{} => a", "(".repeat(40), ")".repeat(40));
+        assert!(RouteRules::parse(&deep).is_err());
+    }
+}
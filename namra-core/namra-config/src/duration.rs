@@ -0,0 +1,46 @@
+//! Duration strings used in agent configuration
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+/// Parse a duration such as `500ms`, `30s`, `5m` or `2h`
+///
+/// A number without a unit is read as seconds.
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+
+    // Check "ms" before "s" since "ms" ends with "s"
+    let (number, unit_ms) = if let Some(n) = value.strip_suffix("ms") {
+        (n, 1)
+    } else if let Some(n) = value.strip_suffix('s') {
+        (n, 1_000)
+    } else if let Some(n) = value.strip_suffix('m') {
+        (n, 60_000)
+    } else if let Some(n) = value.strip_suffix('h') {
+        (n, 3_600_000)
+    } else {
+        (value, 1_000)
+    };
+
+    let number: u64 = number
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid duration: {}", value))?;
+
+    Ok(Duration::from_millis(number * unit_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration(" 10 ").unwrap(), Duration::from_secs(10));
+        assert!(parse_duration("soon").is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! Workflow graph analysis and diagrams
+
+use crate::{WorkflowConfig, WorkflowEdge};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Graph of a workflow's nodes and edges
+///
+/// Used to check that a workflow hangs together and to draw it as a Mermaid
+/// or Graphviz DOT diagram.
+pub struct WorkflowGraph<'a> {
+    config: &'a WorkflowConfig,
+}
+
+/// One way an edge can lead from one node to another
+struct Link<'a> {
+    from: &'a str,
+    to: &'a str,
+    /// Condition or route description that leads to `to`
+    label: Option<&'a str>,
+    /// Whether the edge is an `llm_router`
+    routed: bool,
+}
+
+impl<'a> WorkflowGraph<'a> {
+    pub fn new(config: &'a WorkflowConfig) -> Self {
+        Self { config }
+    }
+
+    /// Every (from, to) step the edges allow, in edge order
+    fn links(&self) -> Vec<Link<'a>> {
+        self.config
+            .edges
+            .iter()
+            .flat_map(|edge| {
+                let routed = matches!(
+                    edge,
+                    WorkflowEdge::Conditional { to, .. } if to.target_type == "llm_router"
+                );
+                edge.branches().into_iter().map(move |(to, label)| Link {
+                    from: edge.source(),
+                    to,
+                    label,
+                    routed,
+                })
+            })
+            .collect()
+    }
+
+    /// Nodes each node can lead to, optionally over direct edges only
+    fn successors(&self, direct_only: bool) -> HashMap<&'a str, Vec<&'a str>> {
+        let mut successors: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.config.edges {
+            if direct_only && !matches!(edge, WorkflowEdge::Direct { .. }) {
+                continue;
+            }
+            for (to, _) in edge.branches() {
+                successors.entry(edge.source()).or_default().push(to);
+            }
+        }
+        successors
+    }
+
+    /// Nodes reachable from `from` in one or more steps
+    fn reach(successors: &HashMap<&'a str, Vec<&'a str>>, from: &str) -> BTreeSet<&'a str> {
+        let mut seen = BTreeSet::new();
+        let mut queue: VecDeque<&str> = successors
+            .get(from)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect();
+        while let Some(node) = queue.pop_front() {
+            if seen.insert(node) {
+                queue.extend(successors.get(node).into_iter().flatten());
+            }
+        }
+        seen
+    }
+
+    /// Whether `to` can be reached from the entry point
+    pub fn reachable(&self, to: &str) -> bool {
+        to == self.config.entry_point
+            || Self::reach(&self.successors(false), &self.config.entry_point).contains(to)
+    }
+
+    /// Nodes that can't be reached from the entry point, in node order
+    pub fn unreachable(&self) -> Vec<&'a str> {
+        let successors = self.successors(false);
+        let reached = Self::reach(&successors, &self.config.entry_point);
+        self.config
+            .nodes
+            .iter()
+            .map(|n| n.id.as_str())
+            .filter(|id| *id != self.config.entry_point && !reached.contains(id))
+            .collect()
+    }
+
+    /// Groups of nodes that can each lead back to themselves through the
+    /// others, in node order
+    ///
+    /// With `direct_only`, only cycles made of direct edges count: a run
+    /// that enters one never leaves it.
+    pub fn cycles(&self, direct_only: bool) -> Vec<Vec<&'a str>> {
+        let successors = self.successors(direct_only);
+        let reach: HashMap<&str, BTreeSet<&str>> = self
+            .config
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), Self::reach(&successors, &n.id)))
+            .collect();
+
+        let mut cycles = Vec::new();
+        let mut grouped = BTreeSet::new();
+        for node in &self.config.nodes {
+            let id = node.id.as_str();
+            if grouped.contains(id) || !reach[id].contains(id) {
+                continue;
+            }
+            let group: Vec<&str> = self
+                .config
+                .nodes
+                .iter()
+                .map(|n| n.id.as_str())
+                .filter(|other| reach[id].contains(other) && reach[other].contains(id))
+                .collect();
+            grouped.extend(group.iter().copied());
+            cycles.push(group);
+        }
+        cycles
+    }
+
+    /// Mermaid flowchart of the workflow
+    ///
+    /// Entry and exit points are drawn as stadiums, join nodes as hexagons,
+    /// and `llm_router` edges dashed.
+    pub fn to_mermaid(&self) -> String {
+        let ids: HashMap<&str, String> = self
+            .config
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, n)| (n.id.as_str(), mermaid_id(idx, &n.id)))
+            .collect();
+        let id = |node: &str| ids.get(node).cloned().unwrap_or_else(|| node.to_string());
+
+        let mut out = String::from("flowchart TD\n");
+        for node in &self.config.nodes {
+            let label = mermaid_text(&node.id);
+            let shape = if node.id == self.config.entry_point || node.id == self.config.exit_point {
+                format!("([\"{}\"])", label)
+            } else if node.node_type == "join" {
+                format!("{{{{\"{}\"}}}}", label)
+            } else {
+                format!("[\"{}\"]", label)
+            };
+            out.push_str(&format!("    {}{}\n", id(&node.id), shape));
+        }
+        for link in self.links() {
+            let arrow = if link.routed { "-.->" } else { "-->" };
+            let label = link
+                .label
+                .map(|l| format!("|\"{}\"|", mermaid_text(l)))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "    {} {}{} {}\n",
+                id(link.from),
+                arrow,
+                label,
+                id(link.to)
+            ));
+        }
+        out
+    }
+
+    /// Graphviz DOT digraph of the workflow
+    pub fn to_dot(&self) -> String {
+        let mut out = format!(
+            "digraph \"{}\" {{\n    node [shape=box];\n",
+            dot_text(&self.config.name)
+        );
+        for node in &self.config.nodes {
+            let mut attrs = Vec::new();
+            if node.node_type == "join" {
+                attrs.push("shape=hexagon");
+            }
+            if node.id == self.config.entry_point {
+                attrs.push("style=bold");
+            }
+            if node.id == self.config.exit_point {
+                attrs.push("peripheries=2");
+            }
+            let attrs = if attrs.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attrs.join(", "))
+            };
+            out.push_str(&format!("    \"{}\"{};\n", dot_text(&node.id), attrs));
+        }
+        for link in self.links() {
+            let mut attrs = Vec::new();
+            if let Some(label) = link.label {
+                attrs.push(format!("label=\"{}\"", dot_text(label)));
+            }
+            if link.routed {
+                attrs.push("style=dashed".to_string());
+            }
+            let attrs = if attrs.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attrs.join(", "))
+            };
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\"{};\n",
+                dot_text(link.from),
+                dot_text(link.to),
+                attrs
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Mermaid ID for a node: the node ID if Mermaid can take it as is
+fn mermaid_id(idx: usize, id: &str) -> String {
+    let plain = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    // `end` closes a subgraph in Mermaid
+    if plain && !id.eq_ignore_ascii_case("end") {
+        id.to_string()
+    } else {
+        format!("node{}", idx)
+    }
+}
+
+/// Text for a quoted Mermaid label
+fn mermaid_text(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', " ")
+}
+
+/// Text for a quoted DOT string
+fn dot_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(yaml: &str) -> WorkflowConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    const REVIEW: &str = r#"
+name: review
+version: 1.0.0
+entry_point: draft
+exit_point: publish
+nodes:
+  - {id: draft, type: agent}
+  - {id: check, type: agent}
+  - {id: publish, type: agent}
+  - {id: end, type: agent}
+edges:
+  - {from: draft, to: check}
+  - from: check
+    to:
+      type: expression
+      logic: |
+        json.approved == "yes" => publish
+        else => draft
+  - {from: end, to: publish}
+"#;
+
+    #[test]
+    fn test_reachability_and_cycles() {
+        let config = workflow(REVIEW);
+        let graph = WorkflowGraph::new(&config);
+
+        assert!(graph.reachable("publish"));
+        assert_eq!(graph.unreachable(), ["end"]);
+        assert_eq!(graph.cycles(false), [vec!["draft", "check"]]);
+        assert!(graph.cycles(true).is_empty());
+
+        let config = workflow(&REVIEW.replace("{from: end, to: publish}", "{from: end, to: end}"));
+        assert_eq!(WorkflowGraph::new(&config).cycles(true), [vec!["end"]]);
+    }
+
+    #[test]
+    fn test_diagrams() {
+        let config = workflow(REVIEW);
+        let graph = WorkflowGraph::new(&config);
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("    draft([\"draft\"])\n"));
+        assert!(mermaid.contains("    node3[\"end\"]\n"));
+        assert!(mermaid.contains("    check -->|\"json.approved == #quot;yes#quot;\"| publish\n"));
+        assert!(mermaid.contains("    check -->|\"else\"| draft\n"));
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph \"review\" {\n"));
+        assert!(dot.contains("    \"publish\" [peripheries=2];\n"));
+        assert!(dot
+            .contains("    \"check\" -> \"publish\" [label=\"json.approved == \\\"yes\\\"\"];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
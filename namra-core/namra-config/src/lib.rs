@@ -4,27 +4,55 @@
 //! configuration files for agents, workflows, and platform settings.
 
 mod agent;
+mod condition;
+mod duration;
+mod graph;
 mod parser;
+mod rate;
+mod secrets;
 mod validator;
 mod workflow;
 
 pub use agent::{
-    AgentConfig, ExecutionConfig, FileSystemBackend, FileSystemToolConfig, FileSystemType,
-    HttpToolConfig, LLMConfig, MemoryConfig, MiddlewareConfig, ToolConfig,
+    AgentConfig, AuditLogConfig, CachingConfig, CircuitBreakerConfig, ExecutionConfig,
+    FileSystemBackend, FileSystemToolConfig, FileSystemType, GovernanceConfig, GuardRule,
+    HttpToolConfig, LLMConfig, MemoryConfig, MiddlewareConfig, PlanExecuteConfig, ResilienceConfig,
+    ResourceQuota, RetryConfig, SecurityConfig, SummarizationConfig, TenancyConfig, ToolConfig,
+    TreeOfThoughtsConfig,
 };
+pub use condition::RouteRules;
+pub use duration::parse_duration;
+pub use graph::WorkflowGraph;
 pub use parser::{ConfigFormat, ConfigParser};
+pub use rate::{parse_rate_limit, RateLimit};
+pub use secrets::{
+    EnvBackend, FileBackend, KeyringBackend, SecretBackend, SecretError, SecretResolver,
+    SecretValue,
+};
 pub use validator::ConfigValidator;
-pub use workflow::{WorkflowConfig, WorkflowEdge, WorkflowNode};
+pub use workflow::{
+    CheckpointConfig, ConditionalTarget, JoinConfig, MergePolicy, WorkflowConfig, WorkflowEdge,
+    WorkflowNode,
+};
 
 use anyhow::Result;
 use std::path::Path;
 
 /// Parse an agent configuration from a file
+///
+/// Secret references are left unresolved, so a configuration can be checked
+/// without its secrets; use [`load_agent_config`] to run it.
 pub fn parse_agent_config<P: AsRef<Path>>(path: P) -> Result<AgentConfig> {
     let parser = ConfigParser::new();
     parser.parse_agent(path)
 }
 
+/// Parse an agent configuration from a file and resolve its secrets
+pub fn load_agent_config<P: AsRef<Path>>(path: P) -> Result<AgentConfig> {
+    let parser = ConfigParser::new();
+    parser.load_agent(path)
+}
+
 /// Parse a workflow configuration from a file
 pub fn parse_workflow_config<P: AsRef<Path>>(path: P) -> Result<WorkflowConfig> {
     let parser = ConfigParser::new();
@@ -36,3 +64,10 @@ pub fn validate_config(config: &AgentConfig) -> Result<()> {
     let validator = ConfigValidator::new();
     validator.validate(config)
 }
+
+/// Validate a workflow configuration, resolving `agent_config` paths
+/// against `base_dir`
+pub fn validate_workflow_config(config: &WorkflowConfig, base_dir: &Path) -> Result<()> {
+    let validator = ConfigValidator::new();
+    validator.validate_workflow(config, base_dir)
+}
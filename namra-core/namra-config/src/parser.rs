@@ -1,6 +1,6 @@
 //! Configuration file parser
 
-use crate::{AgentConfig, WorkflowConfig};
+use crate::{AgentConfig, SecretResolver, WorkflowConfig};
 use anyhow::{Context, Result};
 use std::fs;
 use std::path::Path;
@@ -23,14 +23,27 @@ impl ConfigFormat {
 }
 
 /// Configuration file parser
-pub struct ConfigParser;
+///
+/// [`parse_agent`](Self::parse_agent) leaves secret references unresolved,
+/// which is enough to check a configuration;
+/// [`load_agent`](Self::load_agent) also resolves them, for running it.
+#[derive(Debug, Default)]
+pub struct ConfigParser {
+    secrets: SecretResolver,
+}
 
 impl ConfigParser {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Resolve secrets with `resolver` instead of the built-in backends
+    pub fn with_secret_resolver(mut self, resolver: SecretResolver) -> Self {
+        self.secrets = resolver;
+        self
     }
 
-    /// Parse an agent configuration from a file
+    /// Parse an agent configuration from a file, without resolving secrets
     pub fn parse_agent<P: AsRef<Path>>(&self, path: P) -> Result<AgentConfig> {
         let path = path.as_ref();
         let content = fs::read_to_string(path)
@@ -44,6 +57,13 @@ impl ConfigParser {
         }
     }
 
+    /// Parse an agent configuration from a file and resolve its secrets
+    pub fn load_agent<P: AsRef<Path>>(&self, path: P) -> Result<AgentConfig> {
+        let mut config = self.parse_agent(path)?;
+        self.secrets.resolve_config(&mut config)?;
+        Ok(config)
+    }
+
     /// Parse a workflow configuration from a file
     pub fn parse_workflow<P: AsRef<Path>>(&self, path: P) -> Result<WorkflowConfig> {
         let path = path.as_ref();
@@ -58,6 +78,32 @@ impl ConfigParser {
         }
     }
 
+    /// Whether a file holds a workflow rather than an agent configuration
+    ///
+    /// Workflows are recognized by their `nodes` and `entry_point` fields.
+    pub fn is_workflow<P: AsRef<Path>>(&self, path: P) -> Result<bool> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+
+        let keys: Vec<String> = match self.detect_format(path)? {
+            ConfigFormat::Yaml => match serde_yaml::from_str(&content) {
+                Ok(serde_yaml::Value::Mapping(map)) => map
+                    .keys()
+                    .filter_map(|k| k.as_str().map(str::to_string))
+                    .collect(),
+                _ => Vec::new(),
+            },
+            ConfigFormat::Toml => match toml::from_str(&content) {
+                Ok(toml::Value::Table(table)) => table.keys().cloned().collect(),
+                _ => Vec::new(),
+            },
+        };
+        Ok(["nodes", "entry_point"]
+            .iter()
+            .all(|key| keys.iter().any(|k| k == key)))
+    }
+
     fn detect_format<P: AsRef<Path>>(&self, path: P) -> Result<ConfigFormat> {
         let path = path.as_ref();
         let extension = path
@@ -86,12 +132,6 @@ impl ConfigParser {
     }
 }
 
-impl Default for ConfigParser {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,4 +152,30 @@ mod tests {
         ));
         assert!(ConfigFormat::from_extension("json").is_none());
     }
+
+    #[test]
+    fn test_secrets_resolve_only_on_load() {
+        let path = std::env::temp_dir().join(format!("namra-parser-{}.yaml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+name: test
+version: "1.0.0"
+system_prompt: test
+llm:
+  provider: anthropic
+  model: claude
+  api_key: "${env:NAMRA_TEST_UNSET_KEY}"
+"#,
+        )
+        .unwrap();
+        let parser = ConfigParser::new();
+
+        let config = parser.parse_agent(&path).unwrap();
+        let api_key = config.llm.api_key.unwrap();
+        assert!(!api_key.is_resolved());
+        assert_eq!(api_key.expose(), None);
+        assert!(parser.load_agent(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
 }
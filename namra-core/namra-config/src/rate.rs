@@ -0,0 +1,88 @@
+//! Rate limit strings used in agent configuration
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+/// A number of requests allowed per period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+impl RateLimit {
+    /// Limit of `requests` per hour
+    pub fn per_hour(requests: u32) -> Self {
+        Self {
+            requests,
+            period: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Parse a rate limit such as `100/minute`, `10/s` or `5000/hour`
+///
+/// The period is `second`, `minute`, `hour` or `day` (or `s`, `m`, `h`,
+/// `d`, or a plural), and the count must be greater than 0.
+pub fn parse_rate_limit(value: &str) -> Result<RateLimit> {
+    let value = value.trim();
+    let (count, unit) = value
+        .split_once('/')
+        .with_context(|| format!("Invalid rate limit '{}': expected <count>/<period>", value))?;
+
+    let requests: u32 = count
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid rate limit count: {}", count.trim()))?;
+    if requests == 0 {
+        anyhow::bail!("Rate limit count must be greater than 0");
+    }
+
+    let unit = unit.trim().to_lowercase();
+    // Only whole words take a plural `s`, so `ms` is not read as `m`
+    let singular = match unit.strip_suffix('s') {
+        Some(word) if word.len() > 1 => word,
+        _ => unit.as_str(),
+    };
+    let secs = match singular {
+        "s" | "sec" | "second" => 1,
+        "m" | "min" | "minute" => 60,
+        "h" | "hr" | "hour" => 3_600,
+        "d" | "day" => 86_400,
+        _ => anyhow::bail!(
+            "Invalid rate limit period '{}': use second, minute, hour or day",
+            unit.trim()
+        ),
+    };
+
+    Ok(RateLimit {
+        requests,
+        period: Duration::from_secs(secs),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate_limit() {
+        let limit = parse_rate_limit("100/minute").unwrap();
+        assert_eq!(limit.requests, 100);
+        assert_eq!(limit.period, Duration::from_secs(60));
+
+        assert_eq!(parse_rate_limit("10/s").unwrap().period.as_secs(), 1);
+        assert_eq!(
+            parse_rate_limit(" 5000 / Hours ").unwrap(),
+            RateLimit::per_hour(5000)
+        );
+        assert_eq!(parse_rate_limit("1/day").unwrap().period.as_secs(), 86_400);
+
+        assert!(parse_rate_limit("100").is_err());
+        assert!(parse_rate_limit("0/minute").is_err());
+        assert!(parse_rate_limit("100/fortnight").is_err());
+        assert!(parse_rate_limit("10/ms").is_err());
+        assert!(parse_rate_limit("10/").is_err());
+        assert_eq!(parse_rate_limit("10/mins").unwrap().period.as_secs(), 60);
+    }
+}
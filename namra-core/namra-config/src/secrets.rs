@@ -0,0 +1,486 @@
+//! Secret values and secret references in configuration
+//!
+//! Credential fields (`llm.api_key`, HTTP tool `auth` and `headers`, and
+//! filesystem `credentials`) are [`SecretValue`]s. Their text may contain
+//! references that are expanded when the config is loaded to run it, so the
+//! credential itself never has to be written into the agent file:
+//!
+//! - `${env:GITHUB_TOKEN}` reads an environment variable
+//! - `${file:/run/secrets/github}` reads a file (without its trailing newline)
+//! - `${keyring:namra/github}` reads the `github` account of the `namra`
+//!   service from the OS keyring
+//! - `${GITHUB_TOKEN}` uses `security.secrets_backend` (`env` by default)
+//!
+//! References can be embedded in other text (`Bearer ${env:TOKEN}`), and
+//! `$${` writes a literal `${`. Other backends, such as a vault client, are
+//! added with [`SecretResolver::with_backend`].
+
+use crate::{AgentConfig, FileSystemBackend, ToolConfig};
+use anyhow::Context;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::process::Command;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Errors resolving secret references
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("Unknown secret backend '{0}'")]
+    UnknownBackend(String),
+
+    #[error("Secret '{key}' not found in {backend}: {reason}")]
+    NotFound {
+        backend: String,
+        key: String,
+        reason: String,
+    },
+
+    #[error("Invalid secret reference '{0}'")]
+    InvalidReference(String),
+}
+
+/// Source of secret values, such as the environment or a vault
+pub trait SecretBackend: Send + Sync {
+    /// Look up the secret stored under `key`
+    fn get(&self, key: &str) -> Result<SecretString, SecretError>;
+}
+
+/// Secrets from environment variables
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvBackend;
+
+impl SecretBackend for EnvBackend {
+    fn get(&self, key: &str) -> Result<SecretString, SecretError> {
+        std::env::var(key)
+            .map(SecretString::new)
+            .map_err(|e| not_found("env", key, e))
+    }
+}
+
+/// Secrets stored in files, one per file
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileBackend;
+
+impl SecretBackend for FileBackend {
+    fn get(&self, key: &str) -> Result<SecretString, SecretError> {
+        let content = std::fs::read_to_string(key).map_err(|e| not_found("file", key, e))?;
+        Ok(SecretString::new(
+            content.trim_end_matches(['\n', '\r']).to_string(),
+        ))
+    }
+}
+
+/// Secrets from the OS keyring, addressed as `service/account`
+///
+/// Uses `security` (the macOS keychain) or `secret-tool` (the Secret
+/// Service on Linux).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyringBackend;
+
+impl SecretBackend for KeyringBackend {
+    fn get(&self, key: &str) -> Result<SecretString, SecretError> {
+        let (service, account) = key
+            .split_once('/')
+            .filter(|(service, account)| !service.is_empty() && !account.is_empty())
+            .ok_or_else(|| SecretError::InvalidReference(format!("keyring:{}", key)))?;
+
+        let mut command = if cfg!(target_os = "macos") {
+            let mut command = Command::new("security");
+            command.args(["find-generic-password", "-w", "-s", service, "-a", account]);
+            command
+        } else {
+            let mut command = Command::new("secret-tool");
+            command.args(["lookup", "service", service, "account", account]);
+            command
+        };
+
+        let output = command.output().map_err(|e| not_found("keyring", key, e))?;
+        if !output.status.success() {
+            return Err(not_found("keyring", key, "no matching entry"));
+        }
+        let value = String::from_utf8(output.stdout).map_err(|e| not_found("keyring", key, e))?;
+        Ok(SecretString::new(
+            value.trim_end_matches(['\n', '\r']).to_string(),
+        ))
+    }
+}
+
+fn not_found(backend: &str, key: &str, reason: impl fmt::Display) -> SecretError {
+    SecretError::NotFound {
+        backend: backend.to_string(),
+        key: key.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Credential from the configuration
+///
+/// Holds the text as written (which is what gets serialized) and, once
+/// resolved, the value with its references expanded. `Debug` never shows
+/// either.
+#[derive(Clone)]
+pub struct SecretValue {
+    raw: String,
+    resolved: Option<SecretString>,
+}
+
+impl SecretValue {
+    /// Secret with the given text, resolved when it contains no references
+    pub fn new(raw: impl Into<String>) -> Self {
+        let raw = raw.into();
+        let resolved = (!has_reference(&raw)).then(|| SecretString::new(unescape(&raw)));
+        Self { raw, resolved }
+    }
+
+    /// Text as written in the configuration
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Whether the text refers to a backend rather than holding the value
+    pub fn is_reference(&self) -> bool {
+        has_reference(&self.raw)
+    }
+
+    /// Whether the value is available
+    pub fn is_resolved(&self) -> bool {
+        self.resolved.is_some()
+    }
+
+    /// The resolved value, or `None` for a reference that has not been
+    /// resolved yet
+    pub fn expose(&self) -> Option<&str> {
+        self.resolved
+            .as_ref()
+            .map(|value| value.expose_secret().as_str())
+    }
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretValue([REDACTED])")
+    }
+}
+
+impl From<&str> for SecretValue {
+    fn from(raw: &str) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl From<String> for SecretValue {
+    fn from(raw: String) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl Serialize for SecretValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Expands secret references using named backends
+#[derive(Clone)]
+pub struct SecretResolver {
+    backends: HashMap<String, Arc<dyn SecretBackend>>,
+    default_backend: String,
+}
+
+impl SecretResolver {
+    /// Resolver with the `env`, `file`, and `keyring` backends
+    pub fn new() -> Self {
+        Self {
+            backends: HashMap::new(),
+            default_backend: "env".to_string(),
+        }
+        .with_backend("env", EnvBackend)
+        .with_backend("file", FileBackend)
+        .with_backend("keyring", KeyringBackend)
+    }
+
+    /// Add (or replace) the backend used for `${name:...}` references
+    pub fn with_backend(
+        mut self,
+        name: impl Into<String>,
+        backend: impl SecretBackend + 'static,
+    ) -> Self {
+        self.backends.insert(name.into(), Arc::new(backend));
+        self
+    }
+
+    /// Backend used for references without a backend name, like `${TOKEN}`
+    pub fn with_default_backend(mut self, name: impl Into<String>) -> Self {
+        self.default_backend = name.into();
+        self
+    }
+
+    /// Expand every reference in `text`
+    pub fn expand(&self, text: &str) -> Result<SecretString, SecretError> {
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                expanded.push_str(&rest[..start - 1]);
+                expanded.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            expanded.push_str(&rest[..start]);
+
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| SecretError::InvalidReference(rest[start..].to_string()))?;
+            let reference = &after[..end];
+            let (backend, key) = match reference.split_once(':') {
+                Some((backend, key)) => (backend, key),
+                None => (self.default_backend.as_str(), reference),
+            };
+            if key.is_empty() {
+                return Err(SecretError::InvalidReference(format!("${{{}}}", reference)));
+            }
+
+            let value = self
+                .backends
+                .get(backend)
+                .ok_or_else(|| SecretError::UnknownBackend(backend.to_string()))?
+                .get(key)?;
+            expanded.push_str(value.expose_secret());
+            rest = &after[end + 1..];
+        }
+        expanded.push_str(rest);
+
+        Ok(SecretString::new(expanded))
+    }
+
+    /// Resolve a secret's references in place
+    pub fn resolve(&self, secret: &mut SecretValue) -> Result<(), SecretError> {
+        if !secret.is_resolved() {
+            secret.resolved = Some(self.expand(&secret.raw)?);
+        }
+        Ok(())
+    }
+
+    /// Resolve every secret in an agent configuration
+    ///
+    /// `security.secrets_backend`, when set, becomes the default backend.
+    pub fn resolve_config(&self, config: &mut AgentConfig) -> anyhow::Result<()> {
+        let secrets_backend = config
+            .middleware
+            .as_ref()
+            .and_then(|m| m.security.as_ref())
+            .and_then(|s| s.secrets_backend.clone());
+        let resolver = match secrets_backend {
+            Some(backend) if !self.backends.contains_key(&backend) => {
+                anyhow::bail!("Unknown security secrets_backend '{}'", backend);
+            }
+            Some(backend) => self.clone().with_default_backend(backend),
+            None => self.clone(),
+        };
+
+        for (field, secret) in config_secrets(config) {
+            resolver
+                .resolve(secret)
+                .with_context(|| format!("Cannot resolve {}", field))?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for SecretResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for SecretResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut backends: Vec<&String> = self.backends.keys().collect();
+        backends.sort();
+        f.debug_struct("SecretResolver")
+            .field("backends", &backends)
+            .field("default_backend", &self.default_backend)
+            .finish()
+    }
+}
+
+/// Every secret field of an agent configuration, with its path
+pub(crate) fn config_secrets(config: &mut AgentConfig) -> Vec<(String, &mut SecretValue)> {
+    let mut secrets = Vec::new();
+
+    if let Some(api_key) = config.llm.api_key.as_mut() {
+        secrets.push(("llm.api_key".to_string(), api_key));
+    }
+    if let Some(api_key) = config
+        .middleware
+        .as_mut()
+        .and_then(|m| m.resilience.as_mut())
+        .and_then(|r| r.fallback_llm.as_mut())
+        .and_then(|llm| llm.api_key.as_mut())
+    {
+        secrets.push(("resilience.fallback_llm.api_key".to_string(), api_key));
+    }
+
+    for tool in &mut config.tools {
+        match tool {
+            ToolConfig::BuiltinHttp { name, config } => {
+                if let Some(auth) = config.auth.as_mut() {
+                    secrets.push((format!("tool '{}' auth", name), auth));
+                }
+                for (header, value) in config.headers.iter_mut() {
+                    secrets.push((format!("tool '{}' header {}", name, header), value));
+                }
+            }
+            ToolConfig::BuiltinFilesystem { name, config } => match &mut config.backend {
+                FileSystemBackend::Local { .. } => {}
+                FileSystemBackend::S3 { credentials, .. }
+                | FileSystemBackend::GCS { credentials, .. }
+                | FileSystemBackend::Azure { credentials, .. }
+                | FileSystemBackend::SFTP { credentials, .. } => {
+                    if let Some(credentials) = credentials.as_mut() {
+                        secrets.push((format!("tool '{}' credentials", name), credentials));
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    secrets
+}
+
+fn has_reference(text: &str) -> bool {
+    text.match_indices("${")
+        .any(|(i, _)| !text[..i].ends_with('$'))
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("$${", "${")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MapBackend(HashMap<&'static str, &'static str>);
+
+    impl SecretBackend for MapBackend {
+        fn get(&self, key: &str) -> Result<SecretString, SecretError> {
+            self.0
+                .get(key)
+                .map(|v| SecretString::new(v.to_string()))
+                .ok_or_else(|| not_found("vault", key, "missing"))
+        }
+    }
+
+    fn resolver() -> SecretResolver {
+        SecretResolver::new().with_backend(
+            "vault",
+            MapBackend(HashMap::from([("github/token", "ghp_abc")])),
+        )
+    }
+
+    #[test]
+    fn test_expand_references() {
+        let dir = std::env::temp_dir().join(format!("namra-secret-{}", std::process::id()));
+        std::fs::write(&dir, "from-file\n").unwrap();
+
+        let resolver = resolver();
+        let text = format!("Bearer ${{vault:github/token}} ${{file:{}}}", dir.display());
+        assert_eq!(
+            resolver.expand(&text).unwrap().expose_secret(),
+            "Bearer ghp_abc from-file"
+        );
+        std::fs::remove_file(&dir).unwrap();
+
+        assert_eq!(
+            resolver.expand("cost: $${price}").unwrap().expose_secret(),
+            "cost: ${price}"
+        );
+        assert!(matches!(
+            resolver.expand("${nope:x}"),
+            Err(SecretError::UnknownBackend(_))
+        ));
+        assert!(matches!(
+            resolver.expand("${vault:missing}"),
+            Err(SecretError::NotFound { .. })
+        ));
+        assert!(matches!(
+            resolver.expand("${env:UNTERMINATED"),
+            Err(SecretError::InvalidReference(_))
+        ));
+    }
+
+    #[test]
+    fn test_secret_value_is_redacted() {
+        let mut secret = SecretValue::new("token ${vault:github/token}");
+        assert!(secret.is_reference());
+        assert!(!secret.is_resolved());
+        assert_eq!(secret.expose(), None);
+
+        resolver().resolve(&mut secret).unwrap();
+        assert_eq!(secret.expose(), Some("token ghp_abc"));
+        assert_eq!(format!("{:?}", secret), "SecretValue([REDACTED])");
+
+        // Serializing writes the reference, not the value
+        assert_eq!(
+            serde_json::to_string(&secret).unwrap(),
+            "\"token ${vault:github/token}\""
+        );
+
+        let literal = SecretValue::new("ghp_literal");
+        assert!(!literal.is_reference());
+        assert_eq!(literal.expose(), Some("ghp_literal"));
+    }
+
+    #[test]
+    fn test_resolve_config_uses_secrets_backend() {
+        let mut config: AgentConfig = serde_yaml::from_str(
+            r#"
+name: test
+version: "1.0.0"
+system_prompt: test
+llm:
+  provider: anthropic
+  model: claude
+  api_key: "${github/token}"
+tools:
+  - type: builtin.http
+    name: github
+    config:
+      url: https://api.github.com
+      method: GET
+      auth: "Bearer ${vault:github/token}"
+      headers:
+        X-Client: namra
+middleware:
+  security:
+    secrets_backend: vault
+"#,
+        )
+        .unwrap();
+
+        resolver().resolve_config(&mut config).unwrap();
+        assert_eq!(
+            config.llm.api_key.as_ref().unwrap().expose(),
+            Some("ghp_abc")
+        );
+        let ToolConfig::BuiltinHttp { config: http, .. } = &config.tools[0] else {
+            panic!("expected an HTTP tool");
+        };
+        assert_eq!(http.auth.as_ref().unwrap().expose(), Some("Bearer ghp_abc"));
+        assert_eq!(http.headers["X-Client"].expose(), Some("namra"));
+        assert!(!format!("{:?}", config).contains("ghp_abc"));
+    }
+}
@@ -1,9 +1,21 @@
 //! Configuration validator
 
-use crate::AgentConfig;
+use crate::secrets::config_secrets;
+use crate::{
+    parse_agent_config, parse_duration, parse_rate_limit, AgentConfig, MergePolicy, RouteRules,
+    WorkflowConfig, WorkflowEdge, WorkflowGraph,
+};
 use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::Path;
 use validator::Validate;
 
+/// Node types a workflow can run
+const NODE_TYPES: [&str; 2] = ["agent", "join"];
+
+/// Kinds of conditional edge
+const EDGE_TYPES: [&str; 2] = ["expression", "llm_router"];
+
 /// Configuration validator
 pub struct ConfigValidator;
 
@@ -23,7 +35,46 @@ impl ConfigValidator {
         self.validate_llm_config(config)?;
         self.validate_tools(config)?;
         self.validate_execution_config(config)?;
+        self.validate_memory_config(config)?;
+        self.validate_middleware_config(config)?;
+        self.validate_tenancy(config)?;
+
+        Ok(())
+    }
+
+    /// Problems that don't make the configuration invalid
+    ///
+    /// Reports credentials written into the file instead of referenced with
+    /// `${env:...}` and similar, since agent files tend to end up in version
+    /// control.
+    pub fn warnings(&self, config: &AgentConfig) -> Vec<String> {
+        let mut config = config.clone();
+        config_secrets(&mut config)
+            .into_iter()
+            .filter(|(field, secret)| {
+                let credential = match field.rsplit_once(" header ") {
+                    Some((_, header)) => is_credential_header(header),
+                    None => true,
+                };
+                credential && !secret.is_reference() && !secret.raw().is_empty()
+            })
+            .map(|(field, _)| {
+                format!(
+                    "{} holds a literal secret; use a reference such as ${{env:NAME}}",
+                    field
+                )
+            })
+            .collect()
+    }
 
+    fn validate_tenancy(&self, config: &AgentConfig) -> Result<()> {
+        let quota = config
+            .tenancy
+            .as_ref()
+            .and_then(|t| t.resource_quota.as_ref());
+        if quota.is_some_and(|q| q.max_requests_per_hour == 0) {
+            anyhow::bail!("Tenancy resource_quota max_requests_per_hour must be greater than 0");
+        }
         Ok(())
     }
 
@@ -45,6 +96,161 @@ impl ConfigValidator {
             }
         }
 
+        if let Some(retry) = &config.llm.retry {
+            if retry.max_attempts == 0 {
+                anyhow::bail!("LLM retry max_attempts must be greater than 0");
+            }
+
+            let valid_backoffs = ["exponential", "linear", "constant"];
+            if !valid_backoffs.contains(&retry.backoff.as_str()) {
+                anyhow::bail!(
+                    "Invalid LLM retry backoff: {}. Must be one of: {}",
+                    retry.backoff,
+                    valid_backoffs.join(", ")
+                );
+            }
+
+            parse_duration(&retry.initial_delay).context("Invalid LLM retry initial_delay")?;
+            parse_duration(&retry.max_delay).context("Invalid LLM retry max_delay")?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_memory_config(&self, config: &AgentConfig) -> Result<()> {
+        let Some(memory) = &config.memory else {
+            return Ok(());
+        };
+
+        let valid_types = ["in_memory", "sqlite"];
+        if !valid_types.contains(&memory.memory_type.as_str()) {
+            anyhow::bail!(
+                "Invalid memory type: {}. Must be one of: {}",
+                memory.memory_type,
+                valid_types.join(", ")
+            );
+        }
+
+        parse_duration(&memory.ttl).context("Invalid memory ttl")?;
+        if memory.max_messages == 0 {
+            anyhow::bail!("Memory max_messages must be greater than 0");
+        }
+
+        if let Some(summarization) = memory.summarization.as_ref().filter(|s| s.enabled) {
+            if summarization.trigger_at == 0 {
+                anyhow::bail!("Summarization trigger_at must be greater than 0");
+            }
+            if summarization.keep_recent >= summarization.trigger_at {
+                anyhow::bail!("Summarization keep_recent must be less than trigger_at");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_middleware_config(&self, config: &AgentConfig) -> Result<()> {
+        let Some(middleware) = &config.middleware else {
+            return Ok(());
+        };
+
+        if let Some(resilience) = &middleware.resilience {
+            parse_duration(&resilience.timeout).context("Invalid resilience timeout")?;
+            if let Some(breaker) = &resilience.circuit_breaker {
+                if breaker.failure_threshold == 0 {
+                    anyhow::bail!("Circuit breaker failure_threshold must be greater than 0");
+                }
+                parse_duration(&breaker.timeout).context("Invalid circuit breaker timeout")?;
+                parse_duration(&breaker.half_open_timeout)
+                    .context("Invalid circuit breaker half_open_timeout")?;
+            }
+
+            match resilience.fallback_strategy.as_deref() {
+                None | Some("fail_fast") => {}
+                Some("fallback_llm") if resilience.fallback_llm.is_none() => {
+                    anyhow::bail!("Resilience fallback_strategy 'fallback_llm' requires fallback_llm")
+                }
+                Some("fallback_llm") => {}
+                Some(other) => anyhow::bail!(
+                    "Invalid resilience fallback_strategy: {}. Must be one of: fallback_llm, fail_fast",
+                    other
+                ),
+            }
+        }
+
+        if let Some(caching) = middleware.caching.as_ref().filter(|c| c.enabled) {
+            let valid_backends = ["memory", "sqlite"];
+            if !valid_backends.contains(&caching.backend.as_str()) {
+                anyhow::bail!(
+                    "Invalid caching backend: {}. Must be one of: {}",
+                    caching.backend,
+                    valid_backends.join(", ")
+                );
+            }
+
+            let valid_strategies = ["exact", "semantic"];
+            if !valid_strategies.contains(&caching.strategy.as_str()) {
+                anyhow::bail!(
+                    "Invalid caching strategy: {}. Must be one of: {}",
+                    caching.strategy,
+                    valid_strategies.join(", ")
+                );
+            }
+
+            parse_duration(&caching.ttl).context("Invalid caching ttl")?;
+
+            if let Some(threshold) = caching.similarity_threshold {
+                if !(threshold > 0.0 && threshold <= 1.0) {
+                    anyhow::bail!(
+                        "Caching similarity_threshold must be greater than 0 and at most 1"
+                    );
+                }
+            }
+        }
+
+        if let Some(security) = &middleware.security {
+            if let Some(rate_limit) = &security.rate_limit {
+                parse_rate_limit(rate_limit).context("Invalid security rate_limit")?;
+            }
+
+            let domains = security
+                .allowed_domains
+                .iter()
+                .chain(&security.blocked_domains);
+            for domain in domains {
+                let pattern = domain.strip_prefix("*.").unwrap_or(domain);
+                if domain.trim().is_empty() || (domain != "*" && pattern.contains('*')) {
+                    anyhow::bail!(
+                        "Invalid domain pattern '{}': use a host name, '*.' followed by a domain, or '*'",
+                        domain
+                    );
+                }
+            }
+        }
+
+        if let Some(governance) = &middleware.governance {
+            let budgets = [
+                ("max_cost_per_request", governance.max_cost_per_request),
+                ("max_cost_per_hour", governance.max_cost_per_hour),
+            ];
+            for (field, limit) in budgets {
+                if limit.is_some_and(|limit| limit.is_nan() || limit <= 0.0) {
+                    anyhow::bail!("Governance {} must be greater than 0", field);
+                }
+            }
+
+            if let Some(audit_log) = governance.audit_log.as_ref().filter(|a| a.enabled) {
+                if audit_log.destination != "file" {
+                    anyhow::bail!(
+                        "Unsupported audit log destination '{}' (expected 'file')",
+                        audit_log.destination
+                    );
+                }
+                if audit_log.path.trim().is_empty() {
+                    anyhow::bail!("Audit log path cannot be empty");
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -61,7 +267,12 @@ impl ConfigValidator {
         }
 
         // Validate strategy
-        let valid_strategies = ["react", "chain_of_thought", "tree_of_thoughts"];
+        let valid_strategies = [
+            "react",
+            "chain_of_thought",
+            "tree_of_thoughts",
+            "plan_execute",
+        ];
         if !valid_strategies.contains(&config.execution.strategy.as_str()) {
             anyhow::bail!(
                 "Invalid execution strategy: {}. Must be one of: {}",
@@ -70,10 +281,235 @@ impl ConfigValidator {
             );
         }
 
+        if config.execution.max_parallel_tool_calls == 0 {
+            anyhow::bail!("Execution max_parallel_tool_calls must be greater than 0");
+        }
+
+        if let Some(tot) = &config.execution.tree_of_thoughts {
+            if tot.branching_factor == 0 || tot.max_depth == 0 || tot.beam_width == 0 {
+                anyhow::bail!(
+                    "tree_of_thoughts branching_factor, max_depth and beam_width must be greater than 0"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ConfigValidator {
+    /// Validate a workflow configuration
+    ///
+    /// Checks that the entry and exit points and every edge lead to nodes,
+    /// that the exit point can be reached, that no cycle of direct edges
+    /// loops forever, and that each `agent_config` (relative to `base_dir`)
+    /// is a valid agent configuration.
+    pub fn validate_workflow(&self, config: &WorkflowConfig, base_dir: &Path) -> Result<()> {
+        self.validate_workflow_nodes(config)?;
+        self.validate_workflow_edges(config)?;
+        self.validate_workflow_graph(config)?;
+        self.validate_workflow_execution(config)?;
+
+        for node in &config.nodes {
+            if let Some(path) = &node.agent_config {
+                let path = base_dir.join(path);
+                let agent = parse_agent_config(&path)
+                    .with_context(|| format!("Node '{}' has an invalid agent_config", node.id))?;
+                self.validate(&agent)
+                    .with_context(|| format!("Node '{}' has an invalid agent_config", node.id))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Problems that don't make a workflow invalid: nodes the entry point
+    /// never leads to, and cycles that only conditional edges can leave
+    pub fn workflow_warnings(&self, config: &WorkflowConfig) -> Vec<String> {
+        let graph = WorkflowGraph::new(config);
+        let unreachable = graph
+            .unreachable()
+            .into_iter()
+            .map(|node| format!("Node '{}' can't be reached from the entry point", node));
+        let cycles = graph.cycles(false).into_iter().map(|nodes| {
+            format!(
+                "Nodes {} form a cycle; make sure its conditions lead out of it",
+                nodes.join(", ")
+            )
+        });
+        unreachable.chain(cycles).collect()
+    }
+
+    fn validate_workflow_nodes(&self, config: &WorkflowConfig) -> Result<()> {
+        let mut ids = BTreeSet::new();
+        for node in &config.nodes {
+            if node.id.trim().is_empty() {
+                anyhow::bail!("Workflow nodes must have an id");
+            }
+            if !ids.insert(node.id.as_str()) {
+                anyhow::bail!("Node id '{}' is used more than once", node.id);
+            }
+            if !NODE_TYPES.contains(&node.node_type.as_str()) {
+                anyhow::bail!(
+                    "Invalid type '{}' for node '{}'. Must be one of: {}",
+                    node.node_type,
+                    node.id,
+                    NODE_TYPES.join(", ")
+                );
+            }
+            if node.join.is_some() && node.node_type != "join" {
+                anyhow::bail!(
+                    "Node '{}' has join settings but is not a join node",
+                    node.id
+                );
+            }
+
+            let aggregates = node
+                .join
+                .as_ref()
+                .is_some_and(|join| join.merge == MergePolicy::Agent);
+            if (node.node_type == "agent" || aggregates) && node.agent_config.is_none() {
+                anyhow::bail!("Node '{}' needs an agent_config", node.id);
+            }
+            if let Some(timeout) = &node.timeout {
+                parse_duration(timeout)
+                    .with_context(|| format!("Invalid timeout for node '{}'", node.id))?;
+            }
+        }
+
+        for (field, node) in [
+            ("entry_point", &config.entry_point),
+            ("exit_point", &config.exit_point),
+        ] {
+            if !ids.contains(node.as_str()) {
+                anyhow::bail!("Workflow {} '{}' is not one of its nodes", field, node);
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_workflow_edges(&self, config: &WorkflowConfig) -> Result<()> {
+        let is_node = |id: &str| config.nodes.iter().any(|n| n.id == id);
+
+        for edge in &config.edges {
+            let from = edge.source();
+            if !is_node(from) {
+                anyhow::bail!("Edge from unknown node '{}'", from);
+            }
+
+            if let WorkflowEdge::Conditional { to, .. } = edge {
+                match to.target_type.as_str() {
+                    "expression" => {
+                        RouteRules::parse(&to.logic).with_context(|| {
+                            format!("Invalid expression edge from node '{}'", from)
+                        })?;
+                    }
+                    "llm_router" if to.routes.is_empty() => {
+                        anyhow::bail!("LLM router edge from node '{}' has no routes", from);
+                    }
+                    "llm_router" => {}
+                    other => anyhow::bail!(
+                        "Invalid type '{}' for the edge from node '{}'. Must be one of: {}",
+                        other,
+                        from,
+                        EDGE_TYPES.join(", ")
+                    ),
+                }
+            }
+
+            if let Some((to, _)) = edge.branches().into_iter().find(|(to, _)| !is_node(to)) {
+                anyhow::bail!("Edge from node '{}' leads to unknown node '{}'", from, to);
+            }
+
+            let conditional = |e: &WorkflowEdge| matches!(e, WorkflowEdge::Conditional { .. });
+            let outgoing: Vec<&WorkflowEdge> =
+                config.edges.iter().filter(|e| e.source() == from).collect();
+            if outgoing.len() > 1 && outgoing.iter().any(|e| conditional(e)) {
+                anyhow::bail!(
+                    "Node '{}' has a conditional edge and other outgoing edges; only direct edges can fan out",
+                    from
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_workflow_graph(&self, config: &WorkflowConfig) -> Result<()> {
+        let graph = WorkflowGraph::new(config);
+        if !graph.reachable(&config.exit_point) {
+            anyhow::bail!(
+                "Workflow exit_point '{}' can't be reached from entry_point '{}'",
+                config.exit_point,
+                config.entry_point
+            );
+        }
+        if let Some(nodes) = graph.cycles(true).first() {
+            anyhow::bail!(
+                "Nodes {} form a cycle of direct edges, which never ends",
+                nodes.join(", ")
+            );
+        }
+
+        for node in &config.nodes {
+            let has_edge = config.edges.iter().any(|e| e.source() == node.id);
+            if !has_edge && node.id != config.exit_point {
+                anyhow::bail!(
+                    "Node '{}' has no outgoing edge and is not the exit point",
+                    node.id
+                );
+            }
+
+            if node.node_type == "join" {
+                let sources: BTreeSet<&str> = config
+                    .edges
+                    .iter()
+                    .filter(|e| e.branches().iter().any(|(to, _)| *to == node.id))
+                    .map(WorkflowEdge::source)
+                    .collect();
+                let first = node.join.as_ref().and_then(|join| join.first);
+                if sources.is_empty() {
+                    anyhow::bail!("Join node '{}' has no incoming edges", node.id);
+                }
+                if first.is_some_and(|first| first == 0 || first > sources.len()) {
+                    anyhow::bail!(
+                        "Join node '{}' waits for the first {} branches, but {} edges lead to it",
+                        node.id,
+                        first.unwrap_or_default(),
+                        sources.len()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_workflow_execution(&self, config: &WorkflowConfig) -> Result<()> {
+        parse_duration(&config.config.max_execution_time)
+            .context("Invalid workflow max_execution_time")?;
+
+        if let Some(checkpoint) = config.config.checkpoint.as_ref().filter(|c| c.enabled) {
+            if checkpoint.interval.trim() != "node" {
+                parse_duration(&checkpoint.interval).with_context(|| {
+                    format!(
+                        "Invalid checkpoint interval '{}'. Use 'node' or a duration like '30s'",
+                        checkpoint.interval
+                    )
+                })?;
+            }
+        }
         Ok(())
     }
 }
 
+/// Whether an HTTP header usually carries a credential
+fn is_credential_header(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "authorization"
+        || ["token", "key", "secret", "password"]
+            .iter()
+            .any(|word| name.contains(word))
+}
+
 impl Default for ConfigValidator {
     fn default() -> Self {
         Self::new()
@@ -83,7 +519,10 @@ impl Default for ConfigValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ExecutionConfig, LLMConfig};
+    use crate::{
+        CircuitBreakerConfig, ExecutionConfig, LLMConfig, MiddlewareConfig, ResilienceConfig,
+        RetryConfig, TreeOfThoughtsConfig,
+    };
     use std::collections::HashMap;
 
     fn create_minimal_config() -> AgentConfig {
@@ -100,6 +539,8 @@ mod tests {
                 top_p: None,
                 stream: true,
                 retry: None,
+                base_url: None,
+                api_key: None,
             },
             tools: vec![],
             memory: None,
@@ -134,4 +575,237 @@ mod tests {
         let validator = ConfigValidator::new();
         assert!(validator.validate(&config).is_err());
     }
+
+    #[test]
+    fn test_tree_of_thoughts_settings() {
+        let mut config = create_minimal_config();
+        config.execution.strategy = "tree_of_thoughts".to_string();
+        config.execution.tree_of_thoughts = Some(TreeOfThoughtsConfig::default());
+
+        let validator = ConfigValidator::new();
+        assert!(validator.validate(&config).is_ok());
+
+        config.execution.tree_of_thoughts = Some(TreeOfThoughtsConfig {
+            branching_factor: 0,
+            ..Default::default()
+        });
+        assert!(validator.validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_retry_settings() {
+        let mut config = create_minimal_config();
+        config.llm.retry = Some(RetryConfig::default());
+
+        let validator = ConfigValidator::new();
+        assert!(validator.validate(&config).is_ok());
+
+        config.llm.retry = Some(RetryConfig {
+            backoff: "fibonacci".to_string(),
+            ..Default::default()
+        });
+        assert!(validator.validate(&config).is_err());
+
+        config.llm.retry = Some(RetryConfig {
+            initial_delay: "a while".to_string(),
+            ..Default::default()
+        });
+        assert!(validator.validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_resilience_settings() {
+        let mut config = create_minimal_config();
+        let resilience = ResilienceConfig {
+            enabled: true,
+            circuit_breaker: Some(CircuitBreakerConfig::default()),
+            timeout: "30s".to_string(),
+            fallback_strategy: Some("fallback_llm".to_string()),
+            fallback_llm: None,
+        };
+        config.middleware = Some(MiddlewareConfig {
+            observability: None,
+            security: None,
+            governance: None,
+            resilience: Some(resilience.clone()),
+            caching: None,
+        });
+
+        // The fallback strategy needs a fallback LLM
+        let validator = ConfigValidator::new();
+        assert!(validator.validate(&config).is_err());
+
+        config.middleware.as_mut().unwrap().resilience = Some(ResilienceConfig {
+            fallback_llm: Some(config.llm.clone()),
+            timeout: "soon".to_string(),
+            ..resilience.clone()
+        });
+        assert!(validator.validate(&config).is_err());
+
+        config.middleware.as_mut().unwrap().resilience = Some(ResilienceConfig {
+            fallback_llm: Some(config.llm.clone()),
+            ..resilience
+        });
+        assert!(validator.validate(&config).is_ok());
+    }
+
+    #[test]
+    fn test_memory_settings() {
+        let mut config = create_minimal_config();
+        config.memory = Some(serde_yaml::from_str("{type: sqlite, ttl: 24h}").unwrap());
+
+        let validator = ConfigValidator::new();
+        assert!(validator.validate(&config).is_ok());
+
+        let invalid = [
+            "{type: redis}",
+            "{type: in_memory, ttl: forever}",
+            "{type: in_memory, max_messages: 0}",
+            "{type: in_memory, summarization: {enabled: true, trigger_at: 0, keep_recent: 0}}",
+            "{type: in_memory, summarization: {enabled: true, trigger_at: 10, keep_recent: 10}}",
+        ];
+        for memory in invalid {
+            config.memory = Some(serde_yaml::from_str(memory).unwrap());
+            assert!(validator.validate(&config).is_err(), "{}", memory);
+        }
+    }
+
+    #[test]
+    fn test_caching_settings() {
+        let mut config = create_minimal_config();
+        config.middleware = Some(MiddlewareConfig {
+            caching: Some(
+                serde_yaml::from_str(
+                    "{enabled: true, backend: sqlite, strategy: semantic, similarity_threshold: 0.9}",
+                )
+                .unwrap(),
+            ),
+            ..Default::default()
+        });
+
+        let validator = ConfigValidator::new();
+        assert!(validator.validate(&config).is_ok());
+
+        let invalid = [
+            "{enabled: true, backend: redis}",
+            "{enabled: true, backend: memory, strategy: fuzzy}",
+            "{enabled: true, backend: memory, ttl: soon}",
+            "{enabled: true, backend: memory, similarity_threshold: 1.5}",
+        ];
+        for caching in invalid {
+            config.middleware.as_mut().unwrap().caching =
+                Some(serde_yaml::from_str(caching).unwrap());
+            assert!(validator.validate(&config).is_err(), "{}", caching);
+        }
+    }
+
+    const WORKFLOW: &str = r#"
+name: review
+version: 1.0.0
+entry_point: draft
+exit_point: publish
+nodes:
+  - {id: draft, type: agent, agent_config: agent.yaml}
+  - {id: legal, type: agent, agent_config: agent.yaml}
+  - {id: style, type: agent, agent_config: agent.yaml}
+  - {id: check, type: join, join: {merge: json}}
+  - {id: publish, type: agent, agent_config: agent.yaml}
+edges:
+  - {from: draft, to: legal}
+  - {from: draft, to: style}
+  - {from: legal, to: check}
+  - {from: style, to: check}
+  - from: check
+    to:
+      type: expression
+      logic: |
+        json.legal contains "approved" => publish
+        else => draft
+"#;
+
+    const AGENT: &str = r#"
+name: writer
+version: 1.0.0
+llm: {provider: anthropic, model: claude-3-5-sonnet-20241022}
+system_prompt: You write.
+"#;
+
+    #[test]
+    fn test_validate_workflow() {
+        let dir = std::env::temp_dir().join(format!("namra-workflow-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("agent.yaml"), AGENT).unwrap();
+        let validator = ConfigValidator::new();
+        let workflow = |yaml: &str| -> WorkflowConfig { serde_yaml::from_str(yaml).unwrap() };
+
+        let config = workflow(WORKFLOW);
+        validator.validate_workflow(&config, &dir).unwrap();
+        // The conditional loop back to the draft is allowed, with a warning
+        assert_eq!(
+            validator.workflow_warnings(&config),
+            ["Nodes draft, legal, style, check form a cycle; make sure its conditions lead out of it"]
+        );
+
+        let invalid = [
+            WORKFLOW.replace("entry_point: draft", "entry_point: start"),
+            WORKFLOW.replace("else => draft", "else => redraft"),
+            WORKFLOW.replace("else => draft", "else draft"),
+            WORKFLOW.replace("json.legal contains", "json.legal ="),
+            WORKFLOW.replace("{from: style, to: check}", "{from: style, to: draft}"),
+            WORKFLOW.replace("{merge: json}", "{first: 3}"),
+            WORKFLOW.replace("type: agent, agent_config: agent.yaml}", "type: agent}"),
+            WORKFLOW.replace("agent_config: agent.yaml", "agent_config: missing.yaml"),
+            WORKFLOW.replace("{from: legal, to: check}", "{from: legal, to: legal}"),
+        ];
+        for yaml in &invalid {
+            assert!(
+                validator.validate_workflow(&workflow(yaml), &dir).is_err(),
+                "{}",
+                yaml
+            );
+        }
+
+        let unreachable = workflow(
+            &WORKFLOW
+                .replace(
+                    "nodes:\n",
+                    "nodes:\n  - {id: notify, type: agent, agent_config: agent.yaml}\n",
+                )
+                .replace("edges:\n", "edges:\n  - {from: notify, to: publish}\n"),
+        );
+        validator.validate_workflow(&unreachable, &dir).unwrap();
+        assert_eq!(
+            validator.workflow_warnings(&unreachable)[0],
+            "Node 'notify' can't be reached from the entry point"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_warns_about_literal_secrets() {
+        let mut config = create_minimal_config();
+        let validator = ConfigValidator::new();
+        assert!(validator.warnings(&config).is_empty());
+
+        config.llm.api_key = Some("sk-ant-literal".into());
+        config.tools = vec![serde_yaml::from_str(
+            r#"
+type: builtin.http
+name: github
+config:
+  url: https://api.github.com
+  method: GET
+  auth: "${env:GITHUB_TOKEN}"
+  headers:
+    Accept: application/json
+    X-Api-Key: abc123
+"#,
+        )
+        .unwrap()];
+
+        let warnings = validator.warnings(&config);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("llm.api_key"));
+        assert!(warnings[1].starts_with("tool 'github' header X-Api-Key"));
+    }
 }
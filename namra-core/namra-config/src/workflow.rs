@@ -1,6 +1,8 @@
 //! Workflow configuration types
 
+use crate::condition::{rule_lines, RouteRules};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Workflow configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,9 +44,40 @@ pub struct WorkflowNode {
 
     #[serde(default)]
     pub timeout: Option<String>,
+
+    /// How a `join` node waits for and combines its incoming branches
+    #[serde(default)]
+    pub join: Option<JoinConfig>,
+}
+
+/// How a join node waits for and combines the branches that reach it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JoinConfig {
+    /// Continue once this many branches have arrived; all of them when unset
+    #[serde(default)]
+    pub first: Option<usize>,
+
+    /// How to combine the branch outputs
+    #[serde(default)]
+    pub merge: MergePolicy,
+}
+
+/// Way a join node combines branch outputs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergePolicy {
+    /// Outputs one after another, in the order of the join's incoming edges
+    #[default]
+    Concat,
+    /// JSON object of the outputs keyed by node ID
+    Json,
+    /// The node's `agent_config` agent combines the outputs
+    Agent,
 }
 
 /// Workflow edge (connection between nodes)
+///
+/// A node with several direct edges runs their targets in parallel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum WorkflowEdge {
@@ -55,16 +88,59 @@ pub enum WorkflowEdge {
     Conditional { from: String, to: ConditionalTarget },
 }
 
+impl WorkflowEdge {
+    /// Node the edge starts from
+    pub fn source(&self) -> &str {
+        match self {
+            WorkflowEdge::Direct { from, .. } | WorkflowEdge::Conditional { from, .. } => from,
+        }
+    }
+
+    /// Nodes the edge can lead to, each with the rule condition or route
+    /// description that leads there
+    ///
+    /// Direct edges have no label; `else` rules are labeled `else`. An
+    /// `expression` edge whose rules don't parse has no branches.
+    pub fn branches(&self) -> Vec<(&str, Option<&str>)> {
+        match self {
+            WorkflowEdge::Direct { to, .. } => vec![(to.as_str(), None)],
+            WorkflowEdge::Conditional { to, .. } if to.target_type == "expression" => {
+                if RouteRules::parse(&to.logic).is_err() {
+                    return Vec::new();
+                }
+                // The rules parsed, so each line ends with `=> node`
+                rule_lines(&to.logic)
+                    .filter_map(|line| line.rsplit_once("=>"))
+                    .map(|(condition, node)| (node.trim(), Some(condition.trim())))
+                    .collect()
+            }
+            WorkflowEdge::Conditional { to, .. } => to
+                .routes
+                .iter()
+                .map(|(node, description)| (node.as_str(), Some(description.as_str())))
+                .collect(),
+        }
+    }
+}
+
+/// Target of a conditional edge, chosen when the edge's node finishes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConditionalTarget {
+    /// `expression` or `llm_router`
     #[serde(rename = "type")]
     pub target_type: String,
 
+    /// Routing rules for `expression`, or instructions for `llm_router`
+    #[serde(default)]
     pub logic: String,
+
+    /// Nodes an `llm_router` can choose from (node ID -> description)
+    #[serde(default)]
+    pub routes: BTreeMap<String, String>,
 }
 
 /// Workflow execution configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowExecutionConfig {
     #[serde(default = "default_workflow_timeout")]
     pub max_execution_time: String,
@@ -79,6 +155,17 @@ pub struct WorkflowExecutionConfig {
     pub max_retries: u32,
 }
 
+impl Default for WorkflowExecutionConfig {
+    fn default() -> Self {
+        Self {
+            max_execution_time: default_workflow_timeout(),
+            checkpoint: None,
+            retry_failed_nodes: false,
+            max_retries: default_max_retries(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckpointConfig {
     pub enabled: bool,
@@ -3,6 +3,7 @@
 use crate::types::{LLMRequest, LLMResponse, StreamChunk};
 use async_trait::async_trait;
 use std::pin::Pin;
+use std::time::Duration;
 
 /// Result type for LLM operations
 pub type LLMResult<T> = Result<T, LLMError>;
@@ -37,10 +38,49 @@ pub enum LLMError {
     #[error("Authentication error: {0}")]
     AuthenticationError(String),
 
+    #[error("Circuit open for {0}")]
+    CircuitOpen(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+impl LLMError {
+    /// Whether the request may succeed if sent again
+    ///
+    /// Rate limits, timeouts, connection failures, server errors, and
+    /// overloaded responses (529) are transient; everything else is not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LLMError::RateLimited { .. } | LLMError::Timeout(_) => true,
+            LLMError::HttpError(e) => e.is_timeout() || e.is_connect(),
+            LLMError::ApiError { status, .. } => matches!(status, 408 | 409 | 429 | 500..=599),
+            _ => false,
+        }
+    }
+
+    /// Delay requested by the provider before retrying
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LLMError::RateLimited {
+                retry_after: Some(secs),
+            } => Some(Duration::from_secs(*secs)),
+            _ => None,
+        }
+    }
+}
+
+/// Read a `retry-after` header given in seconds
+pub(crate) fn retry_after_secs(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
 /// Trait for LLM adapters
 ///
 /// All LLM providers (Anthropic, OpenAI, Google, etc.) implement this trait
@@ -104,5 +144,34 @@ pub trait LLMAdapterBuilder {
 
 #[cfg(test)]
 mod tests {
-    // Mock adapter tests will be added in Week 4
+    use super::*;
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(LLMError::RateLimited { retry_after: None }.is_retryable());
+        assert!(LLMError::ApiError {
+            status: 529,
+            message: "overloaded".to_string()
+        }
+        .is_retryable());
+        assert!(!LLMError::ApiError {
+            status: 404,
+            message: "not found".to_string()
+        }
+        .is_retryable());
+        assert!(!LLMError::AuthenticationError("bad key".to_string()).is_retryable());
+        assert!(!LLMError::InvalidRequest("bad".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let err = LLMError::RateLimited {
+            retry_after: Some(7),
+        };
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "12".parse().unwrap());
+        assert_eq!(retry_after_secs(&headers), Some(12));
+    }
 }
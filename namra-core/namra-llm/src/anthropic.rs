@@ -1,6 +1,6 @@
 //! Anthropic (Claude) LLM adapter
 
-use crate::adapter::{LLMAdapter, LLMError, LLMResult, LLMStream};
+use crate::adapter::{retry_after_secs, LLMAdapter, LLMError, LLMResult, LLMStream};
 use crate::types::*;
 use async_trait::async_trait;
 use futures::stream::StreamExt;
@@ -39,34 +39,81 @@ impl AnthropicAdapter {
     }
 
     /// Convert our Message type to Anthropic's format
+    ///
+    /// Assistant tool calls become `tool_use` blocks and tool messages become
+    /// `tool_result` blocks. Consecutive tool results are merged into a single
+    /// user message, as the API expects all results for a turn together.
     fn convert_messages(&self, messages: &[Message]) -> (Option<String>, Vec<AnthropicMessage>) {
         let mut system_prompt = None;
-        let mut converted = Vec::new();
+        let mut converted: Vec<AnthropicMessage> = Vec::new();
 
         for msg in messages {
             match msg.role {
                 MessageRole::System => {
-                    // Anthropic uses a separate system parameter
-                    system_prompt = Some(msg.content.clone());
+                    // Anthropic uses a separate system parameter, so system
+                    // messages (such as a conversation summary) are joined
+                    system_prompt = Some(match system_prompt {
+                        Some(prompt) => format!("{}\n\n{}", prompt, msg.content),
+                        None => msg.content.clone(),
+                    });
                 }
                 MessageRole::User => {
                     converted.push(AnthropicMessage {
                         role: "user".to_string(),
-                        content: msg.content.clone(),
+                        content: AnthropicMessageContent::Text(msg.content.clone()),
                     });
                 }
                 MessageRole::Assistant => {
+                    let tool_calls = msg.tool_calls.as_deref().unwrap_or_default();
+                    let content = if tool_calls.is_empty() {
+                        AnthropicMessageContent::Text(msg.content.clone())
+                    } else {
+                        let mut blocks = Vec::new();
+                        if !msg.content.is_empty() {
+                            blocks.push(AnthropicContentBlock::Text {
+                                text: msg.content.clone(),
+                            });
+                        }
+                        blocks.extend(tool_calls.iter().map(|call| {
+                            AnthropicContentBlock::ToolUse {
+                                id: call.id.clone(),
+                                name: call.name.clone(),
+                                input: call.arguments.clone(),
+                            }
+                        }));
+                        AnthropicMessageContent::Blocks(blocks)
+                    };
                     converted.push(AnthropicMessage {
                         role: "assistant".to_string(),
-                        content: msg.content.clone(),
+                        content,
                     });
                 }
                 MessageRole::Tool => {
-                    // For now, convert tool results to user messages
-                    // Full tool support will be added later
+                    let block = AnthropicContentBlock::ToolResult {
+                        tool_use_id: msg.tool_call_id.clone().unwrap_or_default(),
+                        content: msg.content.clone(),
+                        is_error: msg.is_error,
+                    };
+
+                    // Append to the previous tool result message if there is one
+                    if let Some(AnthropicMessage {
+                        role,
+                        content: AnthropicMessageContent::Blocks(blocks),
+                    }) = converted.last_mut()
+                    {
+                        if role == "user"
+                            && blocks
+                                .iter()
+                                .all(|b| matches!(b, AnthropicContentBlock::ToolResult { .. }))
+                        {
+                            blocks.push(block);
+                            continue;
+                        }
+                    }
+
                     converted.push(AnthropicMessage {
                         role: "user".to_string(),
-                        content: format!("Tool result: {}", msg.content),
+                        content: AnthropicMessageContent::Blocks(vec![block]),
                     });
                 }
             }
@@ -75,16 +122,39 @@ impl AnthropicAdapter {
         (system_prompt, converted)
     }
 
-    /// Calculate cost for Anthropic models
-    fn calculate_cost(&self, input_tokens: u32, output_tokens: u32, model: &str) -> f64 {
+    /// Build the request body for the Messages API
+    fn build_request(&self, request: &LLMRequest, stream: bool) -> AnthropicRequest {
+        let (system, messages) = self.convert_messages(&request.messages);
+
+        AnthropicRequest {
+            model: request.model.clone(),
+            messages,
+            max_tokens: request.max_tokens.unwrap_or(4096),
+            system,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            stop_sequences: request.stop_sequences.clone(),
+            stream,
+            tools: request.tools.clone().filter(|tools| !tools.is_empty()),
+            metadata: None,
+        }
+    }
+
+    /// Per-million-token pricing for a model
+    fn pricing(&self, model: &str) -> (f64, f64) {
         // Pricing as of 2024 (per million tokens)
-        let (input_price, output_price) = match model {
+        match model {
             m if m.contains("claude-3-5-sonnet") => (3.0, 15.0),
             m if m.contains("claude-3-opus") => (15.0, 75.0),
             m if m.contains("claude-3-sonnet") => (3.0, 15.0),
             m if m.contains("claude-3-haiku") => (0.25, 1.25),
             _ => (3.0, 15.0), // Default to Sonnet pricing
-        };
+        }
+    }
+
+    /// Calculate cost for Anthropic models
+    fn calculate_cost(&self, input_tokens: u32, output_tokens: u32, model: &str) -> f64 {
+        let (input_price, output_price) = self.pricing(model);
 
         let input_cost = (input_tokens as f64 / 1_000_000.0) * input_price;
         let output_cost = (output_tokens as f64 / 1_000_000.0) * output_price;
@@ -103,19 +173,7 @@ impl LLMAdapter for AnthropicAdapter {
         let span = llm_request_span("anthropic", &request.model);
 
         async move {
-            let (system, messages) = self.convert_messages(&request.messages);
-
-            let body = AnthropicRequest {
-                model: request.model.clone(),
-                messages,
-                max_tokens: request.max_tokens.unwrap_or(4096),
-                system,
-                temperature: request.temperature,
-                top_p: request.top_p,
-                stop_sequences: request.stop_sequences.clone(),
-                stream: false,
-                metadata: None,
-            };
+            let body = self.build_request(&request, false);
 
             let response = self
                 .client
@@ -131,22 +189,30 @@ impl LLMAdapter for AnthropicAdapter {
             let status = response.status();
 
             if !status.is_success() {
+                let retry_after = retry_after_secs(response.headers());
                 let error_text = response.text().await.unwrap_or_default();
-                return Err(self.handle_error(status.as_u16(), error_text));
+                return Err(self.handle_error(status.as_u16(), retry_after, error_text));
             }
 
             let anthropic_response: AnthropicResponse = response.json().await?;
 
-            // Extract content from response
-            let content = anthropic_response
-                .content
-                .iter()
-                .map(|c| {
-                    let AnthropicContent::Text { text } = c;
-                    text.as_str()
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
+            // Extract text and tool calls from the content blocks
+            let mut text_parts = Vec::new();
+            let mut tool_calls = Vec::new();
+            for block in &anthropic_response.content {
+                match block {
+                    AnthropicContentBlock::Text { text } => text_parts.push(text.as_str()),
+                    AnthropicContentBlock::ToolUse { id, name, input } => {
+                        tool_calls.push(ToolCall {
+                            id: id.clone(),
+                            name: name.clone(),
+                            arguments: input.clone(),
+                        })
+                    }
+                    _ => {}
+                }
+            }
+            let content = text_parts.join("\n");
 
             let cost = self.calculate_cost(
                 anthropic_response.usage.input_tokens,
@@ -169,17 +235,16 @@ impl LLMAdapter for AnthropicAdapter {
                 cost,
             );
 
-            let finish_reason = match anthropic_response.stop_reason.as_deref() {
-                Some("end_turn") => FinishReason::Stop,
-                Some("max_tokens") => FinishReason::Length,
-                Some("stop_sequence") => FinishReason::Stop,
-                _ => FinishReason::Other,
-            };
+            let finish_reason = convert_stop_reason(anthropic_response.stop_reason.as_deref());
 
             Ok(LLMResponse {
                 content,
                 role: MessageRole::Assistant,
-                tool_calls: None,
+                tool_calls: if tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(tool_calls)
+                },
                 usage,
                 finish_reason,
                 metadata: HashMap::new(),
@@ -190,19 +255,7 @@ impl LLMAdapter for AnthropicAdapter {
     }
 
     async fn stream(&self, request: LLMRequest) -> LLMResult<LLMStream> {
-        let (system, messages) = self.convert_messages(&request.messages);
-
-        let body = AnthropicRequest {
-            model: request.model.clone(),
-            messages,
-            max_tokens: request.max_tokens.unwrap_or(4096),
-            system,
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop_sequences: request.stop_sequences.clone(),
-            stream: true,
-            metadata: None,
-        };
+        let body = self.build_request(&request, true);
 
         let response = self
             .client
@@ -218,53 +271,29 @@ impl LLMAdapter for AnthropicAdapter {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_secs(response.headers());
             let error_text = response.text().await.unwrap_or_default();
-            return Err(self.handle_error(status.as_u16(), error_text));
+            return Err(self.handle_error(status.as_u16(), retry_after, error_text));
         }
 
-        // Parse SSE stream
+        let state = StreamState {
+            pricing: self.pricing(&request.model),
+            ..Default::default()
+        };
+
+        // Parse SSE stream, carrying usage and stop reason to the final chunk
         let stream = response.bytes_stream();
         let sse_stream = eventsource_stream::EventStream::new(stream);
 
-        let mapped_stream = sse_stream.filter_map(move |event_result| {
-            async move {
-                match event_result {
-                    Ok(event) => {
-                        if event.event == "message_delta" || event.event == "content_block_delta" {
-                            // Parse the event data
-                            if let Ok(delta) =
-                                serde_json::from_str::<serde_json::Value>(&event.data)
-                            {
-                                if let Some(delta_obj) = delta.get("delta") {
-                                    if let Some(text) =
-                                        delta_obj.get("text").and_then(|t| t.as_str())
-                                    {
-                                        return Some(Ok(StreamChunk {
-                                            content: text.to_string(),
-                                            tool_call_delta: None,
-                                            is_final: false,
-                                            usage: None,
-                                            finish_reason: None,
-                                        }));
-                                    }
-                                }
-                            }
-                        } else if event.event == "message_stop" {
-                            // Final chunk
-                            return Some(Ok(StreamChunk {
-                                content: String::new(),
-                                tool_call_delta: None,
-                                is_final: true,
-                                usage: None,
-                                finish_reason: Some(FinishReason::Stop),
-                            }));
-                        }
-                        None
-                    }
+        let mapped_stream = sse_stream
+            .scan(state, |state, event_result| {
+                let item = match event_result {
+                    Ok(event) => state.handle(&event.event, &event.data),
                     Err(e) => Some(Err(LLMError::StreamError(e.to_string()))),
-                }
-            }
-        });
+                };
+                futures::future::ready(Some(item))
+            })
+            .filter_map(futures::future::ready);
 
         Ok(Box::pin(mapped_stream))
     }
@@ -283,10 +312,10 @@ impl LLMAdapter for AnthropicAdapter {
 }
 
 impl AnthropicAdapter {
-    fn handle_error(&self, status: u16, body: String) -> LLMError {
+    fn handle_error(&self, status: u16, retry_after: Option<u64>, body: String) -> LLMError {
         match status {
             401 => LLMError::AuthenticationError("Invalid API key".to_string()),
-            429 => LLMError::RateLimited { retry_after: None },
+            429 => LLMError::RateLimited { retry_after },
             400 => LLMError::InvalidRequest(body),
             _ => LLMError::ApiError {
                 status,
@@ -296,6 +325,139 @@ impl AnthropicAdapter {
     }
 }
 
+/// Map Anthropic's stop reason to our finish reason
+fn convert_stop_reason(stop_reason: Option<&str>) -> FinishReason {
+    match stop_reason {
+        Some("end_turn") => FinishReason::Stop,
+        Some("max_tokens") => FinishReason::Length,
+        Some("stop_sequence") => FinishReason::Stop,
+        Some("tool_use") => FinishReason::ToolCalls,
+        _ => FinishReason::Other,
+    }
+}
+
+/// State carried across events of a streamed Messages API response
+#[derive(Default)]
+struct StreamState {
+    /// Per-million-token (input, output) pricing for the requested model
+    pricing: (f64, f64),
+    input_tokens: u32,
+    output_tokens: u32,
+    finish_reason: Option<FinishReason>,
+}
+
+impl StreamState {
+    /// Convert a single SSE event from the Messages API into a stream chunk
+    ///
+    /// Text deltas become content, `tool_use` blocks become tool call deltas keyed
+    /// by their content block index. Usage and stop reason are collected from
+    /// `message_start`/`message_delta` and reported on the final chunk. Events we
+    /// don't surface return `None`.
+    fn handle(&mut self, event: &str, data: &str) -> Option<LLMResult<StreamChunk>> {
+        let chunk = |content: String, tool_call_delta: Option<ToolCallDelta>| StreamChunk {
+            content,
+            tool_call_delta,
+            is_final: false,
+            usage: None,
+            finish_reason: None,
+            metadata: HashMap::new(),
+        };
+
+        match event {
+            "message_start" => {
+                let value: serde_json::Value = serde_json::from_str(data).ok()?;
+                let usage = value.get("message")?.get("usage")?;
+                self.input_tokens = usage
+                    .get("input_tokens")
+                    .and_then(|t| t.as_u64())
+                    .unwrap_or(0) as u32;
+                self.output_tokens = usage
+                    .get("output_tokens")
+                    .and_then(|t| t.as_u64())
+                    .unwrap_or(0) as u32;
+                None
+            }
+            "message_delta" => {
+                let value: serde_json::Value = serde_json::from_str(data).ok()?;
+                if let Some(reason) = value
+                    .get("delta")
+                    .and_then(|d| d.get("stop_reason"))
+                    .and_then(|r| r.as_str())
+                {
+                    self.finish_reason = Some(convert_stop_reason(Some(reason)));
+                }
+                // Output token count in message_delta is cumulative
+                if let Some(output_tokens) = value
+                    .get("usage")
+                    .and_then(|u| u.get("output_tokens"))
+                    .and_then(|t| t.as_u64())
+                {
+                    self.output_tokens = output_tokens as u32;
+                }
+                None
+            }
+            "content_block_start" | "content_block_delta" => {
+                let value: serde_json::Value = serde_json::from_str(data).ok()?;
+                let index = value.get("index").and_then(|i| i.as_u64()).unwrap_or(0) as usize;
+
+                if let Some(block) = value.get("content_block") {
+                    // Start of a tool_use block carries the call id and name
+                    if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
+                        return Some(Ok(chunk(
+                            String::new(),
+                            Some(ToolCallDelta {
+                                index,
+                                id: block.get("id").and_then(|v| v.as_str()).map(String::from),
+                                name: block.get("name").and_then(|v| v.as_str()).map(String::from),
+                                arguments: None,
+                            }),
+                        )));
+                    }
+                    return None;
+                }
+
+                let delta = value.get("delta")?;
+                match delta.get("type").and_then(|t| t.as_str()) {
+                    Some("input_json_delta") => Some(Ok(chunk(
+                        String::new(),
+                        Some(ToolCallDelta {
+                            index,
+                            id: None,
+                            name: None,
+                            arguments: delta
+                                .get("partial_json")
+                                .and_then(|v| v.as_str())
+                                .map(String::from),
+                        }),
+                    ))),
+                    _ => delta
+                        .get("text")
+                        .and_then(|t| t.as_str())
+                        .map(|text| Ok(chunk(text.to_string(), None))),
+                }
+            }
+            "message_stop" => {
+                let (input_price, output_price) = self.pricing;
+                let cost = (self.input_tokens as f64 / 1_000_000.0) * input_price
+                    + (self.output_tokens as f64 / 1_000_000.0) * output_price;
+
+                // Final chunk
+                Some(Ok(StreamChunk {
+                    content: String::new(),
+                    tool_call_delta: None,
+                    is_final: true,
+                    usage: Some(
+                        TokenUsage::new(self.input_tokens, self.output_tokens).with_cost(cost),
+                    ),
+                    finish_reason: Some(self.finish_reason.unwrap_or(FinishReason::Stop)),
+                    metadata: HashMap::new(),
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Builder for Anthropic adapter
 #[derive(Default)]
 pub struct AnthropicAdapterBuilder {
@@ -356,6 +518,9 @@ struct AnthropicRequest {
 
     stream: bool,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<ToolDefinition>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     metadata: Option<HashMap<String, String>>,
 }
@@ -363,7 +528,15 @@ struct AnthropicRequest {
 #[derive(Debug, Serialize, Deserialize)]
 struct AnthropicMessage {
     role: String,
-    content: String,
+    content: AnthropicMessageContent,
+}
+
+/// Message content is either a plain string or a list of content blocks
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum AnthropicMessageContent {
+    Text(String),
+    Blocks(Vec<AnthropicContentBlock>),
 }
 
 #[derive(Debug, Deserialize)]
@@ -373,18 +546,33 @@ struct AnthropicResponse {
     #[serde(rename = "type")]
     response_type: String,
     role: String,
-    content: Vec<AnthropicContent>,
+    content: Vec<AnthropicContentBlock>,
     model: String,
     stop_reason: Option<String>,
     stop_sequence: Option<String>,
     usage: AnthropicUsage,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(tag = "type")]
-enum AnthropicContent {
-    #[serde(rename = "text")]
-    Text { text: String },
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+    /// Block types we don't handle (e.g. thinking)
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Deserialize)]
@@ -426,6 +614,175 @@ mod tests {
         assert_eq!(converted.len(), 2);
         assert_eq!(converted[0].role, "user");
         assert_eq!(converted[1].role, "assistant");
+
+        let (system, _) = adapter.convert_messages(&[
+            Message::system("You are helpful"),
+            Message::system("Summary: the user is Ada"),
+            Message::user("Hello"),
+        ]);
+        assert_eq!(
+            system,
+            Some("You are helpful\n\nSummary: the user is Ada".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tool_message_conversion() {
+        let adapter = AnthropicAdapter::new("test-key");
+
+        let mut assistant = Message::assistant("Let me check both.");
+        assistant.tool_calls = Some(vec![
+            ToolCall {
+                id: "toolu_1".to_string(),
+                name: "weather".to_string(),
+                arguments: serde_json::json!({"city": "London"}),
+            },
+            ToolCall {
+                id: "toolu_2".to_string(),
+                name: "weather".to_string(),
+                arguments: serde_json::json!({"city": "Paris"}),
+            },
+        ]);
+
+        let messages = vec![
+            Message::user("Weather in London and Paris?"),
+            assistant,
+            Message::tool("Rainy", "toolu_1"),
+            Message::tool_error("Service unavailable", "toolu_2"),
+        ];
+
+        let (_, converted) = adapter.convert_messages(&messages);
+        let json = serde_json::to_value(&converted).unwrap();
+
+        // Both tool results are merged into a single user message
+        assert_eq!(converted.len(), 3);
+        assert_eq!(json[1]["content"][0]["type"], "text");
+        assert_eq!(json[1]["content"][1]["type"], "tool_use");
+        assert_eq!(json[1]["content"][1]["input"]["city"], "London");
+        assert_eq!(json[2]["role"], "user");
+        assert_eq!(json[2]["content"][0]["type"], "tool_result");
+        assert_eq!(json[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert!(json[2]["content"][0].get("is_error").is_none());
+        assert_eq!(json[2]["content"][1]["tool_use_id"], "toolu_2");
+        assert_eq!(json[2]["content"][1]["is_error"], true);
+    }
+
+    #[test]
+    fn test_request_includes_tools() {
+        let adapter = AnthropicAdapter::new("test-key");
+        let request = LLMRequest::new("claude-3-5-sonnet-20241022", vec![Message::user("Hi")])
+            .with_tools(vec![ToolDefinition {
+                name: "calculator".to_string(),
+                description: "Do math".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+            }]);
+
+        let body = serde_json::to_value(adapter.build_request(&request, false)).unwrap();
+
+        assert_eq!(body["tools"][0]["name"], "calculator");
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["messages"][0]["content"], "Hi");
+    }
+
+    #[test]
+    fn test_parse_tool_use_response() {
+        let response: AnthropicResponse = serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-5-sonnet-20241022",
+            "content": [
+                {"type": "text", "text": "Calculating."},
+                {"type": "tool_use", "id": "toolu_1", "name": "calculator",
+                 "input": {"expression": "(2 + 3) * 4"}},
+                {"type": "thinking", "thinking": "..."}
+            ],
+            "stop_reason": "tool_use",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 10, "output_tokens": 20}
+        }))
+        .unwrap();
+
+        assert_eq!(response.content.len(), 3);
+        assert!(matches!(
+            &response.content[1],
+            AnthropicContentBlock::ToolUse { name, input, .. }
+                if name == "calculator" && input["expression"] == "(2 + 3) * 4"
+        ));
+        assert_eq!(
+            convert_stop_reason(response.stop_reason.as_deref()),
+            FinishReason::ToolCalls
+        );
+    }
+
+    #[test]
+    fn test_parse_tool_use_stream_events() {
+        let mut state = StreamState::default();
+        let start = state.handle(
+            "content_block_start",
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"calculator","input":{}}}"#,
+        )
+        .unwrap()
+        .unwrap();
+        let delta = start.tool_call_delta.unwrap();
+        assert_eq!(delta.index, 1);
+        assert_eq!(delta.id.as_deref(), Some("toolu_1"));
+        assert_eq!(delta.name.as_deref(), Some("calculator"));
+
+        let args = state.handle(
+            "content_block_delta",
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"expr"}}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            args.tool_call_delta.unwrap().arguments.as_deref(),
+            Some("{\"expr")
+        );
+
+        let text = state.handle(
+            "content_block_delta",
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(text.content, "Hi");
+        assert!(text.tool_call_delta.is_none());
+
+        assert!(state.handle("ping", "{}").is_none());
+    }
+
+    #[test]
+    fn test_stream_final_chunk_reports_usage() {
+        let mut state = StreamState {
+            pricing: (3.0, 15.0),
+            ..Default::default()
+        };
+
+        assert!(state
+            .handle(
+                "message_start",
+                r#"{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":1000,"output_tokens":1}}}"#,
+            )
+            .is_none());
+        assert!(state
+            .handle(
+                "message_delta",
+                r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":500}}"#,
+            )
+            .is_none());
+
+        let last = state
+            .handle("message_stop", r#"{"type":"message_stop"}"#)
+            .unwrap()
+            .unwrap();
+        assert!(last.is_final);
+        assert_eq!(last.finish_reason, Some(FinishReason::ToolCalls));
+
+        let usage = last.usage.unwrap();
+        assert_eq!(usage.input_tokens, 1000);
+        assert_eq!(usage.output_tokens, 500);
+        assert!((usage.cost.unwrap() - 0.0105).abs() < 0.0001);
     }
 
     #[tokio::test]
@@ -5,15 +5,21 @@
 //! - OpenAI (GPT-4, GPT-3.5)
 //! - Google (Gemini)
 //! - AWS Bedrock
-//! - Local models (Ollama, vLLM)
+//! - Local models (Ollama, vLLM, llama.cpp, LM Studio) via the OpenAI-compatible adapter
 
 pub mod adapter;
 pub mod anthropic;
+pub mod openai;
+pub mod retry;
+pub mod stream;
 pub mod types;
 
 // Re-export commonly used types
 pub use adapter::{LLMAdapter, LLMError, LLMResult, LLMStream};
 pub use anthropic::AnthropicAdapter;
+pub use openai::OpenAIAdapter;
+pub use retry::{Backoff, RetryPolicy, RetryingAdapter};
+pub use stream::StreamAccumulator;
 pub use types::{
     FinishReason, LLMRequest, LLMResponse, Message, MessageRole, StreamChunk, TokenUsage, ToolCall,
     ToolDefinition,
@@ -0,0 +1,838 @@
+//! OpenAI-compatible chat completions adapter
+//!
+//! Works with the OpenAI API and any server exposing `/v1/chat/completions`
+//! (vLLM, llama.cpp server, Ollama, LM Studio) by pointing `base_url` at it.
+
+use crate::adapter::{retry_after_secs, LLMAdapter, LLMError, LLMResult, LLMStream};
+use crate::stream::parse_tool_arguments;
+use crate::types::*;
+use async_trait::async_trait;
+use futures::stream::StreamExt;
+use namra_middleware::observability::{llm_request_span, record_llm_metrics};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::Instrument;
+
+const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
+
+/// OpenAI chat completions adapter
+pub struct OpenAIAdapter {
+    client: Client,
+    api_key: Option<String>,
+    base_url: String,
+    timeout: Duration,
+}
+
+impl OpenAIAdapter {
+    /// Create a new OpenAI adapter
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key: Some(api_key.into()),
+            base_url: OPENAI_API_BASE.to_string(),
+            timeout: Duration::from_secs(120),
+        }
+    }
+
+    /// Create builder for custom configuration
+    pub fn builder() -> OpenAIAdapterBuilder {
+        OpenAIAdapterBuilder::default()
+    }
+
+    /// Convert our Message type to OpenAI's format
+    fn convert_messages(&self, messages: &[Message]) -> Vec<OpenAIMessage> {
+        messages
+            .iter()
+            .map(|msg| match msg.role {
+                MessageRole::System => OpenAIMessage::text("system", &msg.content),
+                MessageRole::User => OpenAIMessage::text("user", &msg.content),
+                MessageRole::Assistant => {
+                    let tool_calls = msg.tool_calls.as_deref().unwrap_or_default();
+                    if tool_calls.is_empty() {
+                        OpenAIMessage::text("assistant", &msg.content)
+                    } else {
+                        OpenAIMessage {
+                            role: "assistant".to_string(),
+                            // Content may be null when the model only calls tools
+                            content: Some(msg.content.clone()).filter(|c| !c.is_empty()),
+                            tool_calls: Some(
+                                tool_calls
+                                    .iter()
+                                    .map(|call| OpenAIToolCall {
+                                        id: call.id.clone(),
+                                        call_type: "function".to_string(),
+                                        function: OpenAIFunctionCall {
+                                            name: call.name.clone(),
+                                            arguments: call.arguments.to_string(),
+                                        },
+                                    })
+                                    .collect(),
+                            ),
+                            tool_call_id: None,
+                        }
+                    }
+                }
+                MessageRole::Tool => OpenAIMessage {
+                    role: "tool".to_string(),
+                    content: Some(msg.content.clone()),
+                    tool_calls: None,
+                    tool_call_id: msg.tool_call_id.clone(),
+                },
+            })
+            .collect()
+    }
+
+    /// Build the request body for the chat completions API
+    fn build_request(&self, request: &LLMRequest, stream: bool) -> OpenAIRequest {
+        OpenAIRequest {
+            model: request.model.clone(),
+            messages: self.convert_messages(&request.messages),
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            stop: request.stop_sequences.clone(),
+            stream,
+            // Ask for usage in the final streamed chunk
+            stream_options: stream.then_some(OpenAIStreamOptions {
+                include_usage: true,
+            }),
+            tools: request
+                .tools
+                .as_ref()
+                .filter(|tools| !tools.is_empty())
+                .map(|tools| {
+                    tools
+                        .iter()
+                        .map(|tool| OpenAITool {
+                            tool_type: "function".to_string(),
+                            function: OpenAIFunction {
+                                name: tool.name.clone(),
+                                description: tool.description.clone(),
+                                parameters: tool.input_schema.clone(),
+                            },
+                        })
+                        .collect()
+                }),
+        }
+    }
+
+    /// Create a POST request to the chat completions endpoint
+    fn post(&self, body: &OpenAIRequest) -> RequestBuilder {
+        let mut request = self
+            .client
+            .post(format!(
+                "{}/chat/completions",
+                self.base_url.trim_end_matches('/')
+            ))
+            .header("content-type", "application/json")
+            .timeout(self.timeout)
+            .json(body);
+
+        // Local servers typically don't require a key
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        request
+    }
+
+    /// Per-million-token pricing for known OpenAI models
+    ///
+    /// Returns `None` for unknown models, e.g. those served locally.
+    fn pricing(&self, model: &str) -> Option<(f64, f64)> {
+        // Pricing as of 2024 (per million tokens)
+        Some(match model {
+            m if m.starts_with("gpt-4o-mini") => (0.15, 0.60),
+            m if m.starts_with("gpt-4o") => (2.50, 10.0),
+            m if m.starts_with("gpt-4-turbo") => (10.0, 30.0),
+            m if m.starts_with("gpt-4") => (30.0, 60.0),
+            m if m.starts_with("gpt-3.5-turbo") => (0.50, 1.50),
+            m if m.starts_with("o1-mini") || m.starts_with("o3-mini") => (1.10, 4.40),
+            m if m.starts_with("o1") => (15.0, 60.0),
+            _ => return None,
+        })
+    }
+
+    /// Calculate cost for OpenAI models
+    fn calculate_cost(&self, input_tokens: u32, output_tokens: u32, model: &str) -> Option<f64> {
+        let (input_price, output_price) = self.pricing(model)?;
+
+        let input_cost = (input_tokens as f64 / 1_000_000.0) * input_price;
+        let output_cost = (output_tokens as f64 / 1_000_000.0) * output_price;
+
+        Some(input_cost + output_cost)
+    }
+
+    fn handle_error(&self, status: u16, retry_after: Option<u64>, body: String) -> LLMError {
+        match status {
+            401 => LLMError::AuthenticationError("Invalid API key".to_string()),
+            429 => LLMError::RateLimited { retry_after },
+            400 => LLMError::InvalidRequest(body),
+            _ => LLMError::ApiError {
+                status,
+                message: body,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for OpenAIAdapter {
+    fn provider_name(&self) -> &str {
+        "openai"
+    }
+
+    async fn generate(&self, request: LLMRequest) -> LLMResult<LLMResponse> {
+        let span = llm_request_span("openai", &request.model);
+
+        async move {
+            let body = self.build_request(&request, false);
+
+            let response = self.post(&body).send().await?;
+
+            let status = response.status();
+
+            if !status.is_success() {
+                let retry_after = retry_after_secs(response.headers());
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(self.handle_error(status.as_u16(), retry_after, error_text));
+            }
+
+            let openai_response: OpenAIResponse = response.json().await?;
+
+            let choice =
+                openai_response.choices.into_iter().next().ok_or_else(|| {
+                    LLMError::Unknown("Response contained no choices".to_string())
+                })?;
+
+            let tool_calls: Vec<ToolCall> = choice
+                .message
+                .tool_calls
+                .unwrap_or_default()
+                .into_iter()
+                .map(|call| ToolCall {
+                    id: call.id,
+                    name: call.function.name,
+                    arguments: parse_tool_arguments(&call.function.arguments),
+                })
+                .collect();
+
+            let usage = openai_response.usage.unwrap_or_default();
+            let cost = self
+                .calculate_cost(usage.prompt_tokens, usage.completion_tokens, &request.model)
+                .unwrap_or(0.0);
+
+            let token_usage =
+                TokenUsage::new(usage.prompt_tokens, usage.completion_tokens).with_cost(cost);
+
+            // Record LLM metrics on span
+            let current_span = tracing::Span::current();
+            record_llm_metrics(
+                &current_span,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                cost,
+            );
+
+            Ok(LLMResponse {
+                content: choice.message.content.unwrap_or_default(),
+                role: MessageRole::Assistant,
+                tool_calls: if tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(tool_calls)
+                },
+                usage: token_usage,
+                finish_reason: convert_finish_reason(choice.finish_reason.as_deref()),
+                metadata: HashMap::new(),
+            })
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn stream(&self, request: LLMRequest) -> LLMResult<LLMStream> {
+        let body = self.build_request(&request, true);
+
+        let response = self.post(&body).send().await?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let retry_after = retry_after_secs(response.headers());
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(self.handle_error(status.as_u16(), retry_after, error_text));
+        }
+
+        let pricing = self.pricing(&request.model);
+
+        // Parse SSE stream, carrying usage and finish reason to the final chunk
+        let stream = response.bytes_stream();
+        let sse_stream = eventsource_stream::EventStream::new(stream);
+
+        let mapped_stream = sse_stream
+            .scan(StreamState::default(), move |state, event_result| {
+                let items = match event_result {
+                    Ok(event) => state.handle(&event.data, pricing),
+                    Err(e) => vec![Err(LLMError::StreamError(e.to_string()))],
+                };
+                futures::future::ready(Some(items))
+            })
+            .flat_map(futures::stream::iter);
+
+        Ok(Box::pin(mapped_stream))
+    }
+
+    fn max_context_tokens(&self, model: &str) -> Option<u32> {
+        Some(match model {
+            m if m.starts_with("gpt-4o") || m.starts_with("gpt-4-turbo") => 128_000,
+            m if m.starts_with("o1") || m.starts_with("o3") => 128_000,
+            m if m.starts_with("gpt-4") => 8_192,
+            m if m.starts_with("gpt-3.5-turbo") => 16_385,
+            _ => return None,
+        })
+    }
+
+    fn estimate_cost(&self, input_tokens: u32, output_tokens: u32, model: &str) -> Option<f64> {
+        self.calculate_cost(input_tokens, output_tokens, model)
+    }
+}
+
+/// Map OpenAI's finish reason to ours
+fn convert_finish_reason(finish_reason: Option<&str>) -> FinishReason {
+    match finish_reason {
+        Some("stop") => FinishReason::Stop,
+        Some("length") => FinishReason::Length,
+        Some("tool_calls") | Some("function_call") => FinishReason::ToolCalls,
+        Some("content_filter") => FinishReason::ContentFilter,
+        _ => FinishReason::Other,
+    }
+}
+
+/// State carried across streamed chunks
+#[derive(Default)]
+struct StreamState {
+    usage: Option<OpenAIUsage>,
+    finish_reason: Option<FinishReason>,
+}
+
+impl StreamState {
+    /// Convert one SSE data payload into stream chunks
+    ///
+    /// A payload carrying several tool call deltas (parallel tool calls)
+    /// becomes one chunk per delta.
+    fn handle(&mut self, data: &str, pricing: Option<(f64, f64)>) -> Vec<LLMResult<StreamChunk>> {
+        if data.trim() == "[DONE]" {
+            let usage = self.usage.take().map(|u| {
+                let usage = TokenUsage::new(u.prompt_tokens, u.completion_tokens);
+                match pricing {
+                    Some((input_price, output_price)) => usage.with_cost(
+                        (u.prompt_tokens as f64 / 1_000_000.0) * input_price
+                            + (u.completion_tokens as f64 / 1_000_000.0) * output_price,
+                    ),
+                    None => usage,
+                }
+            });
+
+            return vec![Ok(StreamChunk {
+                content: String::new(),
+                tool_call_delta: None,
+                is_final: true,
+                usage,
+                finish_reason: Some(self.finish_reason.unwrap_or(FinishReason::Stop)),
+                metadata: HashMap::new(),
+            })];
+        }
+
+        let chunk: OpenAIStreamChunk = match serde_json::from_str(data) {
+            Ok(chunk) => chunk,
+            Err(e) => return vec![Err(LLMError::SerializationError(e))],
+        };
+
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        let Some(choice) = chunk.choices.into_iter().next() else {
+            return Vec::new();
+        };
+        if let Some(reason) = choice.finish_reason.as_deref() {
+            self.finish_reason = Some(convert_finish_reason(Some(reason)));
+        }
+
+        let mut deltas: Vec<Option<ToolCallDelta>> = choice
+            .delta
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| {
+                Some(ToolCallDelta {
+                    index: call.index,
+                    id: call.id,
+                    name: call.function.as_ref().and_then(|f| f.name.clone()),
+                    arguments: call.function.and_then(|f| f.arguments),
+                })
+            })
+            .collect();
+
+        let content = choice.delta.content.unwrap_or_default();
+        if deltas.is_empty() {
+            if content.is_empty() {
+                return Vec::new();
+            }
+            deltas.push(None);
+        }
+
+        // Any text goes with the first delta
+        let mut content = Some(content);
+        deltas
+            .into_iter()
+            .map(|tool_call_delta| {
+                Ok(StreamChunk {
+                    content: content.take().unwrap_or_default(),
+                    tool_call_delta,
+                    is_final: false,
+                    usage: None,
+                    finish_reason: None,
+                    metadata: HashMap::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Builder for OpenAI adapter
+#[derive(Default)]
+pub struct OpenAIAdapterBuilder {
+    api_key: Option<String>,
+    base_url: Option<String>,
+    timeout_secs: Option<u64>,
+}
+
+impl OpenAIAdapterBuilder {
+    /// Set the API key (optional for local OpenAI-compatible servers)
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Set the API base URL, including the version path (e.g. `http://localhost:11434/v1`)
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = Some(timeout_secs);
+        self
+    }
+
+    pub fn build(self) -> OpenAIAdapter {
+        OpenAIAdapter {
+            client: Client::new(),
+            api_key: self.api_key,
+            base_url: self.base_url.unwrap_or_else(|| OPENAI_API_BASE.to_string()),
+            timeout: Duration::from_secs(self.timeout_secs.unwrap_or(120)),
+        }
+    }
+}
+
+// OpenAI API types
+
+#[derive(Debug, Serialize)]
+struct OpenAIRequest {
+    model: String,
+    messages: Vec<OpenAIMessage>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+
+    stream: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAIStreamOptions>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<OpenAITool>>,
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAIStreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAIMessage {
+    role: String,
+
+    content: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<OpenAIToolCall>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl OpenAIMessage {
+    fn text(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: Some(content.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAITool {
+    #[serde(rename = "type")]
+    tool_type: String,
+    function: OpenAIFunction,
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAIFunction {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAIToolCall {
+    id: String,
+    #[serde(rename = "type", default = "default_call_type")]
+    call_type: String,
+    function: OpenAIFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAIFunctionCall {
+    name: String,
+    /// JSON-encoded arguments
+    arguments: String,
+}
+
+fn default_call_type() -> String {
+    "function".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIResponse {
+    choices: Vec<OpenAIChoice>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIChoice {
+    message: OpenAIMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+struct OpenAIUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIStreamChunk {
+    #[serde(default)]
+    choices: Vec<OpenAIStreamChoice>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIStreamChoice {
+    delta: OpenAIDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<OpenAIToolCallDelta>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIToolCallDelta {
+    #[serde(default)]
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<OpenAIFunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIFunctionDelta {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_cost_calculation() {
+        let adapter = OpenAIAdapter::new("test-key");
+
+        // GPT-4o: $2.50/$10 per million tokens
+        let cost = adapter
+            .calculate_cost(1000, 500, "gpt-4o-2024-08-06")
+            .unwrap();
+        assert!((cost - 0.0075).abs() < 0.0001);
+
+        // GPT-4o mini is matched before GPT-4o
+        let cost = adapter.calculate_cost(1000, 500, "gpt-4o-mini").unwrap();
+        assert!((cost - 0.00045).abs() < 0.00001);
+
+        // Local models have no known price
+        assert!(adapter.calculate_cost(1000, 500, "llama3.1:8b").is_none());
+    }
+
+    #[test]
+    fn test_message_conversion() {
+        let adapter = OpenAIAdapter::new("test-key");
+
+        let mut assistant = Message::assistant("");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "calculator".to_string(),
+            arguments: serde_json::json!({"expression": "2 + 2"}),
+        }]);
+
+        let messages = vec![
+            Message::system("You are helpful"),
+            Message::user("What is 2 + 2?"),
+            assistant,
+            Message::tool("4", "call_1"),
+        ];
+
+        let json = serde_json::to_value(adapter.convert_messages(&messages)).unwrap();
+
+        assert_eq!(json[0]["role"], "system");
+        assert_eq!(json[2]["content"], serde_json::Value::Null);
+        assert_eq!(json[2]["tool_calls"][0]["type"], "function");
+        assert_eq!(
+            json[2]["tool_calls"][0]["function"]["arguments"],
+            r#"{"expression":"2 + 2"}"#
+        );
+        assert_eq!(json[3]["role"], "tool");
+        assert_eq!(json[3]["tool_call_id"], "call_1");
+    }
+
+    #[tokio::test]
+    async fn test_generate_with_tool_calls() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "choices": [{
+                    "index": 0,
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {
+                                "name": "http",
+                                "arguments": "{\"url\": \"https://example.com/f(x)\"}"
+                            }
+                        }]
+                    },
+                    "finish_reason": "tool_calls"
+                }],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 8, "total_tokens": 20}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let adapter = OpenAIAdapter::builder()
+            .api_key("test-key")
+            .base_url(format!("{}/v1", server.uri()))
+            .build();
+
+        let request = LLMRequest::new("gpt-4o", vec![Message::user("Fetch it")]).with_tools(vec![
+            ToolDefinition {
+                name: "http".to_string(),
+                description: "Make HTTP requests".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+            },
+        ]);
+
+        let response = adapter.generate(request).await.unwrap();
+
+        let tool_calls = response.tool_calls.unwrap();
+        assert_eq!(tool_calls[0].name, "http");
+        assert_eq!(tool_calls[0].arguments["url"], "https://example.com/f(x)");
+        assert_eq!(response.finish_reason, FinishReason::ToolCalls);
+        assert_eq!(response.usage.total_tokens, 20);
+
+        // Tools were sent in OpenAI's function format
+        let received = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+        assert_eq!(body["tools"][0]["type"], "function");
+        assert_eq!(body["tools"][0]["function"]["name"], "http");
+        assert_eq!(body["stream"], false);
+    }
+
+    #[tokio::test]
+    async fn test_stream_with_usage() {
+        let server = MockServer::start().await;
+
+        let sse_body = [
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":"lo"}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"calculator","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"expression\":\"1+1\"}"}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":5,"completion_tokens":7,"total_tokens":12}}"#,
+            "[DONE]",
+        ]
+        .iter()
+        .map(|data| format!("data: {}\n\n", data))
+        .collect::<String>();
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(sse_body),
+            )
+            .mount(&server)
+            .await;
+
+        // No API key, as with a local server
+        let adapter = OpenAIAdapter::builder()
+            .base_url(format!("{}/v1", server.uri()))
+            .build();
+
+        let request = LLMRequest::new("llama3.1", vec![Message::user("Hi")]).with_streaming(true);
+        let chunks: Vec<StreamChunk> = adapter
+            .stream(request)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        let text: String = chunks.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(text, "Hello");
+
+        let deltas: Vec<_> = chunks
+            .iter()
+            .filter_map(|c| c.tool_call_delta.as_ref())
+            .collect();
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].name.as_deref(), Some("calculator"));
+        assert_eq!(
+            deltas[1].arguments.as_deref(),
+            Some(r#"{"expression":"1+1"}"#)
+        );
+
+        let last = chunks.last().unwrap();
+        assert!(last.is_final);
+        assert_eq!(last.finish_reason, Some(FinishReason::ToolCalls));
+        let usage = last.usage.as_ref().unwrap();
+        assert_eq!(usage.total_tokens, 12);
+        assert!(usage.cost.is_none());
+
+        let received = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+        assert_eq!(body["stream_options"]["include_usage"], true);
+        assert!(received[0].headers.get("authorization").is_none());
+    }
+
+    #[test]
+    fn test_stream_parallel_tool_calls() {
+        let mut state = StreamState::default();
+        let data = r#"{"choices":[{"index":0,"delta":{"tool_calls":[
+            {"index":0,"id":"call_1","type":"function","function":{"name":"calculator","arguments":"{}"}},
+            {"index":1,"id":"call_2","type":"function","function":{"name":"search","arguments":"{}"}}
+        ]}}]}"#;
+
+        let chunks: Vec<StreamChunk> = state
+            .handle(data, None)
+            .into_iter()
+            .map(|chunk| chunk.unwrap())
+            .collect();
+        assert_eq!(chunks.len(), 2);
+        let deltas: Vec<_> = chunks
+            .iter()
+            .map(|c| c.tool_call_delta.as_ref().unwrap())
+            .collect();
+        assert_eq!(
+            (deltas[0].index, deltas[0].id.as_deref()),
+            (0, Some("call_1"))
+        );
+        assert_eq!(
+            (deltas[1].index, deltas[1].name.as_deref()),
+            (1, Some("search"))
+        );
+
+        // Chunks with neither text nor tool calls are dropped
+        let empty = r#"{"choices":[{"index":0,"delta":{}}]}"#;
+        assert!(state.handle(empty, None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_error_mapping() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("retry-after", "3")
+                    .set_body_string("slow down"),
+            )
+            .mount(&server)
+            .await;
+
+        let adapter = OpenAIAdapter::builder()
+            .api_key("test-key")
+            .base_url(server.uri())
+            .build();
+
+        let result = adapter
+            .generate(LLMRequest::new("gpt-4o", vec![Message::user("Hi")]))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(LLMError::RateLimited {
+                retry_after: Some(3)
+            })
+        ));
+    }
+}
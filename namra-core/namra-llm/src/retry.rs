@@ -0,0 +1,383 @@
+//! Retrying wrapper for LLM adapters
+//!
+//! [`RetryingAdapter`] re-sends requests that fail with a retryable
+//! [`LLMError`] (see [`LLMError::is_retryable`]), waiting between attempts as
+//! set by a [`RetryPolicy`]. A `retry_after` from the provider takes precedence
+//! over the computed backoff, but is still capped at the policy's `max_delay`.
+//!
+//! For streaming requests only opening the stream is retried; an error after
+//! chunks have been delivered is passed through to the caller.
+
+use crate::adapter::{LLMAdapter, LLMError, LLMResult, LLMStream};
+use crate::types::{LLMRequest, LLMResponse};
+use async_trait::async_trait;
+use namra_middleware::observability::{llm_retry_span, record_llm_attempts, record_llm_retry};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::Instrument;
+
+/// How the delay grows between attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// `initial_delay * 2^(n-1)`
+    Exponential,
+    /// `initial_delay * n`
+    Linear,
+    /// `initial_delay` every time
+    Constant,
+}
+
+impl FromStr for Backoff {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exponential" => Ok(Backoff::Exponential),
+            "linear" => Ok(Backoff::Linear),
+            "constant" => Ok(Backoff::Constant),
+            _ => Err(format!("Unknown backoff: {}", s)),
+        }
+    }
+}
+
+/// When and how often to retry a failed request
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first request
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+    pub initial_delay: Duration,
+    /// Upper bound for a computed delay
+    pub max_delay: Duration,
+    /// Scale each delay by a random factor between 0.5 and 1.0
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Create a policy with the given number of attempts and default delays
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Set the backoff curve
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the delay before the first retry
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Set the upper bound for a computed delay
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Enable or disable jitter
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Computed delay before retry number `retry` (starting at 1)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let retry = retry.max(1);
+        let delay = match self.backoff {
+            Backoff::Exponential => self
+                .initial_delay
+                .saturating_mul(2u32.saturating_pow(retry - 1)),
+            Backoff::Linear => self.initial_delay.saturating_mul(retry),
+            Backoff::Constant => self.initial_delay,
+        }
+        .min(self.max_delay);
+
+        if self.jitter {
+            delay.mul_f64(0.5 + random_fraction() * 0.5)
+        } else {
+            delay
+        }
+    }
+
+    /// Delay before retrying after `error`, preferring the provider's
+    /// `retry_after` up to `max_delay`
+    pub fn delay_for(&self, retry: u32, error: &LLMError) -> Duration {
+        match error.retry_after() {
+            Some(delay) => delay.min(self.max_delay),
+            None => self.delay(retry),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Backoff::Exponential,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+/// Random number in `[0, 1)` from the std hasher's random keys
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Adapter that retries transient failures of the adapter it wraps
+pub struct RetryingAdapter {
+    inner: Arc<dyn LLMAdapter>,
+    policy: RetryPolicy,
+}
+
+impl RetryingAdapter {
+    /// Wrap an adapter with a retry policy
+    pub fn new(inner: Arc<dyn LLMAdapter>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// The retry policy in use
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Run `attempt` until it succeeds, fails permanently, or attempts run out
+    async fn with_retries<T, F, Fut>(&self, model: &str, attempt: F) -> LLMResult<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = LLMResult<T>>,
+    {
+        let span = llm_retry_span(self.inner.provider_name(), model, self.policy.max_attempts);
+
+        async {
+            let mut attempts = 1;
+            loop {
+                match attempt().await {
+                    Err(e) if e.is_retryable() && attempts < self.policy.max_attempts => {
+                        let delay = self.policy.delay_for(attempts, &e);
+                        record_llm_retry(&span, attempts, &e.to_string(), delay.as_millis() as u64);
+                        tokio::time::sleep(delay).await;
+                        attempts += 1;
+                    }
+                    result => {
+                        record_llm_attempts(&span, attempts);
+                        return result;
+                    }
+                }
+            }
+        }
+        .instrument(span.clone())
+        .await
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for RetryingAdapter {
+    fn provider_name(&self) -> &str {
+        self.inner.provider_name()
+    }
+
+    async fn generate(&self, request: LLMRequest) -> LLMResult<LLMResponse> {
+        self.with_retries(&request.model, || self.inner.generate(request.clone()))
+            .await
+    }
+
+    async fn stream(&self, request: LLMRequest) -> LLMResult<LLMStream> {
+        self.with_retries(&request.model, || self.inner.stream(request.clone()))
+            .await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.inner.supports_tools()
+    }
+
+    fn max_context_tokens(&self, model: &str) -> Option<u32> {
+        self.inner.max_context_tokens(model)
+    }
+
+    fn estimate_cost(&self, input_tokens: u32, output_tokens: u32, model: &str) -> Option<f64> {
+        self.inner.estimate_cost(input_tokens, output_tokens, model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FinishReason, Message, MessageRole, TokenUsage};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Adapter that returns the scripted errors before succeeding
+    struct FlakyLLM {
+        errors: Mutex<Vec<LLMError>>,
+        calls: Mutex<u32>,
+    }
+
+    impl FlakyLLM {
+        fn new(mut errors: Vec<LLMError>) -> Self {
+            errors.reverse();
+            Self {
+                errors: Mutex::new(errors),
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl LLMAdapter for FlakyLLM {
+        fn provider_name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn generate(&self, _request: LLMRequest) -> LLMResult<LLMResponse> {
+            *self.calls.lock().unwrap() += 1;
+            if let Some(err) = self.errors.lock().unwrap().pop() {
+                return Err(err);
+            }
+            Ok(LLMResponse {
+                content: "ok".to_string(),
+                role: MessageRole::Assistant,
+                tool_calls: None,
+                usage: TokenUsage::new(1, 1),
+                finish_reason: FinishReason::Stop,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn stream(&self, _request: LLMRequest) -> LLMResult<LLMStream> {
+            Err(LLMError::StreamError("not supported".to_string()))
+        }
+
+        fn max_context_tokens(&self, _model: &str) -> Option<u32> {
+            None
+        }
+
+        fn estimate_cost(&self, _input: u32, _output: u32, _model: &str) -> Option<f64> {
+            None
+        }
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts)
+            .with_initial_delay(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    fn request() -> LLMRequest {
+        LLMRequest::new("test-model", vec![Message::user("Hi")])
+    }
+
+    fn overloaded() -> LLMError {
+        LLMError::ApiError {
+            status: 529,
+            message: "overloaded".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors() {
+        let llm = Arc::new(FlakyLLM::new(vec![
+            LLMError::RateLimited {
+                retry_after: Some(0),
+            },
+            overloaded(),
+        ]));
+        let adapter = RetryingAdapter::new(llm.clone(), fast_policy(3));
+
+        let response = adapter.generate(request()).await.unwrap();
+
+        assert_eq!(response.content, "ok");
+        assert_eq!(llm.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let llm = Arc::new(FlakyLLM::new(vec![
+            overloaded(),
+            overloaded(),
+            overloaded(),
+        ]));
+        let adapter = RetryingAdapter::new(llm.clone(), fast_policy(2));
+
+        let result = adapter.generate(request()).await;
+
+        assert!(matches!(
+            result,
+            Err(LLMError::ApiError { status: 529, .. })
+        ));
+        assert_eq!(llm.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_permanent_errors() {
+        let llm = Arc::new(FlakyLLM::new(vec![LLMError::AuthenticationError(
+            "Invalid API key".to_string(),
+        )]));
+        let adapter = RetryingAdapter::new(llm.clone(), fast_policy(5));
+
+        assert!(adapter.generate(request()).await.is_err());
+        assert_eq!(llm.calls(), 1);
+    }
+
+    #[test]
+    fn test_backoff_delays() {
+        let policy = RetryPolicy::new(5)
+            .with_initial_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(5))
+            .with_jitter(false);
+
+        let delays: Vec<u64> = (1..=4).map(|n| policy.delay(n).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5]);
+
+        let linear = policy.clone().with_backoff(Backoff::Linear);
+        assert_eq!(linear.delay(3), Duration::from_secs(3));
+
+        let constant = policy.clone().with_backoff(Backoff::Constant);
+        assert_eq!(constant.delay(4), Duration::from_secs(1));
+
+        // The provider's retry_after wins over the computed delay, up to
+        // max_delay
+        let rate_limited = |secs| LLMError::RateLimited {
+            retry_after: Some(secs),
+        };
+        assert_eq!(
+            policy.delay_for(1, &rate_limited(3)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            policy.delay_for(1, &rate_limited(20)),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new(3).with_initial_delay(Duration::from_secs(2));
+
+        for _ in 0..50 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Helpers for consuming streamed LLM responses
+
+use crate::types::{
+    FinishReason, LLMResponse, MessageRole, StreamChunk, TokenUsage, ToolCall, ToolCallDelta,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Folds [`StreamChunk`]s into a complete [`LLMResponse`]
+///
+/// Text is concatenated, tool call deltas are merged by their `index`,
+/// usage and finish reason are taken from the final chunk, and metadata from
+/// every chunk is merged.
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    content: String,
+    tool_calls: BTreeMap<usize, PartialToolCall>,
+    usage: Option<TokenUsage>,
+    finish_reason: Option<FinishReason>,
+    metadata: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl StreamAccumulator {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk to the response
+    pub fn push(&mut self, chunk: &StreamChunk) {
+        self.content.push_str(&chunk.content);
+
+        if let Some(delta) = &chunk.tool_call_delta {
+            self.push_tool_call_delta(delta);
+        }
+
+        if let Some(usage) = &chunk.usage {
+            self.usage = Some(usage.clone());
+        }
+
+        if let Some(finish_reason) = chunk.finish_reason {
+            self.finish_reason = Some(finish_reason);
+        }
+
+        self.metadata.extend(chunk.metadata.clone());
+    }
+
+    /// Text received so far
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    fn push_tool_call_delta(&mut self, delta: &ToolCallDelta) {
+        let call = self.tool_calls.entry(delta.index).or_default();
+
+        if let Some(id) = &delta.id {
+            call.id = id.clone();
+        }
+        if let Some(name) = &delta.name {
+            call.name.push_str(name);
+        }
+        if let Some(arguments) = &delta.arguments {
+            call.arguments.push_str(arguments);
+        }
+    }
+
+    /// Build the complete response
+    pub fn finish(self) -> LLMResponse {
+        let tool_calls: Vec<ToolCall> = self
+            .tool_calls
+            .into_values()
+            .map(|call| ToolCall {
+                id: call.id,
+                name: call.name,
+                arguments: parse_tool_arguments(&call.arguments),
+            })
+            .collect();
+
+        let finish_reason = self.finish_reason.unwrap_or(if tool_calls.is_empty() {
+            FinishReason::Stop
+        } else {
+            FinishReason::ToolCalls
+        });
+
+        LLMResponse {
+            content: self.content,
+            role: MessageRole::Assistant,
+            tool_calls: if tool_calls.is_empty() {
+                None
+            } else {
+                Some(tool_calls)
+            },
+            usage: self.usage.unwrap_or_else(|| TokenUsage::new(0, 0)),
+            finish_reason,
+            metadata: self.metadata,
+        }
+    }
+}
+
+/// Parse JSON-encoded tool call arguments, keeping malformed JSON as a string
+pub(crate) fn parse_tool_arguments(arguments: &str) -> serde_json::Value {
+    if arguments.trim().is_empty() {
+        return serde_json::json!({});
+    }
+    serde_json::from_str(arguments)
+        .unwrap_or_else(|_| serde_json::Value::String(arguments.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_chunk(content: &str) -> StreamChunk {
+        StreamChunk {
+            content: content.to_string(),
+            tool_call_delta: None,
+            is_final: false,
+            usage: None,
+            finish_reason: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn delta_chunk(index: usize, id: Option<&str>, name: Option<&str>, args: &str) -> StreamChunk {
+        StreamChunk {
+            tool_call_delta: Some(ToolCallDelta {
+                index,
+                id: id.map(String::from),
+                name: name.map(String::from),
+                arguments: Some(args.to_string()),
+            }),
+            ..text_chunk("")
+        }
+    }
+
+    #[test]
+    fn test_accumulates_text_and_usage() {
+        let mut acc = StreamAccumulator::new();
+        acc.push(&text_chunk("Hel"));
+        acc.push(&text_chunk("lo"));
+        assert_eq!(acc.content(), "Hello");
+
+        acc.push(&StreamChunk {
+            is_final: true,
+            usage: Some(TokenUsage::new(3, 2)),
+            finish_reason: Some(FinishReason::Length),
+            ..text_chunk("")
+        });
+
+        let response = acc.finish();
+        assert_eq!(response.content, "Hello");
+        assert!(response.tool_calls.is_none());
+        assert_eq!(response.usage.total_tokens, 5);
+        assert_eq!(response.finish_reason, FinishReason::Length);
+    }
+
+    #[test]
+    fn test_merges_tool_call_deltas_by_index() {
+        let mut acc = StreamAccumulator::new();
+        acc.push(&delta_chunk(1, Some("call_a"), Some("search"), ""));
+        acc.push(&delta_chunk(
+            2,
+            Some("call_b"),
+            Some("calculator"),
+            "{\"expr",
+        ));
+        acc.push(&delta_chunk(1, None, None, "{\"q\": \"rust\"}"));
+        acc.push(&delta_chunk(2, None, None, "ession\": \"1+1\"}"));
+
+        let response = acc.finish();
+        let calls = response.tool_calls.unwrap();
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "call_a");
+        assert_eq!(calls[0].arguments["q"], "rust");
+        assert_eq!(calls[1].name, "calculator");
+        assert_eq!(calls[1].arguments["expression"], "1+1");
+        assert_eq!(response.finish_reason, FinishReason::ToolCalls);
+    }
+}
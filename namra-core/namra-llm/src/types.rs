@@ -17,6 +17,10 @@ pub struct Message {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+
+    /// Marks a tool result message as an error
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_error: bool,
 }
 
 impl Message {
@@ -27,6 +31,7 @@ impl Message {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            is_error: false,
         }
     }
 
@@ -37,6 +42,7 @@ impl Message {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            is_error: false,
         }
     }
 
@@ -47,6 +53,7 @@ impl Message {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            is_error: false,
         }
     }
 
@@ -57,6 +64,14 @@ impl Message {
             name: None,
             tool_calls: None,
             tool_call_id: Some(tool_call_id.into()),
+            is_error: false,
+        }
+    }
+
+    pub fn tool_error(content: impl Into<String>, tool_call_id: impl Into<String>) -> Self {
+        Self {
+            is_error: true,
+            ..Self::tool(content, tool_call_id)
         }
     }
 }
@@ -194,6 +209,7 @@ impl LLMResponse {
             name: None,
             tool_calls: self.tool_calls.clone(),
             tool_call_id: None,
+            is_error: false,
         }
     }
 }
@@ -272,11 +288,21 @@ pub struct StreamChunk {
     /// Finish reason (only in final chunk)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<FinishReason>,
+
+    /// Response metadata, merged into [`LLMResponse::metadata`]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, serde_json::Value>,
 }
 
 /// Delta for tool call in streaming
+///
+/// The first delta for a call carries its `id` and `name`; later deltas carry
+/// fragments of the JSON `arguments`. `index` identifies which call a delta
+/// belongs to when several are streamed in one response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCallDelta {
+    #[serde(default)]
+    pub index: usize,
     pub id: Option<String>,
     pub name: Option<String>,
     pub arguments: Option<String>,
@@ -301,6 +327,11 @@ mod tests {
         let tool = Message::tool("result", "call_123");
         assert_eq!(tool.role, MessageRole::Tool);
         assert_eq!(tool.tool_call_id, Some("call_123".to_string()));
+        assert!(!tool.is_error);
+
+        let tool_error = Message::tool_error("failed", "call_456");
+        assert_eq!(tool_error.role, MessageRole::Tool);
+        assert!(tool_error.is_error);
     }
 
     #[test]
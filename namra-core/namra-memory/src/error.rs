@@ -0,0 +1,25 @@
+//! Error types for namra-memory
+
+use thiserror::Error;
+
+/// Memory error types
+#[derive(Debug, Error)]
+pub enum MemoryError {
+    #[error("Storage error: {0}")]
+    Storage(#[from] namra_storage::StorageError),
+
+    #[error("LLM error: {0}")]
+    Llm(#[from] namra_llm::adapter::LLMError),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Memory store lock poisoned")]
+    LockPoisoned,
+}
+
+/// Result type alias for memory operations
+pub type MemoryResult<T> = Result<T, MemoryError>;
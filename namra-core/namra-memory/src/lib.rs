@@ -1 +1,41 @@
-// TODO: Implement nexus-memory
+//! # namra-memory
+//!
+//! Conversation memory for Namra agents.
+//!
+//! A [`MemoryStore`] keeps the messages of conversation sessions, and
+//! [`ConversationMemory`] applies an agent's `memory` configuration on top of
+//! it: sessions are scoped to the agent's namespace, messages older than the
+//! TTL are forgotten, and only the most recent `max_messages` are loaded into
+//! a new run. With `memory.summarization`, a [`Summarizer`] replaces older
+//! messages with an LLM-written summary once a conversation grows too long.
+//!
+//! ## Quick Start
+//!
+//! ```no_run
+//! use namra_llm::types::Message;
+//! use namra_memory::{ConversationMemory, InMemoryStore};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> namra_memory::MemoryResult<()> {
+//! let memory = ConversationMemory::new(Arc::new(InMemoryStore::new()), "support");
+//!
+//! memory
+//!     .record("customer-42", &[Message::user("Hi"), Message::assistant("Hello!")])
+//!     .await?;
+//! let history = memory.history("customer-42").await?;
+//! assert_eq!(history.len(), 2);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod error;
+pub mod memory;
+pub mod sqlite;
+pub mod store;
+pub mod summarize;
+
+pub use error::{MemoryError, MemoryResult};
+pub use memory::ConversationMemory;
+pub use sqlite::SqliteMemoryStore;
+pub use store::{InMemoryStore, MemoryStore};
+pub use summarize::{estimate_tokens, is_summary, Summarizer, SUMMARY_NAME};
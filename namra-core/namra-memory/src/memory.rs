@@ -0,0 +1,227 @@
+//! Conversation memory configured by an agent's `memory` section
+
+use chrono::Utc;
+use namra_config::{parse_duration, MemoryConfig};
+use namra_llm::types::{LLMResponse, Message, MessageRole};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{MemoryError, MemoryResult};
+use crate::store::MemoryStore;
+use crate::summarize::{is_summary, Summarizer};
+
+/// Default time a message is remembered for
+pub const DEFAULT_TTL: Duration = Duration::from_secs(3600);
+
+/// Default number of messages loaded into a run
+pub const DEFAULT_MAX_MESSAGES: usize = 50;
+
+/// An agent's view of its conversation sessions
+pub struct ConversationMemory {
+    store: Arc<dyn MemoryStore>,
+    namespace: String,
+    ttl: Duration,
+    max_messages: usize,
+    summarizer: Option<Arc<Summarizer>>,
+}
+
+impl ConversationMemory {
+    /// Memory for sessions in `namespace`, with the default TTL and size
+    pub fn new(store: Arc<dyn MemoryStore>, namespace: impl Into<String>) -> Self {
+        Self {
+            store,
+            namespace: namespace.into(),
+            ttl: DEFAULT_TTL,
+            max_messages: DEFAULT_MAX_MESSAGES,
+            summarizer: None,
+        }
+    }
+
+    /// Memory configured by `config`
+    ///
+    /// Sessions are scoped to `config.namespace`, or else to `agent_name`.
+    pub fn from_config(
+        config: &MemoryConfig,
+        agent_name: &str,
+        store: Arc<dyn MemoryStore>,
+    ) -> MemoryResult<Self> {
+        let ttl = parse_duration(&config.ttl)
+            .map_err(|e| MemoryError::Config(format!("Invalid memory ttl: {}", e)))?;
+        let namespace = config.namespace.as_deref().unwrap_or(agent_name);
+
+        Ok(Self::new(store, namespace)
+            .with_ttl(ttl)
+            .with_max_messages(config.max_messages as usize))
+    }
+
+    /// Forget messages once they are older than `ttl`
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Load at most `max_messages` of a session into a run
+    pub fn with_max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = max_messages;
+        self
+    }
+
+    /// Summarize long sessions with `summarizer`
+    pub fn with_summarizer(mut self, summarizer: Arc<Summarizer>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// Messages to continue `session` from, oldest first
+    ///
+    /// Keeps the most recent `max_messages` that have not expired, starting
+    /// at a user message so that no tool result is cut off from its call.
+    /// The session's summary, if it has one, always comes first.
+    pub async fn history(&self, session: &str) -> MemoryResult<Vec<Message>> {
+        let mut messages = self.load(session).await?;
+        let summary = match messages.first() {
+            Some(first) if is_summary(first) => Some(messages.remove(0)),
+            _ => None,
+        };
+
+        let excess = messages.len().saturating_sub(self.max_messages);
+        messages.drain(..excess);
+        let start = messages
+            .iter()
+            .position(|m| m.role == MessageRole::User)
+            .unwrap_or(messages.len());
+        messages.drain(..start);
+
+        messages.splice(0..0, summary);
+        Ok(messages)
+    }
+
+    /// Whether [`summarize`](Self::summarize) would summarize `session`
+    pub async fn needs_summary(&self, session: &str) -> MemoryResult<bool> {
+        match &self.summarizer {
+            Some(summarizer) => Ok(summarizer.should_summarize(&self.load(session).await?)),
+            None => Ok(false),
+        }
+    }
+
+    /// Replace the older messages of a long `session` with a summary
+    ///
+    /// Does nothing without a summarizer or while the session is within its
+    /// limits. Otherwise the summary and the messages kept verbatim are
+    /// stored again, and the summarizing response is returned so its usage
+    /// can be accounted for.
+    pub async fn summarize(&self, session: &str) -> MemoryResult<Option<LLMResponse>> {
+        let Some(summarizer) = &self.summarizer else {
+            return Ok(None);
+        };
+
+        let mut messages = self.load(session).await?;
+        let Some(response) = summarizer.compact(&mut messages).await? else {
+            return Ok(None);
+        };
+
+        self.store
+            .replace(&self.key(session), &messages, Utc::now())
+            .await?;
+        Ok(Some(response))
+    }
+
+    /// Remember the messages of a finished turn of `session`
+    ///
+    /// Expired messages are deleted from the store first.
+    pub async fn record(&self, session: &str, messages: &[Message]) -> MemoryResult<()> {
+        let now = Utc::now();
+        let expired_before = now - chrono::Duration::from_std(self.ttl).unwrap_or_default();
+        self.store.expire(expired_before).await?;
+        self.store.append(&self.key(session), messages, now).await
+    }
+
+    /// Forget every message of `session`
+    pub async fn clear(&self, session: &str) -> MemoryResult<()> {
+        self.store.clear(&self.key(session)).await
+    }
+
+    /// Unexpired messages of `session`
+    async fn load(&self, session: &str) -> MemoryResult<Vec<Message>> {
+        let since = Utc::now() - chrono::Duration::from_std(self.ttl).unwrap_or_default();
+        self.store.load(&self.key(session), since).await
+    }
+
+    /// Key of `session` in the store
+    fn key(&self, session: &str) -> String {
+        format!("{}:{}", self.namespace, session)
+    }
+}
+
+impl fmt::Debug for ConversationMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConversationMemory")
+            .field("namespace", &self.namespace)
+            .field("ttl", &self.ttl)
+            .field("max_messages", &self.max_messages)
+            .field("summarizer", &self.summarizer)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryStore;
+    use crate::SqliteMemoryStore;
+    use namra_storage::SqliteStorage;
+    use std::sync::Mutex;
+
+    fn turn(question: &str, answer: &str) -> [Message; 2] {
+        [Message::user(question), Message::assistant(answer)]
+    }
+
+    #[tokio::test]
+    async fn test_history_is_trimmed_to_whole_turns() {
+        let memory =
+            ConversationMemory::new(Arc::new(InMemoryStore::new()), "support").with_max_messages(3);
+
+        memory.record("a", &turn("one", "1")).await.unwrap();
+        memory.record("a", &turn("two", "2")).await.unwrap();
+        memory.record("b", &turn("other", "x")).await.unwrap();
+
+        // The oldest kept message would be an answer, so it is dropped too
+        let history = memory.history("a").await.unwrap();
+        assert_eq!(history, turn("two", "2").to_vec());
+
+        memory.clear("a").await.unwrap();
+        assert!(memory.history("a").await.unwrap().is_empty());
+        assert_eq!(memory.history("b").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_scoped_and_expire() {
+        let store = Arc::new(SqliteMemoryStore::new(Arc::new(Mutex::new(
+            SqliteStorage::open_memory().unwrap(),
+        ))));
+        let support = ConversationMemory::new(store.clone(), "support");
+        let billing = ConversationMemory::new(store.clone(), "billing");
+
+        support.record("a", &turn("hi", "hello")).await.unwrap();
+        assert_eq!(support.history("a").await.unwrap().len(), 2);
+        assert!(billing.history("a").await.unwrap().is_empty());
+
+        let forgetful = ConversationMemory::new(store, "support").with_ttl(Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(forgetful.history("a").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config: MemoryConfig =
+            serde_json::from_value(serde_json::json!({"type": "sqlite", "max_messages": 10}))
+                .unwrap();
+        let memory =
+            ConversationMemory::from_config(&config, "support", Arc::new(InMemoryStore::new()))
+                .unwrap();
+        assert_eq!(memory.namespace, "support");
+        assert_eq!(memory.ttl, Duration::from_secs(3600));
+        assert_eq!(memory.max_messages, 10);
+    }
+}
@@ -0,0 +1,90 @@
+//! Conversation sessions kept in SQLite
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use namra_llm::types::Message;
+use namra_storage::SqliteStorage;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{MemoryError, MemoryResult};
+use crate::store::MemoryStore;
+
+/// Store kept in a SQLite database, so sessions outlive the process
+pub struct SqliteMemoryStore {
+    store: Arc<Mutex<SqliteStorage>>,
+}
+
+impl SqliteMemoryStore {
+    /// Store in an open database, such as the run history
+    pub fn new(store: Arc<Mutex<SqliteStorage>>) -> Self {
+        Self { store }
+    }
+
+    /// Store in the database at `path`, creating it if needed
+    pub fn open(path: &Path) -> MemoryResult<Self> {
+        let storage = SqliteStorage::open(path)?;
+        Ok(Self::new(Arc::new(Mutex::new(storage))))
+    }
+
+    fn with_store<T>(
+        &self,
+        f: impl FnOnce(&SqliteStorage) -> namra_storage::StorageResult<T>,
+    ) -> MemoryResult<T> {
+        let store = self.store.lock().map_err(|_| MemoryError::LockPoisoned)?;
+        Ok(f(&store)?)
+    }
+}
+
+impl fmt::Debug for SqliteMemoryStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqliteMemoryStore").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl MemoryStore for SqliteMemoryStore {
+    async fn load(&self, session: &str, since: DateTime<Utc>) -> MemoryResult<Vec<Message>> {
+        self.with_store(|store| store.memory_messages(session, since))?
+            .into_iter()
+            .map(|message| serde_json::from_value(message).map_err(Into::into))
+            .collect()
+    }
+
+    async fn append(
+        &self,
+        session: &str,
+        messages: &[Message],
+        now: DateTime<Utc>,
+    ) -> MemoryResult<()> {
+        let messages = messages
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.with_store(|store| store.append_memory_messages(session, &messages, now))
+    }
+
+    async fn clear(&self, session: &str) -> MemoryResult<()> {
+        self.with_store(|store| store.clear_memory_session(session))?;
+        Ok(())
+    }
+
+    async fn replace(
+        &self,
+        session: &str,
+        messages: &[Message],
+        now: DateTime<Utc>,
+    ) -> MemoryResult<()> {
+        let messages = messages
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.with_store(|store| store.replace_memory_session(session, &messages, now))
+    }
+
+    async fn expire(&self, before: DateTime<Utc>) -> MemoryResult<()> {
+        self.with_store(|store| store.delete_memory_before(before))?;
+        Ok(())
+    }
+}
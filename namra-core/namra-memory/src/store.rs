@@ -0,0 +1,115 @@
+//! Storage for conversation sessions
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use namra_llm::types::Message;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::{MemoryError, MemoryResult};
+
+/// Storage for the messages of conversation sessions
+#[async_trait]
+pub trait MemoryStore: Send + Sync {
+    /// Messages of `session` stored at or after `since`, oldest first
+    async fn load(&self, session: &str, since: DateTime<Utc>) -> MemoryResult<Vec<Message>>;
+
+    /// Append messages to `session`, stored at `now`
+    async fn append(
+        &self,
+        session: &str,
+        messages: &[Message],
+        now: DateTime<Utc>,
+    ) -> MemoryResult<()>;
+
+    /// Forget every message of `session`
+    async fn clear(&self, session: &str) -> MemoryResult<()>;
+
+    /// Replace every message of `session` with `messages`, stored at `now`
+    ///
+    /// Readers see either the old messages or the new ones, never a mix.
+    async fn replace(
+        &self,
+        session: &str,
+        messages: &[Message],
+        now: DateTime<Utc>,
+    ) -> MemoryResult<()>;
+
+    /// Forget messages stored before `before`, in every session
+    async fn expire(&self, before: DateTime<Utc>) -> MemoryResult<()>;
+}
+
+/// Messages of a session with the time each was stored
+type Session = Vec<(DateTime<Utc>, Message)>;
+
+/// Store kept in memory for the life of the process
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn sessions(&self) -> MemoryResult<std::sync::MutexGuard<'_, HashMap<String, Session>>> {
+        self.sessions.lock().map_err(|_| MemoryError::LockPoisoned)
+    }
+}
+
+#[async_trait]
+impl MemoryStore for InMemoryStore {
+    async fn load(&self, session: &str, since: DateTime<Utc>) -> MemoryResult<Vec<Message>> {
+        Ok(self
+            .sessions()?
+            .get(session)
+            .map(|messages| {
+                messages
+                    .iter()
+                    .filter(|(stored_at, _)| *stored_at >= since)
+                    .map(|(_, message)| message.clone())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn append(
+        &self,
+        session: &str,
+        messages: &[Message],
+        now: DateTime<Utc>,
+    ) -> MemoryResult<()> {
+        self.sessions()?
+            .entry(session.to_string())
+            .or_default()
+            .extend(messages.iter().map(|message| (now, message.clone())));
+        Ok(())
+    }
+
+    async fn clear(&self, session: &str) -> MemoryResult<()> {
+        self.sessions()?.remove(session);
+        Ok(())
+    }
+
+    async fn replace(
+        &self,
+        session: &str,
+        messages: &[Message],
+        now: DateTime<Utc>,
+    ) -> MemoryResult<()> {
+        let messages = messages.iter().map(|message| (now, message.clone()));
+        self.sessions()?
+            .insert(session.to_string(), messages.collect());
+        Ok(())
+    }
+
+    async fn expire(&self, before: DateTime<Utc>) -> MemoryResult<()> {
+        let mut sessions = self.sessions()?;
+        for messages in sessions.values_mut() {
+            messages.retain(|(stored_at, _)| *stored_at >= before);
+        }
+        sessions.retain(|_, messages| !messages.is_empty());
+        Ok(())
+    }
+}
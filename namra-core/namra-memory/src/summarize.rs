@@ -0,0 +1,436 @@
+//! Rolling summarization of long conversations
+//!
+//! Once a conversation has more than `trigger_at` messages, or its estimated
+//! size no longer fits the model's context window, [`Summarizer`] asks the LLM
+//! to summarize everything but the last `keep_recent` messages and replaces
+//! them with a single system message holding the summary. Leading system
+//! messages (the agent's system prompt) are never summarized, and an earlier
+//! summary is folded into the next one.
+//!
+//! The kept messages start at a user or assistant message, never between a
+//! tool call and its results. When they start mid-run, at an assistant
+//! message, the user message that started the run is kept after the summary
+//! so the conversation still opens with a user turn.
+
+use namra_config::SummarizationConfig;
+use namra_llm::adapter::LLMAdapter;
+use namra_llm::types::{LLMRequest, LLMResponse, Message, MessageRole};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::error::MemoryResult;
+
+/// `name` of the system message holding a conversation summary
+pub const SUMMARY_NAME: &str = "conversation_summary";
+
+/// Instructions for the summarizing request
+const SUMMARY_PROMPT: &str = "Summarize the conversation below for an assistant that will \
+continue it without seeing the original. Keep the user's goals and instructions, facts and \
+decisions, tool results that may still be needed, and open questions. Be concise.";
+
+/// Most tokens the summary may use
+const SUMMARY_MAX_TOKENS: u32 = 1024;
+
+/// Replaces older messages of a conversation with an LLM-written summary
+pub struct Summarizer {
+    llm: Arc<dyn LLMAdapter>,
+    model: String,
+    trigger_at: usize,
+    keep_recent: usize,
+    token_limit: Option<u32>,
+}
+
+impl Summarizer {
+    /// Summarize with `model` once there are more than `trigger_at` messages
+    pub fn new(
+        llm: Arc<dyn LLMAdapter>,
+        model: impl Into<String>,
+        trigger_at: usize,
+        keep_recent: usize,
+    ) -> Self {
+        Self {
+            llm,
+            model: model.into(),
+            trigger_at,
+            keep_recent,
+            token_limit: None,
+        }
+    }
+
+    /// Summarizer configured by `memory.summarization`
+    pub fn from_config(
+        config: &SummarizationConfig,
+        llm: Arc<dyn LLMAdapter>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            llm,
+            model,
+            config.trigger_at as usize,
+            config.keep_recent as usize,
+        )
+    }
+
+    /// Also summarize once the estimated size exceeds `tokens`
+    pub fn with_token_limit(mut self, tokens: u32) -> Self {
+        self.token_limit = Some(tokens);
+        self
+    }
+
+    /// Whether `messages` have grown past the limits with something to summarize
+    pub fn should_summarize(&self, messages: &[Message]) -> bool {
+        self.older(messages).is_some()
+    }
+
+    /// Summarize `messages` in place if they have grown past the limits
+    ///
+    /// Returns the summarizing response, for usage accounting, or `None` if
+    /// nothing was summarized.
+    pub async fn compact(&self, messages: &mut Vec<Message>) -> MemoryResult<Option<LLMResponse>> {
+        let Some(older) = self.older(messages) else {
+            return Ok(None);
+        };
+
+        let summarized: Vec<Message> = older
+            .summarized()
+            .map(|idx| messages[idx].clone())
+            .collect();
+        let request = LLMRequest::new(
+            self.model.clone(),
+            vec![
+                Message::system(SUMMARY_PROMPT),
+                Message::user(transcript(&summarized)),
+            ],
+        )
+        .with_temperature(0.0)
+        .with_max_tokens(SUMMARY_MAX_TOKENS);
+        let response = self.llm.generate(request).await?;
+
+        tracing::info!(
+            summarized = summarized.len(),
+            kept = messages.len() - older.range.end,
+            "Summarized earlier conversation"
+        );
+        let task = older.task.map(|idx| messages[idx].clone());
+        let summary = summary_message(&response.content);
+        messages.splice(older.range, std::iter::once(summary).chain(task));
+        Ok(Some(response))
+    }
+
+    /// Messages to replace with a summary, if they are too long
+    ///
+    /// Covers everything after the pinned system messages except the last
+    /// `keep_recent`, which are extended back until they start at a user or
+    /// assistant message outside any unfinished tool exchange.
+    fn older(&self, messages: &[Message]) -> Option<Older> {
+        let start = pinned(messages);
+        let too_long = messages.len() - start > self.trigger_at
+            || self
+                .token_limit
+                .is_some_and(|limit| estimate_tokens(messages) > limit);
+        if !too_long {
+            return None;
+        }
+
+        let mut split = messages.len().saturating_sub(self.keep_recent).max(start);
+        while split > start && !opens_turn(messages, split) {
+            split -= 1;
+        }
+
+        let task = match messages.get(split) {
+            Some(m) if m.role == MessageRole::User => None,
+            _ => (start..split)
+                .rev()
+                .find(|&idx| messages[idx].role == MessageRole::User),
+        };
+        let older = Older {
+            range: start..split,
+            task,
+        };
+
+        let summarized: Vec<usize> = older.summarized().collect();
+        let already_summarized = summarized.len() == 1 && is_summary(&messages[summarized[0]]);
+        (!summarized.is_empty() && !already_summarized).then_some(older)
+    }
+}
+
+/// Messages of a conversation to replace with a summary
+struct Older {
+    range: Range<usize>,
+    /// User message in `range` kept after the summary, when the kept
+    /// messages don't start with one
+    task: Option<usize>,
+}
+
+impl Older {
+    /// Indexes of the messages the summary replaces
+    fn summarized(&self) -> impl Iterator<Item = usize> + '_ {
+        self.range.clone().filter(|idx| Some(*idx) != self.task)
+    }
+}
+
+/// Whether the kept messages can start at `messages[at]`
+///
+/// True for a user or assistant message when no message from there on
+/// answers a tool call made before it.
+fn opens_turn(messages: &[Message], at: usize) -> bool {
+    let (before, after) = messages.split_at(at);
+    match after.first() {
+        None => return true,
+        Some(m) if m.role == MessageRole::Tool => return false,
+        Some(_) => {}
+    }
+
+    let earlier_calls: HashSet<&str> = before
+        .iter()
+        .flat_map(|m| m.tool_calls.iter().flatten())
+        .map(|call| call.id.as_str())
+        .collect();
+    !after.iter().any(|m| {
+        m.tool_call_id
+            .as_deref()
+            .is_some_and(|id| earlier_calls.contains(id))
+    })
+}
+
+impl fmt::Debug for Summarizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Summarizer")
+            .field("model", &self.model)
+            .field("trigger_at", &self.trigger_at)
+            .field("keep_recent", &self.keep_recent)
+            .field("token_limit", &self.token_limit)
+            .finish()
+    }
+}
+
+/// Whether `message` holds a conversation summary
+pub fn is_summary(message: &Message) -> bool {
+    message.role == MessageRole::System && message.name.as_deref() == Some(SUMMARY_NAME)
+}
+
+/// Rough token count of `messages`, at about four characters a token
+pub fn estimate_tokens(messages: &[Message]) -> u32 {
+    let chars: usize = messages
+        .iter()
+        .map(|m| {
+            let calls: usize = m
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|c| c.name.len() + c.arguments.to_string().len())
+                .sum();
+            m.content.len() + calls
+        })
+        .sum();
+    (chars / 4 + messages.len() * 4) as u32
+}
+
+/// Number of leading system messages that are never summarized
+fn pinned(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .take_while(|m| m.role == MessageRole::System && !is_summary(m))
+        .count()
+}
+
+fn summary_message(summary: &str) -> Message {
+    Message {
+        name: Some(SUMMARY_NAME.to_string()),
+        ..Message::system(format!(
+            "Summary of the earlier conversation:\n{}",
+            summary.trim()
+        ))
+    }
+}
+
+/// Plain-text rendering of messages for the summarizing request
+fn transcript(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .map(|m| match m.role {
+            _ if is_summary(m) => format!("Earlier summary: {}", m.content),
+            MessageRole::System => format!("System: {}", m.content),
+            MessageRole::User => format!("User: {}", m.content),
+            MessageRole::Assistant => {
+                let calls = m
+                    .tool_calls
+                    .iter()
+                    .flatten()
+                    .map(|c| format!("\nAssistant called tool {}({})", c.name, c.arguments));
+                format!("Assistant: {}{}", m.content, calls.collect::<String>())
+            }
+            MessageRole::Tool => format!("Tool result: {}", m.content),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use namra_llm::adapter::{LLMError, LLMResult, LLMStream};
+    use namra_llm::types::{FinishReason, TokenUsage, ToolCall};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Answers every request with a fixed summary, keeping the requests
+    #[derive(Default)]
+    struct SummaryLLM {
+        requests: Mutex<Vec<LLMRequest>>,
+    }
+
+    #[async_trait]
+    impl LLMAdapter for SummaryLLM {
+        fn provider_name(&self) -> &str {
+            "summary"
+        }
+
+        async fn generate(&self, request: LLMRequest) -> LLMResult<LLMResponse> {
+            self.requests.lock().unwrap().push(request);
+            Ok(LLMResponse {
+                content: "The user is Ada.".to_string(),
+                role: MessageRole::Assistant,
+                tool_calls: None,
+                usage: TokenUsage::new(100, 10),
+                finish_reason: FinishReason::Stop,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn stream(&self, _request: LLMRequest) -> LLMResult<LLMStream> {
+            Err(LLMError::Unknown("not supported".to_string()))
+        }
+
+        fn max_context_tokens(&self, _model: &str) -> Option<u32> {
+            None
+        }
+
+        fn estimate_cost(&self, _input: u32, _output: u32, _model: &str) -> Option<f64> {
+            None
+        }
+    }
+
+    fn tool_turn(id: &str) -> Vec<Message> {
+        let mut call = Message::assistant("");
+        call.tool_calls = Some(vec![ToolCall {
+            id: id.to_string(),
+            name: "calculator".to_string(),
+            arguments: serde_json::json!({"expression": "6*7"}),
+        }]);
+        vec![call, Message::tool("42", id)]
+    }
+
+    #[tokio::test]
+    async fn test_older_messages_are_summarized() {
+        let llm = Arc::new(SummaryLLM::default());
+        let summarizer = Summarizer::new(llm.clone(), "model", 4, 2);
+
+        let mut messages = vec![
+            Message::system("You are helpful."),
+            Message::user("I'm Ada"),
+            Message::assistant("Hi Ada"),
+            Message::user("What is 6*7?"),
+        ];
+        assert!(summarizer.compact(&mut messages).await.unwrap().is_none());
+
+        messages.extend(tool_turn("call_1"));
+        let response = summarizer.compact(&mut messages).await.unwrap().unwrap();
+        assert_eq!(response.usage.input_tokens, 100);
+
+        // The kept turn starts at the user's question, with the tool call
+        // and its result
+        assert_eq!(messages.len(), 5);
+        assert_eq!(messages[0].content, "You are helpful.");
+        assert!(is_summary(&messages[1]));
+        assert!(messages[1].content.ends_with("The user is Ada."));
+        assert_eq!(messages[2].content, "What is 6*7?");
+        assert!(messages[3].tool_calls.is_some());
+        assert_eq!(messages[4].role, MessageRole::Tool);
+
+        let transcript = &llm.requests.lock().unwrap()[0].messages[1].content;
+        assert!(transcript.starts_with("User: I'm Ada"));
+        assert!(!transcript.contains("You are helpful."));
+    }
+
+    #[tokio::test]
+    async fn test_long_tool_loops_are_summarized() {
+        let llm = Arc::new(SummaryLLM::default());
+        let summarizer = Summarizer::new(llm.clone(), "model", 6, 3);
+
+        // One user message, then only tool calls and their results
+        let mut messages = vec![
+            Message::system("You are helpful."),
+            Message::user("Add up the numbers"),
+        ];
+        for n in 0..5 {
+            messages.extend(tool_turn(&format!("call_{}", n)));
+        }
+        summarizer.compact(&mut messages).await.unwrap().unwrap();
+
+        // The task is kept after the summary and the kept calls keep their
+        // results
+        let roles: Vec<MessageRole> = messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            [
+                MessageRole::System,
+                MessageRole::System,
+                MessageRole::User,
+                MessageRole::Assistant,
+                MessageRole::Tool,
+                MessageRole::Assistant,
+                MessageRole::Tool,
+            ]
+        );
+        assert!(is_summary(&messages[1]));
+        assert_eq!(messages[2].content, "Add up the numbers");
+
+        let transcript = &llm.requests.lock().unwrap()[0].messages[1].content;
+        assert!(transcript.starts_with("Assistant: "));
+        assert!(!transcript.contains("Add up the numbers"));
+
+        // Nothing new to summarize once only the task would go
+        let summarizer = Summarizer::new(llm.clone(), "model", 4, 4);
+        assert!(!summarizer.should_summarize(&messages));
+    }
+
+    #[tokio::test]
+    async fn test_summaries_are_folded_into_the_next() {
+        let llm = Arc::new(SummaryLLM::default());
+        let summarizer = Summarizer::new(llm.clone(), "model", 2, 1);
+
+        let mut messages = vec![
+            summary_message("Earlier things."),
+            Message::user("one"),
+            Message::assistant("two"),
+            Message::user("three"),
+        ];
+        summarizer.compact(&mut messages).await.unwrap().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(is_summary(&messages[0]));
+        assert_eq!(messages[1].content, "three");
+
+        let transcript = &llm.requests.lock().unwrap()[0].messages[1].content;
+        assert!(transcript.starts_with("Earlier summary: "));
+    }
+
+    #[test]
+    fn test_token_limit_triggers_summarization() {
+        let summarizer =
+            Summarizer::new(Arc::new(SummaryLLM::default()), "model", 100, 1).with_token_limit(50);
+        let messages = vec![
+            Message::user("word ".repeat(100)),
+            Message::assistant("Noted"),
+            Message::user("Hi"),
+        ];
+        assert!(summarizer.should_summarize(&messages));
+
+        // Nothing to summarize when only recent messages are too long
+        let recent = vec![Message::user("Hi"), Message::assistant("word ".repeat(100))];
+        assert!(!summarizer.should_summarize(&recent));
+    }
+}
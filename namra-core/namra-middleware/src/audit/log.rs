@@ -0,0 +1,492 @@
+//! Hash-chained JSON Lines audit log
+//!
+//! Every line is one [`AuditRecord`], serialized as JSON with its `hash` field
+//! last. The hash is the SHA-256 of the line as written without that field,
+//! so verifying never depends on reading values back exactly (a cost like
+//! `0.0037799999999999995` doesn't survive a JSON round trip). The hashed
+//! text includes `prev_hash`, the hash of the record before it (or
+//! [`GENESIS_HASH`] for the first record), which links the records into a
+//! chain. Reopening a log continues the existing chain.
+//!
+//! Writers share a log safely: [`AuditLog::shared`] hands every caller in a
+//! process the same log for a path, and each append takes an exclusive lock
+//! on the file and first reads any records other processes have added, so
+//! the chain never forks.
+
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use thiserror::Error;
+
+/// `prev_hash` of the first record in a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Errors reading, writing, or verifying an audit log
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("Audit log IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Audit log serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// The chain is broken at the given line (1-based)
+    #[error("Audit log chain broken at line {line}: {reason}")]
+    Broken { line: usize, reason: String },
+}
+
+/// Something that happened during an agent run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    RunStarted {
+        agent: String,
+        version: String,
+    },
+    RunResumed {
+        agent: String,
+        version: String,
+    },
+    RunFinished {
+        success: bool,
+        stop_reason: String,
+        iterations: u32,
+        total_tokens: u32,
+        total_cost: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    LlmCall {
+        provider: String,
+        model: String,
+        input_tokens: u32,
+        output_tokens: u32,
+        cost: f64,
+    },
+    ToolCall {
+        tool: String,
+        /// Tool input with sensitive values redacted
+        input: Value,
+        success: bool,
+        execution_time_ms: u64,
+    },
+    ApprovalDecision {
+        tool: String,
+        /// `approved`, `denied`, or `pending`
+        decision: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position in the log, starting at 1
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub run_id: String,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// The record without its `hash` field, as hashed and written
+    fn content(&self) -> Result<String, AuditError> {
+        let mut content = serde_json::to_value(self)?;
+        if let Value::Object(fields) = &mut content {
+            fields.remove("hash");
+        }
+        Ok(content.to_string())
+    }
+}
+
+/// `content` with the `hash` field appended as its last field
+fn with_hash(content: &str, hash: &str) -> String {
+    let fields = content.strip_suffix('}').unwrap_or(content);
+    format!("{},{}}}", fields, hash_field(hash))
+}
+
+/// The content a written line was hashed from, if `hash` is its last field
+fn without_hash(line: &str, hash: &str) -> Option<String> {
+    let fields = line.trim_end().strip_suffix('}')?;
+    let fields = fields.strip_suffix(&hash_field(hash))?.strip_suffix(',')?;
+    Some(format!("{}}}", fields))
+}
+
+fn hash_field(hash: &str) -> String {
+    format!("\"hash\":{}", Value::from(hash))
+}
+
+/// Result of a successful verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSummary {
+    /// Number of records checked
+    pub records: u64,
+    /// Hash of the last record, to compare against a copy kept elsewhere
+    pub last_hash: String,
+}
+
+struct ChainState {
+    file: File,
+    /// Records and last hash of the chain as of `len`
+    chain: AuditSummary,
+    /// Lines read or written so far
+    lines: usize,
+    /// Length of the file after the last line read or written
+    len: u64,
+}
+
+impl ChainState {
+    /// Run `f` holding an exclusive lock on the file, which other processes
+    /// respect too
+    fn locked<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, AuditError>,
+    ) -> Result<T, AuditError> {
+        FileExt::lock_exclusive(&self.file)?;
+        let result = f(self);
+        FileExt::unlock(&self.file)?;
+        result
+    }
+
+    /// Follow records appended since `len`; the file must be locked
+    fn catch_up(&mut self) -> Result<(), AuditError> {
+        let len = self.file.metadata()?.len();
+        if len < self.len {
+            return Err(AuditError::Broken {
+                line: self.lines,
+                reason: "the log was truncated".to_string(),
+            });
+        }
+        if len > self.len {
+            (&self.file).seek(SeekFrom::Start(self.len))?;
+            let chain = std::mem::replace(&mut self.chain, genesis());
+            (self.chain, self.lines) = follow(BufReader::new(&self.file), chain, self.lines)?;
+            self.len = len;
+        }
+        Ok(())
+    }
+}
+
+/// Append-only audit log file
+pub struct AuditLog {
+    path: PathBuf,
+    state: Mutex<ChainState>,
+}
+
+impl AuditLog {
+    /// Open a log for appending, creating it (and its directory) if needed
+    ///
+    /// An existing log is verified first so new records never extend a
+    /// broken chain. Within a process, prefer [`AuditLog::shared`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AuditError> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+        let mut state = ChainState {
+            file,
+            chain: genesis(),
+            lines: 0,
+            len: 0,
+        };
+        state.locked(ChainState::catch_up)?;
+
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// The log at `path`, shared by every caller in the process
+    ///
+    /// Paths are compared after resolving links and relative parts, so two
+    /// agents configured with different spellings of a path still append to
+    /// one chain.
+    pub fn shared(path: impl AsRef<Path>) -> Result<Arc<Self>, AuditError> {
+        static LOGS: OnceLock<Mutex<HashMap<PathBuf, Weak<AuditLog>>>> = OnceLock::new();
+
+        // The file must exist to resolve its path; reading the chain waits
+        // for a cache miss
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new().create(true).append(true).open(path)?;
+        let key = std::fs::canonicalize(path)?;
+
+        let mut logs = LOGS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(shared) = logs.get(&key).and_then(Weak::upgrade) {
+            return Ok(shared);
+        }
+        logs.retain(|_, log| log.strong_count() > 0);
+        let log = Arc::new(Self::open(path)?);
+        logs.insert(key, Arc::downgrade(&log));
+        Ok(log)
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an event for `run_id`
+    pub fn record(&self, run_id: &str, event: AuditEvent) -> Result<AuditRecord, AuditError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.locked(|state| {
+            // Other processes may have appended since our last record
+            state.catch_up()?;
+
+            let mut record = AuditRecord {
+                seq: state.chain.records + 1,
+                timestamp: Utc::now(),
+                run_id: run_id.to_string(),
+                event,
+                prev_hash: state.chain.last_hash.clone(),
+                hash: String::new(),
+            };
+            let content = record.content()?;
+            record.hash = sha256_hex(&content);
+
+            let mut line = with_hash(&content, &record.hash);
+            line.push('\n');
+            (&state.file).write_all(line.as_bytes())?;
+            (&state.file).flush()?;
+
+            state.chain = AuditSummary {
+                records: record.seq,
+                last_hash: record.hash.clone(),
+            };
+            state.lines += 1;
+            state.len += line.len() as u64;
+            Ok(record)
+        })
+    }
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Check every record's hash and its link to the previous record
+pub fn verify(path: impl AsRef<Path>) -> Result<AuditSummary, AuditError> {
+    let reader = BufReader::new(File::open(path)?);
+    follow(reader, genesis(), 0).map(|(summary, _)| summary)
+}
+
+/// Check the records in `reader`, which continue `chain` after `lines` lines
+///
+/// Returns the chain after the last record and the number of lines read in
+/// total.
+fn follow(
+    reader: impl BufRead,
+    chain: AuditSummary,
+    lines: usize,
+) -> Result<(AuditSummary, usize), AuditError> {
+    let AuditSummary {
+        mut records,
+        mut last_hash,
+    } = chain;
+    let mut total = lines;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        total = lines + index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let broken = |reason: String| AuditError::Broken {
+            line: total,
+            reason,
+        };
+
+        let record: AuditRecord = serde_json::from_str(&line)
+            .map_err(|e| broken(format!("not a valid record ({})", e)))?;
+
+        if record.seq != records + 1 {
+            return Err(broken(format!(
+                "expected record {} but found {}",
+                records + 1,
+                record.seq
+            )));
+        }
+        if record.prev_hash != last_hash {
+            return Err(broken("previous hash does not match".to_string()));
+        }
+        let content = without_hash(&line, &record.hash);
+        if content.map(|c| sha256_hex(&c)).as_deref() != Some(record.hash.as_str()) {
+            return Err(broken("record hash does not match its content".to_string()));
+        }
+
+        records = record.seq;
+        last_hash = record.hash;
+    }
+
+    Ok((AuditSummary { records, last_hash }, total))
+}
+
+/// An empty chain
+fn genesis() -> AuditSummary {
+    AuditSummary {
+        records: 0,
+        last_hash: GENESIS_HASH.to_string(),
+    }
+}
+
+fn sha256_hex(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(tool: &str) -> AuditEvent {
+        AuditEvent::ToolCall {
+            tool: tool.to_string(),
+            input: serde_json::json!({"path": "/tmp/report.txt"}),
+            success: true,
+            execution_time_ms: 12,
+        }
+    }
+
+    #[test]
+    fn test_chain_continues_across_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit/agent.jsonl");
+
+        let log = AuditLog::open(&path).unwrap();
+        let first = log
+            .record(
+                "run-1",
+                AuditEvent::RunStarted {
+                    agent: "reporter".to_string(),
+                    version: "1.0.0".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        log.record("run-1", tool_call("read_file")).unwrap();
+        drop(log);
+
+        let log = AuditLog::open(&path).unwrap();
+        let third = log.record("run-2", tool_call("write_file")).unwrap();
+        assert_eq!(third.seq, 3);
+
+        let summary = verify(&path).unwrap();
+        assert_eq!(summary.records, 3);
+        assert_eq!(summary.last_hash, third.hash);
+    }
+
+    #[test]
+    fn test_costs_verify_after_reading_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let log = AuditLog::open(&path).unwrap();
+        let call = log
+            .record(
+                "run-1",
+                AuditEvent::LlmCall {
+                    provider: "openai".to_string(),
+                    model: "gpt-4o".to_string(),
+                    input_tokens: 812,
+                    output_tokens: 175,
+                    cost: 0.0012 + 0.00258,
+                },
+            )
+            .unwrap();
+        drop(log);
+
+        assert_eq!(verify(&path).unwrap().last_hash, call.hash);
+        AuditLog::open(&path).unwrap();
+    }
+
+    #[test]
+    fn test_writers_extend_one_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let shared = AuditLog::shared(&path).unwrap();
+        let same = AuditLog::shared(dir.path().join(".").join("audit.jsonl")).unwrap();
+        assert!(Arc::ptr_eq(&shared, &same));
+
+        // A writer with its own handle, as in another process
+        let other = AuditLog::open(&path).unwrap();
+        shared.record("run-1", tool_call("read_file")).unwrap();
+        let second = other.record("run-2", tool_call("read_file")).unwrap();
+        let third = same.record("run-1", tool_call("write_file")).unwrap();
+
+        assert_eq!(second.seq, 2);
+        assert_eq!(third.prev_hash, second.hash);
+        assert_eq!(verify(&path).unwrap().records, 3);
+    }
+
+    #[test]
+    fn test_shared_log_is_read_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("audit.jsonl");
+
+        let shared = AuditLog::shared(&path).unwrap();
+        assert!(AuditLog::open(&path).is_ok());
+
+        // Opening again would read the broken line; the cached log doesn't
+        std::fs::write(&path, "not a record\n").unwrap();
+        assert!(AuditLog::open(&path).is_err());
+        assert!(Arc::ptr_eq(&shared, &AuditLog::shared(&path).unwrap()));
+    }
+
+    #[test]
+    fn test_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let log = AuditLog::open(&path).unwrap();
+        for tool in ["read_file", "write_file", "delete_file"] {
+            log.record("run-1", tool_call(tool)).unwrap();
+        }
+        drop(log);
+        let original = std::fs::read_to_string(&path).unwrap();
+
+        // Edited content
+        std::fs::write(&path, original.replace("delete_file", "list_files")).unwrap();
+        assert!(matches!(
+            verify(&path),
+            Err(AuditError::Broken { line: 3, .. })
+        ));
+
+        // Deleted record
+        let lines: Vec<&str> = original.lines().collect();
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(matches!(
+            verify(&path),
+            Err(AuditError::Broken { line: 2, .. })
+        ));
+
+        // Appending to a broken log is refused
+        assert!(AuditLog::open(&path).is_err());
+    }
+}
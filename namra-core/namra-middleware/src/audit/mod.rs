@@ -0,0 +1,11 @@
+//! Tamper-evident audit logging
+//!
+//! Agent activity is appended to a JSON Lines file. Each record carries the
+//! SHA-256 hash of the previous record, so editing, reordering, or deleting a
+//! record breaks the chain and is reported by [`verify`].
+
+pub mod log;
+pub mod redact;
+
+pub use log::{verify, AuditError, AuditEvent, AuditLog, AuditRecord, AuditSummary, GENESIS_HASH};
+pub use redact::redact;
@@ -0,0 +1,70 @@
+//! Redaction of sensitive values before they are written to the audit log
+
+use serde_json::Value;
+
+/// Placeholder written instead of a sensitive value
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments that mark a value as sensitive (matched case-insensitively)
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "credential",
+    "private_key",
+    "cookie",
+];
+
+/// Copy of `value` with the values of sensitive-looking keys replaced
+///
+/// Objects are walked recursively, including objects inside arrays.
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if is_sensitive(key) {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        other => other.clone(),
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_lowercase().replace('-', "_");
+    SENSITIVE_KEYS.iter().any(|fragment| key.contains(fragment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redacts_nested_sensitive_keys() {
+        let input = json!({
+            "url": "https://api.example.com",
+            "headers": {"Authorization": "Bearer abc", "Accept": "json"},
+            "accounts": [{"name": "ops", "API-Key": "xyz"}],
+        });
+
+        let redacted = redact(&input);
+
+        assert_eq!(redacted["url"], "https://api.example.com");
+        assert_eq!(redacted["headers"]["Authorization"], REDACTED);
+        assert_eq!(redacted["headers"]["Accept"], "json");
+        assert_eq!(redacted["accounts"][0]["API-Key"], REDACTED);
+        assert_eq!(redacted["accounts"][0]["name"], "ops");
+    }
+}
@@ -1,3 +1,6 @@
 //! Namra middleware for observability, security, and other cross-cutting concerns
 
+pub mod audit;
 pub mod observability;
+pub mod resilience;
+pub mod security;
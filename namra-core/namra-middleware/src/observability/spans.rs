@@ -14,6 +14,7 @@ pub fn agent_run_span(agent_name: &str, agent_version: Option<&str>) -> Span {
         agent.version = %agent_version.unwrap_or("unknown"),
         agent.iterations = tracing::field::Empty,
         agent.success = tracing::field::Empty,
+        agent.security.detections = tracing::field::Empty,
     )
 }
 
@@ -33,6 +34,36 @@ pub fn llm_request_span(provider: &str, model: &str) -> Span {
     )
 }
 
+/// Create a span covering every attempt of a retried LLM request
+///
+/// Each attempt gets its own `llm.request` span nested inside this one.
+pub fn llm_retry_span(provider: &str, model: &str, max_attempts: u32) -> Span {
+    tracing::info_span!(
+        "llm.retry",
+        otel.kind = "internal",
+        llm.provider = %provider,
+        llm.model = %model,
+        llm.retry.max_attempts = max_attempts,
+        llm.retry.attempts = tracing::field::Empty,
+        llm.retry.last_error = tracing::field::Empty,
+    )
+}
+
+/// Create a span for a response cache lookup
+///
+/// On a miss the provider request runs inside this span.
+pub fn llm_cache_span(provider: &str, model: &str, strategy: &str) -> Span {
+    tracing::info_span!(
+        "llm.cache",
+        otel.kind = "internal",
+        llm.provider = %provider,
+        llm.model = %model,
+        llm.cache.strategy = %strategy,
+        llm.cache.hit = tracing::field::Empty,
+        llm.cache.similarity = tracing::field::Empty,
+    )
+}
+
 /// Create a span for tool execution with input/output placeholders
 pub fn tool_execution_span(tool_name: &str) -> Span {
     tracing::info_span!(
@@ -68,6 +99,34 @@ pub fn record_llm_response(span: &Span, response: &str, max_size: usize) {
     span.record("llm.response", truncated.as_str());
 }
 
+/// Record a failed LLM attempt that will be retried after `delay_ms`
+pub fn record_llm_retry(span: &Span, attempt: u32, error: &str, delay_ms: u64) {
+    span.record("llm.retry.last_error", error);
+    span.in_scope(|| {
+        tracing::warn!(
+            llm.retry.attempt = attempt,
+            llm.retry.delay_ms = delay_ms,
+            error = %error,
+            "LLM request failed, retrying"
+        )
+    });
+}
+
+/// Record how many attempts an LLM request took
+pub fn record_llm_attempts(span: &Span, attempts: u32) {
+    span.record("llm.retry.attempts", attempts);
+}
+
+/// Record the outcome of a response cache lookup on a span
+///
+/// `similarity` is set for semantic matches.
+pub fn record_llm_cache(span: &Span, hit: bool, similarity: Option<f32>) {
+    span.record("llm.cache.hit", hit);
+    if let Some(similarity) = similarity {
+        span.record("llm.cache.similarity", similarity as f64);
+    }
+}
+
 /// Record tool execution result on a span
 pub fn record_tool_result(span: &Span, success: bool, duration_ms: u64) {
     span.record("tool.success", success);
@@ -94,6 +153,34 @@ pub fn record_agent_result(span: &Span, iterations: u32, success: bool) {
     span.record("agent.success", success);
 }
 
+/// Record something a security guard flagged in the run
+///
+/// Emitted as a warning event on `span`; the matched text is not included.
+pub fn record_security_detection(
+    span: &Span,
+    guard: &str,
+    rule: &str,
+    stage: &str,
+    source: Option<&str>,
+    blocked: bool,
+) {
+    span.in_scope(|| {
+        tracing::warn!(
+            security.guard = %guard,
+            security.rule = %rule,
+            security.stage = %stage,
+            security.source = %source.unwrap_or(""),
+            security.blocked = blocked,
+            "Security guard detection"
+        )
+    });
+}
+
+/// Record how many security detections a run had on its span
+pub fn record_security_detections(span: &Span, count: usize) {
+    span.record("agent.security.detections", count);
+}
+
 /// Truncate content to fit within OTEL attribute size limits
 fn truncate_content(content: &str, max_size: usize) -> String {
     let max_size = if max_size == 0 {
@@ -0,0 +1,254 @@
+//! Circuit breaker
+//!
+//! Tracks consecutive failures of a dependency (an LLM provider or a tool).
+//! After `failure_threshold` failures the circuit opens and calls are rejected
+//! without reaching the dependency. Once `half_open_timeout` has passed, a
+//! single trial call is let through: success closes the circuit, failure
+//! opens it again.
+//!
+//! Each call holds a [`CallPermit`] that reports its outcome. A permit dropped
+//! without an outcome, such as a call abandoned mid-flight, counts as a
+//! failure so a half-open circuit isn't left waiting on a trial that never
+//! finishes.
+
+use anyhow::{Context, Result};
+use namra_config::{parse_duration, CircuitBreakerConfig};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// State of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls flow normally
+    Closed,
+    /// Calls are rejected
+    Open,
+    /// A trial call decides whether to close or re-open
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Circuit breaker guarding a single dependency
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: String,
+    failure_threshold: u32,
+    half_open_timeout: Duration,
+    inner: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed circuit breaker
+    pub fn new(
+        name: impl Into<String>,
+        failure_threshold: u32,
+        half_open_timeout: Duration,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            failure_threshold: failure_threshold.max(1),
+            half_open_timeout,
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    /// Create a circuit breaker from `middleware.resilience.circuit_breaker`
+    pub fn from_config(name: impl Into<String>, config: &CircuitBreakerConfig) -> Result<Self> {
+        let half_open_timeout = parse_duration(&config.half_open_timeout)
+            .context("Invalid circuit breaker half_open_timeout")?;
+        Ok(Self::new(name, config.failure_threshold, half_open_timeout))
+    }
+
+    /// Name of the guarded dependency
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current state, moving from open to half-open once the timeout has passed
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// Ask to make a call; `None` means the circuit is open and the call
+    /// must not be made
+    ///
+    /// In the half-open state only one trial call is allowed at a time.
+    pub fn try_acquire(&self) -> Option<CallPermit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+
+        let allowed = match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen if inner.trial_in_flight => false,
+            CircuitState::HalfOpen => {
+                inner.trial_in_flight = true;
+                true
+            }
+        };
+
+        // Built only when allowed: a dropped permit records a failure
+        if allowed {
+            Some(CallPermit {
+                breaker: self,
+                reported: false,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Record a successful call, closing the circuit
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != CircuitState::Closed {
+            tracing::info!(circuit = %self.name, "Circuit closed");
+        }
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.trial_in_flight = false;
+    }
+
+    /// Record a failed call, opening the circuit at the threshold or after a
+    /// failed trial call
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        inner.trial_in_flight = false;
+
+        let should_open = match inner.state {
+            CircuitState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+
+        if should_open {
+            tracing::warn!(
+                circuit = %self.name,
+                failures = inner.consecutive_failures,
+                "Circuit opened"
+            );
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    fn refresh(&self, inner: &mut BreakerState) {
+        if inner.state == CircuitState::Open
+            && inner
+                .opened_at
+                .is_some_and(|at| at.elapsed() >= self.half_open_timeout)
+        {
+            inner.state = CircuitState::HalfOpen;
+            inner.trial_in_flight = false;
+        }
+    }
+}
+
+/// Permission to make one call through a [`CircuitBreaker`]
+///
+/// Dropping the permit without calling [`success`](Self::success) or
+/// [`failure`](Self::failure) records a failure.
+#[must_use = "dropping the permit records a failed call"]
+#[derive(Debug)]
+pub struct CallPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    reported: bool,
+}
+
+impl CallPermit<'_> {
+    /// The call succeeded
+    pub fn success(mut self) {
+        self.reported = true;
+        self.breaker.record_success();
+    }
+
+    /// The call failed
+    pub fn failure(mut self) {
+        self.reported = true;
+        self.breaker.record_failure();
+    }
+}
+
+impl Drop for CallPermit<'_> {
+    fn drop(&mut self) {
+        if !self.reported {
+            self.breaker.record_failure();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new("llm", 2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.try_acquire().unwrap().failure();
+
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.try_acquire().is_none());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let breaker = CircuitBreaker::new("llm", 2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_allows_one_trial() {
+        let breaker = CircuitBreaker::new("llm", 1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let trial = breaker.try_acquire().unwrap();
+        assert!(breaker.try_acquire().is_none());
+
+        // A failed trial re-opens the circuit; a successful one closes it
+        trial.failure();
+        breaker.try_acquire().unwrap().success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_abandoned_call_counts_as_failure() {
+        let breaker = CircuitBreaker::new("tool", 1, Duration::ZERO);
+
+        breaker.record_failure();
+        let trial = breaker.try_acquire().unwrap();
+        drop(trial);
+
+        // The abandoned trial re-opened the circuit and a new trial is allowed
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire().is_some());
+
+        let closed = CircuitBreaker::new("tool", 1, Duration::from_secs(60));
+        drop(closed.try_acquire());
+        assert_eq!(closed.state(), CircuitState::Open);
+    }
+}
@@ -0,0 +1,5 @@
+//! Resilience primitives for protecting agents from failing dependencies
+
+pub mod circuit_breaker;
+
+pub use circuit_breaker::{CallPermit, CircuitBreaker, CircuitState};
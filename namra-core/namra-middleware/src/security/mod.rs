@@ -0,0 +1,15 @@
+//! Security guards for agent input, tool output, and final responses
+//!
+//! A [`GuardPipeline`] passes content through a list of [`Guard`]s. The
+//! built-in guards detect prompt-injection attempts ([`InjectionGuard`]),
+//! mark tool output as untrusted data ([`DelimiterGuard`]), and redact
+//! secrets and personal data ([`RedactionGuard`]).
+
+pub mod guard;
+pub mod rules;
+
+pub use guard::{
+    DelimiterGuard, Detection, Guard, GuardPipeline, GuardStage, InjectionGuard, Inspection,
+    RedactionGuard, Scan,
+};
+pub use rules::{GuardError, Rule, RuleMatch, RuleSet};
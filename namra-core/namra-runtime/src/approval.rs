@@ -0,0 +1,415 @@
+//! Human-in-the-loop approval for sensitive tool calls
+//!
+//! Tools listed in `governance.require_approval_for` (when governance is
+//! enabled) and Python plugin tools with `require_approval: true` are reviewed
+//! by an [`ApprovalHandler`] before they run. Denied calls are not executed;
+//! the model receives a tool error explaining the denial and carries on.
+//!
+//! A handler may also leave a decision pending. The run then stops with
+//! [`StopReason::AwaitingApproval`](crate::StopReason::AwaitingApproval) and
+//! its state is handed to [`ApprovalHandler::pause`] so it can be continued
+//! with [`AgentExecutor::resume`](crate::AgentExecutor::resume) once someone
+//! has decided. [`PendingApprovals`] implements this on top of the run
+//! history database.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use namra_config::{AgentConfig, ToolConfig};
+use namra_llm::types::{Message, TokenUsage};
+use namra_storage::{ApprovalRecord, ApprovalStatus, SqliteStorage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::context::{ExecutionContext, ToolCallRecord};
+use crate::error::{Result, RuntimeError};
+
+/// A tool call waiting for a decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub run_id: String,
+    pub agent_name: String,
+    pub tool_name: String,
+    pub arguments: Value,
+}
+
+/// Outcome of reviewing a tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    /// Run the tool
+    Approved,
+    /// Don't run the tool; the reason is passed on to the model
+    Denied(Option<String>),
+    /// No decision yet; pause the run (carries the approval ID)
+    Pending(String),
+}
+
+/// Decides whether sensitive tool calls may run
+#[async_trait]
+pub trait ApprovalHandler: Send + Sync {
+    /// Review a tool call before it is executed
+    async fn review(&self, request: &ApprovalRequest) -> Result<ApprovalDecision>;
+
+    /// Persist a run that stopped on a pending decision
+    ///
+    /// Handlers that never return [`ApprovalDecision::Pending`] can keep the
+    /// default, which refuses to pause.
+    async fn pause(&self, run: &PausedRun) -> Result<()> {
+        Err(RuntimeError::Other(format!(
+            "Run {} cannot be paused by this approval handler",
+            run.run_id
+        )))
+    }
+}
+
+/// State of a run stopped while waiting for approvals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PausedRun {
+    pub run_id: String,
+    pub agent_name: String,
+    /// The run's original input
+    pub input: String,
+    pub messages: Vec<Message>,
+    pub iteration: u32,
+    pub tool_calls: Vec<ToolCallRecord>,
+    pub thoughts: Vec<String>,
+    pub total_tokens: TokenUsage,
+    pub total_cost: f64,
+    pub metadata: HashMap<String, Value>,
+    /// Approval IDs the run is waiting on
+    pub pending: Vec<String>,
+}
+
+impl PausedRun {
+    /// Capture a context that stopped on the given pending approvals
+    pub(crate) fn capture(
+        agent_name: &str,
+        input: &str,
+        context: &ExecutionContext,
+        pending: Vec<String>,
+    ) -> Self {
+        Self {
+            run_id: context.id.clone(),
+            agent_name: agent_name.to_string(),
+            input: input.to_string(),
+            messages: context.messages.clone(),
+            iteration: context.iteration,
+            tool_calls: context.tool_calls.clone(),
+            thoughts: context.thoughts.clone(),
+            total_tokens: context.total_tokens.clone(),
+            total_cost: context.total_cost,
+            metadata: context.metadata.clone(),
+            pending,
+        }
+    }
+
+    /// Load the paused state into a fresh context
+    pub(crate) fn restore(self, context: &mut ExecutionContext) {
+        context.id = self.run_id;
+        context.messages = self.messages;
+        context.iteration = self.iteration;
+        context.tool_calls = self.tool_calls;
+        context.thoughts = self.thoughts;
+        context.total_tokens = self.total_tokens;
+        context.total_cost = self.total_cost;
+        context.metadata = self.metadata;
+        context.metadata.remove("pending_approvals");
+    }
+}
+
+/// The tools of an agent that need approval, and who approves them
+#[derive(Clone)]
+pub struct ApprovalGate {
+    agent_name: String,
+    tools: HashSet<String>,
+    handler: Option<Arc<dyn ApprovalHandler>>,
+}
+
+impl ApprovalGate {
+    /// Gate the agent's approval-required tools behind `handler`
+    ///
+    /// Without a handler every call to those tools is denied.
+    pub fn new(config: &AgentConfig, handler: Option<Arc<dyn ApprovalHandler>>) -> Self {
+        let mut tools: HashSet<String> = config
+            .tools
+            .iter()
+            .filter_map(|tool| match tool {
+                ToolConfig::PluginPython {
+                    name,
+                    require_approval: true,
+                    ..
+                } => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        if let Some(governance) = config
+            .middleware
+            .as_ref()
+            .and_then(|m| m.governance.as_ref())
+            .filter(|g| g.enabled)
+        {
+            tools.extend(governance.require_approval_for.iter().cloned());
+        }
+
+        Self {
+            agent_name: config.name.clone(),
+            tools,
+            handler,
+        }
+    }
+
+    /// Whether any tool needs approval
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Whether calls to `tool_name` need approval
+    pub fn requires(&self, tool_name: &str) -> bool {
+        self.tools.contains(tool_name)
+    }
+
+    /// Review a call to `tool_name` made during run `run_id`
+    pub async fn review(
+        &self,
+        run_id: &str,
+        tool_name: &str,
+        arguments: &Value,
+    ) -> Result<ApprovalDecision> {
+        let Some(handler) = &self.handler else {
+            return Ok(ApprovalDecision::Denied(Some(
+                "no approver is available".to_string(),
+            )));
+        };
+
+        let request = ApprovalRequest {
+            run_id: run_id.to_string(),
+            agent_name: self.agent_name.clone(),
+            tool_name: tool_name.to_string(),
+            arguments: arguments.clone(),
+        };
+        let decision = handler.review(&request).await?;
+        tracing::info!(
+            tool = tool_name,
+            run_id = run_id,
+            decision = ?decision,
+            "Tool call reviewed"
+        );
+        Ok(decision)
+    }
+
+    /// Hand a paused run to the approval handler
+    pub async fn pause(&self, run: &PausedRun) -> Result<()> {
+        match &self.handler {
+            Some(handler) => handler.pause(run).await,
+            None => Err(RuntimeError::Other(
+                "No approval handler to pause the run".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for ApprovalGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApprovalGate")
+            .field("agent_name", &self.agent_name)
+            .field("tools", &self.tools)
+            .field("handler", &self.handler.is_some())
+            .finish()
+    }
+}
+
+/// Approval handler that records requests for a later decision
+///
+/// New requests are stored as pending approvals in the run history database
+/// and the run is paused. Once every pending approval has been decided (see
+/// [`SqliteStorage::decide_approval`]) the run can be resumed; reviewing the
+/// same call again then returns the recorded decision.
+pub struct PendingApprovals {
+    storage: Arc<Mutex<SqliteStorage>>,
+}
+
+impl PendingApprovals {
+    /// Store approvals and paused runs in `storage`
+    pub fn new(storage: Arc<Mutex<SqliteStorage>>) -> Self {
+        Self { storage }
+    }
+
+    fn storage(&self) -> Result<std::sync::MutexGuard<'_, SqliteStorage>> {
+        self.storage
+            .lock()
+            .map_err(|_| RuntimeError::Other("Run history lock poisoned".to_string()))
+    }
+
+    /// Load a paused run so it can be resumed
+    ///
+    /// Fails if any of its approvals are still pending. The paused state is
+    /// kept until [`finish_paused_run`](Self::finish_paused_run), so a failed
+    /// resume can be retried.
+    pub fn paused_run(&self, run_id: &str) -> Result<Option<PausedRun>> {
+        let storage = self.storage()?;
+
+        let mut undecided = Vec::new();
+        for approval in storage
+            .list_approvals(Some(ApprovalStatus::Pending))
+            .map_err(storage_error)?
+        {
+            if approval.run_id == run_id {
+                undecided.push(approval.id);
+            }
+        }
+        if !undecided.is_empty() {
+            return Err(RuntimeError::Other(format!(
+                "Run {} is still waiting for approval of: {}",
+                run_id,
+                undecided.join(", ")
+            )));
+        }
+
+        match storage.get_paused_run(run_id).map_err(storage_error)? {
+            Some(state) => Ok(Some(serde_json::from_value(state).map_err(|e| {
+                RuntimeError::Other(format!("Invalid paused run {}: {}", run_id, e))
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove a paused run once its resumed run has finished
+    pub fn finish_paused_run(&self, run_id: &str) -> Result<bool> {
+        self.storage()?
+            .delete_paused_run(run_id)
+            .map_err(storage_error)
+    }
+}
+
+#[async_trait]
+impl ApprovalHandler for PendingApprovals {
+    async fn review(&self, request: &ApprovalRequest) -> Result<ApprovalDecision> {
+        let storage = self.storage()?;
+
+        let existing = storage
+            .find_approval(&request.run_id, &request.tool_name, &request.arguments)
+            .map_err(storage_error)?;
+        if let Some(approval) = existing {
+            return Ok(match approval.status {
+                ApprovalStatus::Approved => ApprovalDecision::Approved,
+                ApprovalStatus::Denied => ApprovalDecision::Denied(approval.reason),
+                ApprovalStatus::Pending => ApprovalDecision::Pending(approval.id),
+            });
+        }
+
+        let approval = ApprovalRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            run_id: request.run_id.clone(),
+            agent_name: request.agent_name.clone(),
+            tool_name: request.tool_name.clone(),
+            arguments: request.arguments.clone(),
+            status: ApprovalStatus::Pending,
+            reason: None,
+            requested_at: Utc::now(),
+            decided_at: None,
+        };
+        storage.save_approval(&approval).map_err(storage_error)?;
+        Ok(ApprovalDecision::Pending(approval.id))
+    }
+
+    async fn pause(&self, run: &PausedRun) -> Result<()> {
+        let state = serde_json::to_value(run)
+            .map_err(|e| RuntimeError::Other(format!("Could not save paused run: {}", e)))?;
+        self.storage()?
+            .save_paused_run(&run.run_id, &run.agent_name, &state)
+            .map_err(storage_error)
+    }
+}
+
+fn storage_error(err: namra_storage::StorageError) -> RuntimeError {
+    RuntimeError::Other(format!("Approval storage error: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_config;
+    use std::time::Duration;
+
+    fn request(tool_name: &str) -> ApprovalRequest {
+        ApprovalRequest {
+            run_id: "run-1".to_string(),
+            agent_name: "test_agent".to_string(),
+            tool_name: tool_name.to_string(),
+            arguments: serde_json::json!({"path": "/tmp/data"}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_approvals_round_trip() {
+        let storage = Arc::new(Mutex::new(SqliteStorage::open_memory().unwrap()));
+        let approvals = PendingApprovals::new(storage.clone());
+
+        let ApprovalDecision::Pending(id) = approvals.review(&request("delete")).await.unwrap()
+        else {
+            panic!("expected a pending decision");
+        };
+
+        // Asking again doesn't create a second request
+        assert_eq!(
+            approvals.review(&request("delete")).await.unwrap(),
+            ApprovalDecision::Pending(id.clone())
+        );
+
+        let context = ExecutionContext::new(5, Duration::from_secs(60));
+        let mut paused = PausedRun::capture("test_agent", "clean up", &context, vec![id.clone()]);
+        paused.run_id = "run-1".to_string();
+        approvals.pause(&paused).await.unwrap();
+
+        assert!(approvals.paused_run("run-1").is_err());
+
+        storage
+            .lock()
+            .unwrap()
+            .decide_approval(&id, ApprovalStatus::Denied, Some("not today"))
+            .unwrap();
+        assert_eq!(
+            approvals.review(&request("delete")).await.unwrap(),
+            ApprovalDecision::Denied(Some("not today".to_string()))
+        );
+
+        let resumed = approvals.paused_run("run-1").unwrap().unwrap();
+        assert_eq!(resumed.input, "clean up");
+        assert_eq!(resumed.pending, vec![id]);
+
+        // The paused state stays until the resumed run finishes
+        assert!(approvals.paused_run("run-1").unwrap().is_some());
+        assert!(approvals.finish_paused_run("run-1").unwrap());
+        assert!(approvals.paused_run("run-1").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gate_denies_without_handler() {
+        use namra_config::{GovernanceConfig, MiddlewareConfig};
+
+        let mut config = test_config();
+        config.middleware = Some(MiddlewareConfig {
+            governance: Some(GovernanceConfig {
+                enabled: true,
+                max_cost_per_request: None,
+                max_cost_per_hour: None,
+                require_approval_for: vec!["delete".to_string()],
+                policies: vec![],
+                compliance_standards: vec![],
+                audit_log: None,
+            }),
+            ..Default::default()
+        });
+
+        let gate = ApprovalGate::new(&config, None);
+        assert!(gate.requires("delete"));
+        assert!(!gate.requires("search"));
+        assert!(matches!(
+            gate.review("run-1", "delete", &Value::Null).await.unwrap(),
+            ApprovalDecision::Denied(Some(_))
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+//! Cost budgets from `middleware.governance`
+//!
+//! `max_cost_per_request` caps the cost of a single run. `max_cost_per_hour`
+//! caps an agent's spend over the last hour, counting runs saved in the run
+//! history ([`SqliteStorage`]) plus the current run. Runs over an exhausted
+//! hourly budget fail before the first LLM call; otherwise the run stops with
+//! [`RuntimeError::BudgetExceeded`] as soon as an LLM response brings its
+//! spend to the tighter of the two limits.
+
+use chrono::Utc;
+use namra_config::AgentConfig;
+use namra_storage::SqliteStorage;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::error::{Result, RuntimeError};
+
+/// Period a cost budget applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetWindow {
+    /// A single run (`max_cost_per_request`)
+    Request,
+    /// The agent's runs over the last hour (`max_cost_per_hour`)
+    Hour,
+}
+
+impl fmt::Display for BudgetWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetWindow::Request => write!(f, "per-request"),
+            BudgetWindow::Hour => write!(f, "hourly"),
+        }
+    }
+}
+
+/// Cost limit a run must stay under, in USD
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostBudget {
+    pub window: BudgetWindow,
+    pub limit: f64,
+    /// Spend counted against the limit before the run started
+    pub spent_before: f64,
+}
+
+impl CostBudget {
+    /// Budget for a single run
+    pub fn per_request(limit: f64) -> Self {
+        Self {
+            window: BudgetWindow::Request,
+            limit,
+            spent_before: 0.0,
+        }
+    }
+
+    /// Hourly budget with `spent` already used by earlier runs
+    pub fn hourly(limit: f64, spent: f64) -> Self {
+        Self {
+            window: BudgetWindow::Hour,
+            limit,
+            spent_before: spent,
+        }
+    }
+
+    /// What the current run may still spend
+    pub fn remaining(&self) -> f64 {
+        self.limit - self.spent_before
+    }
+
+    /// Fail if a run costing `run_cost` has reached the limit
+    pub fn check(&self, run_cost: f64) -> Result<()> {
+        let spent = self.spent_before + run_cost;
+        if spent >= self.limit {
+            return Err(RuntimeError::BudgetExceeded {
+                window: self.window,
+                limit: self.limit,
+                spent,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The tightest budget that applies to a new run of the agent
+///
+/// Without a run history the hourly limit only covers the current run.
+/// An earlier record of `run_id` (a paused run being resumed) isn't counted,
+/// since the resumed run starts from its cost.
+pub(crate) fn run_budget(
+    config: &AgentConfig,
+    history: Option<&Mutex<SqliteStorage>>,
+    run_id: &str,
+) -> Result<Option<CostBudget>> {
+    let Some(governance) = config
+        .middleware
+        .as_ref()
+        .and_then(|m| m.governance.as_ref())
+        .filter(|g| g.enabled)
+    else {
+        return Ok(None);
+    };
+
+    let per_request = governance.max_cost_per_request.map(CostBudget::per_request);
+
+    let hourly = match governance.max_cost_per_hour {
+        Some(limit) => {
+            let spent = match history {
+                Some(history) => history
+                    .lock()
+                    .map_err(|_| RuntimeError::Other("Run history lock poisoned".to_string()))?
+                    .cost_since(
+                        &config.name,
+                        Utc::now() - chrono::Duration::hours(1),
+                        Some(run_id),
+                    )
+                    .map_err(|e| {
+                        RuntimeError::Other(format!("Could not read run history: {}", e))
+                    })?,
+                None => 0.0,
+            };
+            Some(CostBudget::hourly(limit, spent))
+        }
+        None => None,
+    };
+
+    Ok([per_request, hourly]
+        .into_iter()
+        .flatten()
+        .min_by(|a, b| a.remaining().total_cmp(&b.remaining())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_config;
+    use namra_config::{GovernanceConfig, MiddlewareConfig};
+    use namra_storage::{RunRecord, StopReason};
+    use std::collections::HashMap;
+
+    fn governed(per_request: Option<f64>, per_hour: Option<f64>) -> AgentConfig {
+        let mut config = test_config();
+        config.middleware = Some(MiddlewareConfig {
+            governance: Some(GovernanceConfig {
+                enabled: true,
+                max_cost_per_request: per_request,
+                max_cost_per_hour: per_hour,
+                require_approval_for: vec![],
+                policies: vec![],
+                compliance_standards: vec![],
+                audit_log: None,
+            }),
+            ..Default::default()
+        });
+        config
+    }
+
+    fn past_run(agent_name: &str, cost: f64) -> RunRecord {
+        RunRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            agent_name: agent_name.to_string(),
+            agent_version: None,
+            input_prompt: "question".to_string(),
+            response: None,
+            success: true,
+            stop_reason: StopReason::Completed,
+            error_message: None,
+            iterations: 1,
+            total_tokens: 10,
+            total_cost: cost,
+            execution_time_ms: 5,
+            llm_provider: None,
+            llm_model: None,
+            started_at: Utc::now(),
+            completed_at: Utc::now(),
+            tool_calls: vec![],
+            thoughts: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_picks_tightest_budget() {
+        let config = governed(Some(1.0), Some(10.0));
+        let history = Mutex::new(SqliteStorage::open_memory().unwrap());
+        history
+            .lock()
+            .unwrap()
+            .save_run(&past_run(&config.name, 9.5))
+            .unwrap();
+
+        let budget = run_budget(&config, Some(&history), "run-2")
+            .unwrap()
+            .unwrap();
+        assert_eq!(budget.window, BudgetWindow::Hour);
+        assert!(budget.check(0.4).is_ok());
+        assert!(matches!(
+            budget.check(0.5),
+            Err(RuntimeError::BudgetExceeded {
+                window: BudgetWindow::Hour,
+                ..
+            })
+        ));
+
+        // Without history only the current run counts
+        let budget = run_budget(&config, None, "run-2").unwrap().unwrap();
+        assert_eq!(budget, CostBudget::per_request(1.0));
+    }
+
+    #[test]
+    fn test_disabled_governance_has_no_budget() {
+        let mut config = governed(Some(1.0), None);
+        config
+            .middleware
+            .as_mut()
+            .unwrap()
+            .governance
+            .as_mut()
+            .unwrap()
+            .enabled = false;
+
+        assert!(run_budget(&config, None, "run-2").unwrap().is_none());
+    }
+}
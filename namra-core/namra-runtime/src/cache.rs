@@ -0,0 +1,739 @@
+//! Response caching from `middleware.caching`
+//!
+//! [`CachingLLM`] answers a request from a [`ResponseCache`] when the same
+//! request, with whitespace normalized, was answered within the TTL, and
+//! stores the responses it has to fetch. With the `semantic` strategy a
+//! request whose conversation embeds close enough to a cached one (cosine
+//! similarity at or above `similarity_threshold`) reuses that response; the
+//! model, sampling settings, tools, and system prompt must still match
+//! exactly.
+//!
+//! Semantic hits are only as good as the [`Embedder`]: one that doesn't
+//! capture meaning returns the cached answer to a different question that
+//! shares most of its words ("Is X safe?" and "Is X not safe?"). The runtime
+//! has no embedder of its own, so the `semantic` strategy needs one supplied
+//! through [`AgentExecutorBuilder::cache_embedder`](crate::AgentExecutorBuilder::cache_embedder).
+//!
+//! Hits cost nothing: the cached response's usage is zeroed and the original
+//! usage is kept in the response's `cache` metadata entry, which marks the
+//! hit. Only complete responses (stopped or asking for tools) are cached.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use namra_config::{parse_duration, AgentConfig, CachingConfig};
+use namra_llm::adapter::{LLMAdapter, LLMResult, LLMStream};
+use namra_llm::types::{
+    FinishReason, LLMRequest, LLMResponse, Message, MessageRole, StreamChunk, TokenUsage,
+    ToolCallDelta,
+};
+use namra_llm::StreamAccumulator;
+use namra_middleware::observability::{llm_cache_span, record_llm_cache};
+use namra_storage::{CacheEntry, SqliteStorage};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::Instrument;
+
+use crate::error::{Result, RuntimeError};
+
+/// Similarity threshold used when `similarity_threshold` is not set
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.95;
+
+/// Storage for cached responses
+pub trait ResponseCache: Send + Sync {
+    /// Entry stored under `key`, unless it expired by `now`
+    fn get(&self, key: &str, now: DateTime<Utc>) -> Result<Option<CacheEntry>>;
+
+    /// Unexpired entries in `scope` that have an embedding
+    fn candidates(&self, scope: &str, now: DateTime<Utc>) -> Result<Vec<CacheEntry>>;
+
+    /// Store an entry, replacing any with the same key
+    fn put(&self, entry: CacheEntry) -> Result<()>;
+}
+
+/// Cache kept in memory for the life of the process
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, CacheEntry>>> {
+        self.entries
+            .lock()
+            .map_err(|_| RuntimeError::Other("Response cache lock poisoned".to_string()))
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    fn get(&self, key: &str, now: DateTime<Utc>) -> Result<Option<CacheEntry>> {
+        Ok(self
+            .entries()?
+            .get(key)
+            .filter(|entry| entry.expires_at > now)
+            .cloned())
+    }
+
+    fn candidates(&self, scope: &str, now: DateTime<Utc>) -> Result<Vec<CacheEntry>> {
+        Ok(self
+            .entries()?
+            .values()
+            .filter(|e| e.scope == scope && e.expires_at > now && e.embedding.is_some())
+            .cloned()
+            .collect())
+    }
+
+    fn put(&self, entry: CacheEntry) -> Result<()> {
+        let mut entries = self.entries()?;
+        entries.retain(|_, e| e.expires_at > entry.created_at);
+        entries.insert(entry.key.clone(), entry);
+        Ok(())
+    }
+}
+
+/// Cache kept in a SQLite database, shared between processes
+pub struct SqliteCache {
+    store: Arc<Mutex<SqliteStorage>>,
+}
+
+impl SqliteCache {
+    /// Cache in an open database, such as the run history
+    pub fn new(store: Arc<Mutex<SqliteStorage>>) -> Self {
+        Self { store }
+    }
+
+    /// Cache in the database at `path`, creating it if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        let storage = SqliteStorage::open(path).map_err(|e| {
+            RuntimeError::ConfigError(format!(
+                "Could not open response cache {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(Self::new(Arc::new(Mutex::new(storage))))
+    }
+
+    fn with_store<T>(
+        &self,
+        f: impl FnOnce(&SqliteStorage) -> namra_storage::StorageResult<T>,
+    ) -> Result<T> {
+        let store = self
+            .store
+            .lock()
+            .map_err(|_| RuntimeError::Other("Response cache lock poisoned".to_string()))?;
+        f(&store).map_err(|e| RuntimeError::Other(format!("Response cache error: {}", e)))
+    }
+}
+
+impl ResponseCache for SqliteCache {
+    fn get(&self, key: &str, now: DateTime<Utc>) -> Result<Option<CacheEntry>> {
+        self.with_store(|store| store.get_cache_entry(key, now))
+    }
+
+    fn candidates(&self, scope: &str, now: DateTime<Utc>) -> Result<Vec<CacheEntry>> {
+        self.with_store(|store| store.cache_entries_in_scope(scope, now))
+    }
+
+    fn put(&self, entry: CacheEntry) -> Result<()> {
+        self.with_store(|store| store.put_cache_entry(&entry))
+    }
+}
+
+/// Turns text into a vector for semantic cache lookups
+///
+/// Use a model-backed embedding: texts that embed close together are
+/// treated as the same question.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// LLM adapter that serves repeated requests from a cache
+pub struct CachingLLM {
+    inner: Arc<dyn LLMAdapter>,
+    cache: Arc<dyn ResponseCache>,
+    ttl: Duration,
+    semantic: Option<(Arc<dyn Embedder>, f32)>,
+}
+
+/// Where a request belongs in the cache and whether it was found there
+struct Lookup {
+    key: String,
+    scope: String,
+    embedding: Option<Vec<f32>>,
+    hit: Option<LLMResponse>,
+}
+
+impl CachingLLM {
+    /// Cache `inner`'s responses for `ttl`, matching requests exactly
+    pub fn new(inner: Arc<dyn LLMAdapter>, cache: Arc<dyn ResponseCache>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            cache,
+            ttl,
+            semantic: None,
+        }
+    }
+
+    /// Also reuse responses to conversations at least `threshold` similar
+    pub fn with_semantic_matching(mut self, embedder: Arc<dyn Embedder>, threshold: f32) -> Self {
+        self.semantic = Some((embedder, threshold));
+        self
+    }
+
+    fn strategy(&self) -> &'static str {
+        if self.semantic.is_some() {
+            "semantic"
+        } else {
+            "exact"
+        }
+    }
+
+    /// Look the request up, treating cache failures as misses
+    async fn lookup(&self, request: &LLMRequest) -> Lookup {
+        let (scope, conversation) = request_scope(request);
+        let key = sha256_hex(&json!({"scope": scope, "conversation": conversation}).to_string());
+        let mut lookup = Lookup {
+            key,
+            scope,
+            embedding: None,
+            hit: None,
+        };
+
+        let now = Utc::now();
+        match self.cache.get(&lookup.key, now) {
+            Ok(Some(entry)) => {
+                lookup.hit = cached_response(entry, self.strategy(), None);
+                if lookup.hit.is_some() {
+                    return lookup;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "Response cache lookup failed"),
+        }
+
+        let Some((embedder, threshold)) = &self.semantic else {
+            return lookup;
+        };
+        let embedding = match embedder.embed(&conversation_text(&conversation)).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                tracing::warn!(error = %e, "Could not embed request for the response cache");
+                return lookup;
+            }
+        };
+
+        let candidates = self
+            .cache
+            .candidates(&lookup.scope, now)
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Response cache lookup failed");
+                Vec::new()
+            });
+        let best = candidates
+            .into_iter()
+            .filter_map(|entry| {
+                let similarity = cosine_similarity(&embedding, entry.embedding.as_ref()?);
+                Some((entry, similarity))
+            })
+            .filter(|(_, similarity)| similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((entry, similarity)) = best {
+            lookup.hit = cached_response(entry, self.strategy(), Some(similarity));
+        }
+        lookup.embedding = Some(embedding);
+        lookup
+    }
+
+    /// Record the outcome of a lookup on the cache span
+    fn record(&self, span: &tracing::Span, lookup: &Lookup) {
+        let similarity = lookup
+            .hit
+            .as_ref()
+            .and_then(|r| r.metadata["cache"]["similarity"].as_f64())
+            .map(|s| s as f32);
+        record_llm_cache(span, lookup.hit.is_some(), similarity);
+    }
+}
+
+/// Entry storing `response` for a lookup that missed
+///
+/// `None` for incomplete responses, which are not cached.
+fn cache_entry(lookup: Lookup, ttl: Duration, response: &LLMResponse) -> Option<CacheEntry> {
+    if !matches!(
+        response.finish_reason,
+        FinishReason::Stop | FinishReason::ToolCalls
+    ) {
+        return None;
+    }
+    let now = Utc::now();
+    Some(CacheEntry {
+        key: lookup.key,
+        scope: lookup.scope,
+        embedding: lookup.embedding,
+        response: serde_json::to_value(response).ok()?,
+        created_at: now,
+        expires_at: now + chrono::Duration::from_std(ttl).ok()?,
+    })
+}
+
+fn store(cache: &dyn ResponseCache, entry: Option<CacheEntry>) {
+    if let Some(entry) = entry {
+        if let Err(e) = cache.put(entry) {
+            tracing::warn!(error = %e, "Could not store response in cache");
+        }
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for CachingLLM {
+    fn provider_name(&self) -> &str {
+        self.inner.provider_name()
+    }
+
+    async fn generate(&self, request: LLMRequest) -> LLMResult<LLMResponse> {
+        let span = llm_cache_span(self.provider_name(), &request.model, self.strategy());
+
+        async {
+            let mut lookup = self.lookup(&request).await;
+            self.record(&span, &lookup);
+            if let Some(response) = lookup.hit.take() {
+                return Ok(response);
+            }
+
+            let response = self.inner.generate(request).await?;
+            store(
+                self.cache.as_ref(),
+                cache_entry(lookup, self.ttl, &response),
+            );
+            Ok(response)
+        }
+        .instrument(span.clone())
+        .await
+    }
+
+    async fn stream(&self, request: LLMRequest) -> LLMResult<LLMStream> {
+        let span = llm_cache_span(self.provider_name(), &request.model, self.strategy());
+
+        let (lookup, stream) = async {
+            let mut lookup = self.lookup(&request).await;
+            self.record(&span, &lookup);
+            if let Some(response) = lookup.hit.take() {
+                return Ok((None, replay(response)));
+            }
+            let stream = self.inner.stream(request).await?;
+            LLMResult::Ok((Some(lookup), stream))
+        }
+        .instrument(span.clone())
+        .await?;
+
+        let Some(lookup) = lookup else {
+            return Ok(stream);
+        };
+
+        // Store the response once the stream has been read to the end
+        let state = Arc::new(Mutex::new(Some(StreamAccumulator::new())));
+        let recorder = state.clone();
+        let recorded = stream.inspect(move |chunk| {
+            let mut state = recorder.lock().unwrap_or_else(|e| e.into_inner());
+            match chunk {
+                Ok(chunk) => {
+                    if let Some(accumulator) = state.as_mut() {
+                        accumulator.push(chunk);
+                    }
+                }
+                Err(_) => *state = None,
+            }
+        });
+
+        let cache = self.cache.clone();
+        let ttl = self.ttl;
+        let finish = futures::stream::once(async move {
+            let accumulator = state.lock().unwrap_or_else(|e| e.into_inner()).take();
+            if let Some(accumulator) = accumulator {
+                store(
+                    cache.as_ref(),
+                    cache_entry(lookup, ttl, &accumulator.finish()),
+                );
+            }
+            None
+        })
+        .filter_map(|chunk: Option<LLMResult<StreamChunk>>| async move { chunk });
+
+        Ok(Box::pin(recorded.chain(finish)))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.inner.supports_tools()
+    }
+
+    fn max_context_tokens(&self, model: &str) -> Option<u32> {
+        self.inner.max_context_tokens(model)
+    }
+
+    fn estimate_cost(&self, input_tokens: u32, output_tokens: u32, model: &str) -> Option<f64> {
+        self.inner.estimate_cost(input_tokens, output_tokens, model)
+    }
+}
+
+/// Wrap the agent's LLM according to `middleware.caching`
+///
+/// `cache` and `embedder` override the store and embedder built from the
+/// config. The `sqlite` backend uses `caching.path`, or else the run
+/// history database.
+pub(crate) fn wrap_llm(
+    config: &AgentConfig,
+    llm: Arc<dyn LLMAdapter>,
+    cache: Option<Arc<dyn ResponseCache>>,
+    embedder: Option<Arc<dyn Embedder>>,
+    history: Option<&Arc<Mutex<SqliteStorage>>>,
+) -> Result<Arc<dyn LLMAdapter>> {
+    let Some(caching) = caching_config(config) else {
+        return Ok(llm);
+    };
+
+    let ttl = parse_duration(&caching.ttl)
+        .map_err(|e| RuntimeError::ConfigError(format!("Invalid caching ttl: {}", e)))?;
+
+    let cache: Arc<dyn ResponseCache> = match (cache, caching.backend.as_str()) {
+        (Some(cache), _) => cache,
+        (None, "memory") => Arc::new(MemoryCache::new()),
+        (None, "sqlite") => match (&caching.path, history) {
+            (Some(path), _) => Arc::new(SqliteCache::open(Path::new(path))?),
+            (None, Some(history)) => Arc::new(SqliteCache::new(history.clone())),
+            (None, None) => {
+                return Err(RuntimeError::ConfigError(
+                    "The sqlite caching backend needs caching.path or a run history database"
+                        .to_string(),
+                ))
+            }
+        },
+        (None, backend) => {
+            return Err(RuntimeError::ConfigError(format!(
+                "Unsupported caching backend: {}",
+                backend
+            )))
+        }
+    };
+
+    let caching_llm = CachingLLM::new(llm, cache, ttl);
+    let caching_llm = match caching.strategy.as_str() {
+        "exact" => caching_llm,
+        "semantic" => {
+            let embedder = embedder.ok_or_else(|| {
+                RuntimeError::ConfigError(
+                    "The semantic caching strategy needs an embedding model; supply one with \
+                     AgentExecutorBuilder::cache_embedder or use strategy: exact"
+                        .to_string(),
+                )
+            })?;
+            caching_llm.with_semantic_matching(
+                embedder,
+                caching
+                    .similarity_threshold
+                    .unwrap_or(DEFAULT_SIMILARITY_THRESHOLD),
+            )
+        }
+        strategy => {
+            return Err(RuntimeError::ConfigError(format!(
+                "Unsupported caching strategy: {}",
+                strategy
+            )))
+        }
+    };
+
+    Ok(Arc::new(caching_llm))
+}
+
+fn caching_config(config: &AgentConfig) -> Option<&CachingConfig> {
+    config
+        .middleware
+        .as_ref()?
+        .caching
+        .as_ref()
+        .filter(|c| c.enabled)
+}
+
+/// Split a request into the settings a cached response must share exactly
+/// (hashed into the scope) and the normalized conversation
+fn request_scope(request: &LLMRequest) -> (String, Vec<Value>) {
+    let (system, conversation): (Vec<&Message>, Vec<&Message>) = request
+        .messages
+        .iter()
+        .partition(|m| m.role == MessageRole::System);
+
+    let settings = json!({
+        "model": request.model,
+        "temperature": request.temperature,
+        "max_tokens": request.max_tokens,
+        "top_p": request.top_p,
+        "stop_sequences": request.stop_sequences,
+        "tools": request.tools,
+        "extra": request.extra,
+        "system": system.iter().map(|m| normalize_whitespace(&m.content)).collect::<Vec<_>>(),
+    });
+
+    let conversation = conversation
+        .into_iter()
+        .map(|m| {
+            json!({
+                "role": m.role,
+                "content": normalize_whitespace(&m.content),
+                "name": m.name,
+                "tool_calls": m.tool_calls,
+                "tool_call_id": m.tool_call_id,
+                "is_error": m.is_error,
+            })
+        })
+        .collect();
+
+    (sha256_hex(&settings.to_string()), conversation)
+}
+
+/// Text of a normalized conversation, for embedding
+fn conversation_text(conversation: &[Value]) -> String {
+    conversation
+        .iter()
+        .map(|m| {
+            format!(
+                "{}: {}",
+                m["role"].as_str().unwrap_or_default(),
+                m["content"].as_str().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Response from a cache entry, marked as a hit and with its usage zeroed
+fn cached_response(
+    entry: CacheEntry,
+    strategy: &str,
+    similarity: Option<f32>,
+) -> Option<LLMResponse> {
+    let mut response: LLMResponse = serde_json::from_value(entry.response).ok()?;
+    let saved = std::mem::replace(&mut response.usage, TokenUsage::new(0, 0).with_cost(0.0));
+
+    let mut marker = json!({
+        "hit": true,
+        "strategy": strategy,
+        "cached_at": entry.created_at.to_rfc3339(),
+        "saved_tokens": saved.total_tokens,
+    });
+    if let Some(cost) = saved.cost {
+        marker["saved_cost"] = json!(cost);
+    }
+    if let Some(similarity) = similarity {
+        marker["similarity"] = json!(similarity);
+    }
+    response.metadata.insert("cache".to_string(), marker);
+    Some(response)
+}
+
+/// Stream a cached response as the chunks a provider would send
+fn replay(response: LLMResponse) -> LLMStream {
+    let chunk = |content: String, tool_call_delta: Option<ToolCallDelta>| StreamChunk {
+        content,
+        tool_call_delta,
+        is_final: false,
+        usage: None,
+        finish_reason: None,
+        metadata: HashMap::new(),
+    };
+
+    let mut chunks = Vec::new();
+    if !response.content.is_empty() {
+        chunks.push(chunk(response.content, None));
+    }
+    for (index, call) in response.tool_calls.into_iter().flatten().enumerate() {
+        chunks.push(chunk(
+            String::new(),
+            Some(ToolCallDelta {
+                index,
+                id: Some(call.id),
+                name: Some(call.name),
+                arguments: Some(call.arguments.to_string()),
+            }),
+        ));
+    }
+    chunks.push(StreamChunk {
+        is_final: true,
+        usage: Some(response.usage),
+        finish_reason: Some(response.finish_reason),
+        metadata: response.metadata,
+        ..chunk(String::new(), None)
+    });
+
+    Box::pin(futures::stream::iter(chunks.into_iter().map(Ok)))
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+fn sha256_hex(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{text_response, HashingEmbedder, ScriptedLLM};
+
+    fn request(question: &str) -> LLMRequest {
+        LLMRequest::new(
+            "test-model",
+            vec![Message::system("Be brief."), Message::user(question)],
+        )
+    }
+
+    fn caching(llm: Arc<ScriptedLLM>) -> CachingLLM {
+        CachingLLM::new(llm, Arc::new(MemoryCache::new()), Duration::from_secs(60))
+    }
+
+    #[tokio::test]
+    async fn test_exact_hits_ignore_whitespace() {
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            text_response("Paris"),
+            text_response("Berlin"),
+        ]));
+        let cached = caching(llm.clone());
+
+        let first = cached
+            .generate(request("Capital of France?"))
+            .await
+            .unwrap();
+        assert!(!first.metadata.contains_key("cache"));
+
+        let second = cached
+            .generate(request("  Capital of\nFrance? "))
+            .await
+            .unwrap();
+        assert_eq!(second.content, "Paris");
+        assert_eq!(second.metadata["cache"]["hit"], true);
+        assert_eq!(second.usage.total_tokens, 0);
+        assert_eq!(second.usage.cost, Some(0.0));
+
+        let other = cached
+            .generate(request("Capital of Germany?"))
+            .await
+            .unwrap();
+        assert_eq!(other.content, "Berlin");
+        assert_eq!(llm.requests().len(), 2);
+
+        // Different settings are a different request
+        let hotter = request("Capital of France?").with_temperature(1.0);
+        assert!(cached.generate(hotter).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire() {
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            text_response("one"),
+            text_response("two"),
+        ]));
+        let cached = CachingLLM::new(llm, Arc::new(MemoryCache::new()), Duration::ZERO);
+
+        cached.generate(request("Count")).await.unwrap();
+        let again = cached.generate(request("Count")).await.unwrap();
+        assert_eq!(again.content, "two");
+    }
+
+    #[tokio::test]
+    async fn test_semantic_hits() {
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            text_response("Paris"),
+            text_response("Rome"),
+        ]));
+        let cached =
+            caching(llm.clone()).with_semantic_matching(Arc::new(HashingEmbedder::default()), 0.8);
+
+        cached
+            .generate(request("What is the capital of France?"))
+            .await
+            .unwrap();
+        let similar = cached
+            .generate(request("what is the capital of France"))
+            .await
+            .unwrap();
+        assert_eq!(similar.content, "Paris");
+        assert_eq!(similar.metadata["cache"]["strategy"], "semantic");
+        assert!(similar.metadata["cache"]["similarity"].as_f64().unwrap() >= 0.8);
+
+        let different = cached
+            .generate(request("Name the capital city of Italy"))
+            .await
+            .unwrap();
+        assert_eq!(different.content, "Rome");
+    }
+
+    #[test]
+    fn test_semantic_strategy_needs_an_embedder() {
+        use crate::testing::test_config;
+        use namra_config::MiddlewareConfig;
+
+        let mut config = test_config();
+        config.middleware = Some(MiddlewareConfig {
+            caching: Some(
+                serde_yaml::from_str("{enabled: true, backend: memory, strategy: semantic}")
+                    .unwrap(),
+            ),
+            ..Default::default()
+        });
+        let llm = || Arc::new(ScriptedLLM::new(vec![])) as Arc<dyn LLMAdapter>;
+
+        let err = wrap_llm(&config, llm(), None, None, None).err().unwrap();
+        assert!(matches!(err, RuntimeError::ConfigError(_)));
+        let embedder: Arc<dyn Embedder> = Arc::new(HashingEmbedder::default());
+        assert!(wrap_llm(&config, llm(), None, Some(embedder), None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_streamed_responses_are_cached() {
+        let llm = Arc::new(ScriptedLLM::new(vec![text_response("streamed answer")]));
+        let store = Arc::new(SqliteCache::new(Arc::new(Mutex::new(
+            SqliteStorage::open_memory().unwrap(),
+        ))));
+        let cached = CachingLLM::new(llm.clone(), store, Duration::from_secs(60));
+
+        for _ in 0..2 {
+            let mut stream = cached.stream(request("Tell me")).await.unwrap();
+            let mut accumulator = StreamAccumulator::new();
+            while let Some(chunk) = stream.next().await {
+                accumulator.push(&chunk.unwrap());
+            }
+            assert_eq!(accumulator.finish().content, "streamed answer");
+        }
+        assert_eq!(llm.requests().len(), 1);
+
+        let hit = cached.generate(request("Tell me")).await.unwrap();
+        assert_eq!(hit.metadata["cache"]["hit"], true);
+    }
+}
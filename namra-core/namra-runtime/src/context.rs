@@ -1,9 +1,21 @@
 //! Execution context for agent runs
 
 use namra_llm::types::{Message, TokenUsage};
+use namra_memory::Summarizer;
+use namra_middleware::audit::{AuditEvent, AuditLog};
+use namra_middleware::observability::record_security_detection;
+use namra_middleware::security::{GuardPipeline, GuardStage};
+
+use crate::approval::ApprovalGate;
+use crate::budget::CostBudget;
+use crate::error::{Result, RuntimeError};
+use crate::rate_limit::{CallKind, RateLimiter};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc::UnboundedSender;
 
 /// Execution context tracks the state of an agent run
 #[derive(Debug, Clone)]
@@ -40,6 +52,46 @@ pub struct ExecutionContext {
 
     /// Metadata
     pub metadata: HashMap<String, serde_json::Value>,
+
+    /// Receiver of live events; when set, strategies stream LLM output
+    pub events: Option<UnboundedSender<StreamEvent>>,
+
+    /// Cost budget the run must stay under, if any
+    pub budget: Option<CostBudget>,
+
+    /// Review of tool calls that need approval
+    pub approvals: Option<ApprovalGate>,
+
+    /// Audit log receiving the run's activity
+    pub audit: Option<Arc<AuditLog>>,
+
+    /// Security guards checking input, tool output, and the response
+    pub guards: Option<Arc<GuardPipeline>>,
+
+    /// Rate limits the run's LLM and tool calls count against
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// Summarizer keeping the conversation within `memory.summarization`
+    pub summarizer: Option<Arc<Summarizer>>,
+}
+
+/// Live event emitted during a streaming agent run
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    /// Text generated by the LLM
+    Token(String),
+
+    /// The LLM requested a tool call
+    ToolCall {
+        name: String,
+        arguments: serde_json::Value,
+    },
+
+    /// A tool call finished
+    ToolResult { name: String, success: bool },
+
+    /// An LLM call finished with the given usage
+    Usage(TokenUsage),
 }
 
 /// Record of a tool call
@@ -79,6 +131,26 @@ impl ExecutionContext {
             tool_calls: Vec::new(),
             thoughts: Vec::new(),
             metadata: HashMap::new(),
+            events: None,
+            budget: None,
+            approvals: None,
+            audit: None,
+            guards: None,
+            rate_limiter: None,
+            summarizer: None,
+        }
+    }
+
+    /// Whether live events are being streamed
+    pub fn is_streaming(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Emit a live event if streaming is enabled
+    pub fn emit(&self, event: StreamEvent) {
+        if let Some(events) = &self.events {
+            // The receiver going away shouldn't fail the run
+            let _ = events.send(event);
         }
     }
 
@@ -113,6 +185,106 @@ impl ExecutionContext {
         self.total_cost += cost;
     }
 
+    /// Write an event to the audit log, if the run has one
+    ///
+    /// Write failures are logged rather than failing the run.
+    pub fn audit(&self, event: AuditEvent) {
+        if let Some(log) = &self.audit {
+            if let Err(e) = log.record(&self.id, event) {
+                tracing::error!(
+                    error = %e,
+                    path = %log.path().display(),
+                    "Could not write audit record"
+                );
+            }
+        }
+    }
+
+    /// Pass content through the security guards, if the run has any
+    ///
+    /// Returns the content as changed by the guards. Detections are added to
+    /// the `security_detections` metadata and logged on the current span.
+    pub fn guard(
+        &mut self,
+        stage: GuardStage,
+        source: Option<&str>,
+        text: String,
+    ) -> Result<String> {
+        let Some(guards) = &self.guards else {
+            return Ok(text);
+        };
+        let scan = guards.scan(stage, source, text);
+        if scan.detections.is_empty() {
+            return Ok(scan.text);
+        }
+
+        let span = tracing::Span::current();
+        for detection in &scan.detections {
+            record_security_detection(
+                &span,
+                &detection.guard,
+                &detection.rule,
+                &detection.stage.to_string(),
+                detection.source.as_deref(),
+                detection.blocked,
+            );
+        }
+
+        let recorded = self
+            .metadata
+            .entry("security_detections".to_string())
+            .or_insert_with(|| serde_json::json!([]));
+        if let Some(recorded) = recorded.as_array_mut() {
+            recorded.extend(
+                scan.detections
+                    .iter()
+                    .filter_map(|d| serde_json::to_value(d).ok()),
+            );
+        }
+
+        match scan.blocked_by {
+            Some(guard) => Err(RuntimeError::GuardRejected {
+                rules: scan
+                    .detections
+                    .iter()
+                    .filter(|d| d.guard == guard)
+                    .map(|d| d.rule.clone())
+                    .collect(),
+                guard,
+            }),
+            None => Ok(scan.text),
+        }
+    }
+
+    /// Number of security detections recorded so far
+    pub fn security_detections(&self) -> usize {
+        self.metadata
+            .get("security_detections")
+            .and_then(|d| d.as_array())
+            .map_or(0, Vec::len)
+    }
+
+    /// Wait for the rate limits to allow a `kind` call
+    ///
+    /// Fails if the wait would run past the run's timeout.
+    pub async fn acquire_rate(&self, kind: CallKind) -> Result<()> {
+        match &self.rate_limiter {
+            Some(limiter) => {
+                let remaining = self.timeout.saturating_sub(self.elapsed());
+                limiter.acquire(kind, remaining).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Fail once the run's cost has reached its budget
+    pub fn check_budget(&self) -> Result<()> {
+        match &self.budget {
+            Some(budget) => budget.check(self.total_cost),
+            None => Ok(()),
+        }
+    }
+
     /// Record a tool call
     pub fn record_tool_call(&mut self, record: ToolCallRecord) {
         self.tool_calls.push(record);
@@ -166,6 +338,14 @@ pub struct ExecutionResult {
 
     /// Intermediate thoughts/reasoning from each iteration
     pub thoughts: Vec<String>,
+
+    /// Error message when the run failed
+    #[serde(default)]
+    pub error: Option<String>,
+
+    /// Strategy- and middleware-specific details collected during the run
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
 }
 
 /// Reason execution stopped
@@ -185,6 +365,44 @@ pub enum StopReason {
 
     /// User requested stop
     UserStop,
+
+    /// Response was cut off at the max_tokens limit
+    Truncated,
+
+    /// A cost budget from `middleware.governance` was reached
+    BudgetExceeded,
+
+    /// Paused until pending tool calls are approved or denied
+    AwaitingApproval,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::Completed => write!(f, "completed"),
+            StopReason::MaxIterations => write!(f, "max_iterations"),
+            StopReason::Timeout => write!(f, "timeout"),
+            StopReason::Error(_) => write!(f, "error"),
+            StopReason::UserStop => write!(f, "user_stop"),
+            StopReason::Truncated => write!(f, "truncated"),
+            StopReason::BudgetExceeded => write!(f, "budget_exceeded"),
+            StopReason::AwaitingApproval => write!(f, "awaiting_approval"),
+        }
+    }
+}
+
+impl From<&RuntimeError> for StopReason {
+    fn from(err: &RuntimeError) -> Self {
+        match err {
+            RuntimeError::MaxIterationsReached(_) => StopReason::MaxIterations,
+            RuntimeError::Timeout(_) => StopReason::Timeout,
+            RuntimeError::Stopped(_) => StopReason::UserStop,
+            RuntimeError::Truncated { .. } => StopReason::Truncated,
+            RuntimeError::BudgetExceeded { .. } => StopReason::BudgetExceeded,
+            RuntimeError::ApprovalPending { .. } => StopReason::AwaitingApproval,
+            err => StopReason::Error(err.to_string()),
+        }
+    }
 }
 
 impl ExecutionResult {
@@ -211,6 +429,8 @@ impl ExecutionResult {
             execution_time_ms,
             stop_reason: StopReason::Completed,
             thoughts,
+            error: None,
+            metadata: HashMap::new(),
         }
     }
 
@@ -235,10 +455,24 @@ impl ExecutionResult {
             total_tokens,
             total_cost,
             execution_time_ms,
-            stop_reason: StopReason::Error(error),
+            stop_reason: StopReason::Error(error.clone()),
             thoughts,
+            error: Some(error),
+            metadata: HashMap::new(),
         }
     }
+
+    /// Replace the stop reason, e.g. with one derived from the error
+    pub fn with_stop_reason(mut self, stop_reason: StopReason) -> Self {
+        self.stop_reason = stop_reason;
+        self
+    }
+
+    /// Attach metadata collected in the execution context
+    pub fn with_metadata(mut self, metadata: HashMap<String, serde_json::Value>) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -289,6 +523,37 @@ mod tests {
         assert_eq!(ctx.total_tokens(), 450);
     }
 
+    #[test]
+    fn test_emit_events() {
+        let mut ctx = ExecutionContext::new(10, Duration::from_secs(60));
+        assert!(!ctx.is_streaming());
+        ctx.emit(StreamEvent::Token("dropped".to_string()));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        ctx.events = Some(tx);
+        ctx.emit(StreamEvent::Token("Hi".to_string()));
+
+        assert!(matches!(rx.try_recv(), Ok(StreamEvent::Token(t)) if t == "Hi"));
+    }
+
+    #[test]
+    fn test_stop_reason_from_error() {
+        assert_eq!(
+            StopReason::from(&RuntimeError::MaxIterationsReached(3)),
+            StopReason::MaxIterations
+        );
+        assert_eq!(
+            StopReason::from(&RuntimeError::Truncated {
+                partial: String::new()
+            }),
+            StopReason::Truncated
+        );
+        assert_eq!(
+            StopReason::from(&RuntimeError::ToolNotFound("x".to_string())),
+            StopReason::Error("Tool not found: x".to_string())
+        );
+    }
+
     #[test]
     fn test_cost_tracking() {
         let mut ctx = ExecutionContext::new(10, Duration::from_secs(60));
@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::budget::BudgetWindow;
+
 /// Errors that can occur during agent execution
 #[derive(Debug, Error)]
 pub enum RuntimeError {
@@ -13,6 +15,10 @@ pub enum RuntimeError {
     #[error("Tool error: {0}")]
     ToolError(#[from] namra_tools::ToolError),
 
+    /// Conversation memory error
+    #[error("Memory error: {0}")]
+    MemoryError(#[from] namra_memory::MemoryError),
+
     /// Configuration error
     #[error("Configuration error: {0}")]
     ConfigError(String),
@@ -33,6 +39,53 @@ pub enum RuntimeError {
     #[error("Invalid tool call: {0}")]
     InvalidToolCall(String),
 
+    /// Response hit the max_tokens limit and could not be continued
+    #[error("Response truncated at the max_tokens limit")]
+    Truncated {
+        /// Text generated before the cut-off
+        partial: String,
+    },
+
+    /// Plan could not be completed, even after revisions
+    #[error("Plan failed: {0}")]
+    PlanFailed(String),
+
+    /// Spend reached a cost budget from `middleware.governance`
+    #[error("Cost budget exceeded: ${spent:.4} spent against the {window} limit of ${limit:.4}")]
+    BudgetExceeded {
+        /// Budget that was hit
+        window: BudgetWindow,
+        /// Configured limit in USD
+        limit: f64,
+        /// Spend counted against the limit in USD
+        spent: f64,
+    },
+
+    /// A rate limit from `security.rate_limit` or the tenancy quota was hit
+    #[error("Rate limit reached for {key}; next call allowed in {:.1}s", retry_after.as_secs_f64())]
+    RateLimited {
+        /// Bucket that ran out, such as `agent:reporter:llm`
+        key: String,
+        /// Time until the bucket has a token again
+        retry_after: std::time::Duration,
+    },
+
+    /// Tool calls are waiting for a human decision
+    #[error("Waiting for approval of tool calls: {}", approvals.join(", "))]
+    ApprovalPending {
+        /// IDs of the pending approvals
+        approvals: Vec<String>,
+    },
+
+    /// A security guard from `middleware.security` rejected the content
+    #[error("Rejected by the {guard} guard (matched {})", rules.join(", "))]
+    GuardRejected {
+        /// Guard that rejected the content
+        guard: String,
+        /// Rules that matched
+        rules: Vec<String>,
+    },
+
     /// Execution stopped
     #[error("Execution stopped: {0}")]
     Stopped(String),
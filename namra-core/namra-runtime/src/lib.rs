@@ -12,6 +12,8 @@
 //! - **ExecutionContext**: Tracks state during execution (messages, tokens, costs)
 //! - **Strategy**: Pluggable execution strategies (ReAct, Chain-of-Thought, etc.)
 //! - **ToolCallRecord**: Records details of each tool invocation
+//! - **WorkflowExecutor**: Runs workflow graphs of agents, passing each node's
+//!   output to the next
 //!
 //! # Example
 //!
@@ -37,7 +39,7 @@
 //!     .build()?;
 //!
 //! // Execute agent
-//! let result = executor.execute("What is 42 * 137?").await?;
+//! let result = executor.execute("What is 42 * 137?", None).await?;
 //! println!("Response: {}", result.response);
 //! println!("Tokens used: {}", result.total_tokens);
 //! println!("Cost: ${:.4}", result.total_cost);
@@ -51,6 +53,11 @@
 //! Currently implemented strategies:
 //!
 //! - **ReAct**: Reasoning and Acting - alternates between thinking and tool use
+//! - **Chain-of-Thought**: Explicit step-by-step reasoning before answering
+//! - **Tree-of-Thoughts**: Searches multiple reasoning paths scored by the LLM
+//! - **Plan-and-Execute**: Plans tool steps up front, executes them, and re-plans on failure
+//!
+//! `StrategyRegistry` builds the strategy named in `execution.strategy`.
 //!
 //! # Features
 //!
@@ -60,16 +67,42 @@
 //! - Tool call recording and history
 //! - Comprehensive error handling
 
+pub mod approval;
+pub mod budget;
+pub mod cache;
 pub mod context;
 pub mod error;
 pub mod executor;
+pub mod llm_factory;
+pub mod rate_limit;
+pub mod resilience;
 pub mod strategy;
 pub mod tool_factory;
+pub mod workflow;
+
+#[cfg(test)]
+mod testing;
 
 // Re-export key types for convenience
-pub use context::{ExecutionContext, ExecutionResult, StopReason, ToolCallRecord};
+pub use approval::{
+    ApprovalDecision, ApprovalGate, ApprovalHandler, ApprovalRequest, PausedRun, PendingApprovals,
+};
+pub use budget::{BudgetWindow, CostBudget};
+pub use cache::{CachingLLM, Embedder, MemoryCache, ResponseCache, SqliteCache};
+pub use context::{ExecutionContext, ExecutionResult, StopReason, StreamEvent, ToolCallRecord};
 pub use error::{Result, RuntimeError};
 pub use executor::{AgentExecutor, AgentExecutorBuilder};
+pub use llm_factory::LLMFactory;
+pub use rate_limit::{CallKind, RateLimiter};
+pub use resilience::{ResilientLLM, ResilientTool};
+pub use strategy::chain_of_thought::ChainOfThoughtStrategy;
+pub use strategy::plan_execute::{PlanExecuteStrategy, PlanStep};
 pub use strategy::react::ReActStrategy;
+pub use strategy::registry::{StrategyFactory, StrategyRegistry};
+pub use strategy::tree_of_thoughts::TreeOfThoughtsStrategy;
 pub use strategy::Strategy;
 pub use tool_factory::ToolFactory;
+pub use workflow::{
+    Arrival, Join, LlmRouter, NodeResult, PendingStep, RouteRules, WorkflowCheckpoint,
+    WorkflowExecutor, WorkflowExecutorBuilder, WorkflowResult,
+};
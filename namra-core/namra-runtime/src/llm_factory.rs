@@ -0,0 +1,174 @@
+//! LLM factory for creating adapters from configuration
+//!
+//! API keys come from `llm.api_key` (resolved when the config is loaded) or
+//! else from the environment (`ANTHROPIC_API_KEY`, `OPENAI_API_KEY`). When
+//! `llm.retry` is set the adapter is wrapped in a [`RetryingAdapter`] so
+//! transient provider failures are retried.
+
+use namra_config::{parse_duration, LLMConfig, RetryConfig};
+use namra_llm::{
+    AnthropicAdapter, Backoff, LLMAdapter, OpenAIAdapter, RetryPolicy, RetryingAdapter,
+};
+use std::env;
+use std::sync::Arc;
+
+use crate::error::{Result, RuntimeError};
+
+/// Factory for creating LLM adapters from configuration
+pub struct LLMFactory;
+
+impl LLMFactory {
+    /// Create a new LLM factory
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Build the adapter for `llm.provider`, with retries if configured
+    pub fn build(&self, config: &LLMConfig) -> Result<Arc<dyn LLMAdapter>> {
+        let adapter = self.build_provider(config)?;
+
+        match &config.retry {
+            Some(retry) => Ok(Arc::new(RetryingAdapter::new(
+                adapter,
+                self.retry_policy(retry)?,
+            ))),
+            None => Ok(adapter),
+        }
+    }
+
+    /// Build the provider adapter without any wrappers
+    fn build_provider(&self, config: &LLMConfig) -> Result<Arc<dyn LLMAdapter>> {
+        match config.provider.as_str() {
+            "anthropic" => {
+                let api_key = self.api_key(config, "ANTHROPIC_API_KEY")?.ok_or_else(|| {
+                    RuntimeError::ConfigError(
+                        "ANTHROPIC_API_KEY environment variable not set".to_string(),
+                    )
+                })?;
+                let mut builder = AnthropicAdapter::builder().api_key(api_key);
+                if let Some(base_url) = &config.base_url {
+                    builder = builder.base_url(base_url);
+                }
+                Ok(Arc::new(builder.build()))
+            }
+            "openai" => {
+                let mut builder = OpenAIAdapter::builder();
+                // Local OpenAI-compatible servers usually don't need a key
+                match (self.api_key(config, "OPENAI_API_KEY")?, &config.base_url) {
+                    (Some(api_key), _) => builder = builder.api_key(api_key),
+                    (None, Some(_)) => {}
+                    (None, None) => {
+                        return Err(RuntimeError::ConfigError(
+                            "OPENAI_API_KEY environment variable not set".to_string(),
+                        ))
+                    }
+                }
+                if let Some(base_url) = &config.base_url {
+                    builder = builder.base_url(base_url);
+                }
+                Ok(Arc::new(builder.build()))
+            }
+            provider => Err(RuntimeError::ConfigError(format!(
+                "Unsupported LLM provider: {}",
+                provider
+            ))),
+        }
+    }
+
+    /// `llm.api_key`, falling back to the `env_var` environment variable
+    fn api_key(&self, config: &LLMConfig, env_var: &str) -> Result<Option<String>> {
+        match &config.api_key {
+            Some(key) => key
+                .expose()
+                .map(|key| Some(key.to_string()))
+                .ok_or_else(|| {
+                    RuntimeError::ConfigError(
+                        "llm.api_key has unresolved secret references".to_string(),
+                    )
+                }),
+            None => Ok(env::var(env_var).ok()),
+        }
+    }
+
+    /// Convert `llm.retry` into a retry policy
+    pub fn retry_policy(&self, retry: &RetryConfig) -> Result<RetryPolicy> {
+        let backoff: Backoff = retry.backoff.parse().map_err(RuntimeError::ConfigError)?;
+        let initial_delay = parse_duration(&retry.initial_delay).map_err(|e| {
+            RuntimeError::ConfigError(format!("Invalid retry initial_delay: {}", e))
+        })?;
+        let max_delay = parse_duration(&retry.max_delay)
+            .map_err(|e| RuntimeError::ConfigError(format!("Invalid retry max_delay: {}", e)))?;
+
+        Ok(RetryPolicy::new(retry.max_attempts)
+            .with_backoff(backoff)
+            .with_initial_delay(initial_delay)
+            .with_max_delay(max_delay)
+            .with_jitter(retry.jitter))
+    }
+}
+
+impl Default for LLMFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_config;
+    use std::time::Duration;
+
+    #[test]
+    fn test_retry_policy_from_config() {
+        let retry = RetryConfig {
+            max_attempts: 5,
+            backoff: "linear".to_string(),
+            initial_delay: "500ms".to_string(),
+            max_delay: "10s".to_string(),
+            jitter: false,
+        };
+
+        let policy = LLMFactory::new().retry_policy(&retry).unwrap();
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(policy.backoff, Backoff::Linear);
+        assert_eq!(policy.delay(3), Duration::from_millis(1500));
+        assert_eq!(policy.max_delay, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_unsupported_provider() {
+        let mut config = test_config().llm;
+        config.provider = "carrier-pigeon".to_string();
+
+        let err = LLMFactory::new().build(&config).err().unwrap();
+        assert!(err.to_string().contains("Unsupported LLM provider"));
+    }
+
+    #[test]
+    fn test_local_openai_server_needs_no_key() {
+        let mut config = test_config().llm;
+        config.provider = "openai".to_string();
+        config.base_url = Some("http://localhost:8000/v1".to_string());
+        config.retry = Some(RetryConfig::default());
+
+        let adapter = LLMFactory::new().build(&config).unwrap();
+        assert_eq!(adapter.provider_name(), "openai");
+    }
+
+    #[test]
+    fn test_api_key_from_config() {
+        let mut config = test_config().llm;
+        config.provider = "anthropic".to_string();
+        config.api_key = Some("sk-ant-test".into());
+        let factory = LLMFactory::new();
+        assert_eq!(
+            factory.api_key(&config, "NAMRA_TEST_UNSET_KEY").unwrap(),
+            Some("sk-ant-test".to_string())
+        );
+        assert!(factory.build(&config).is_ok());
+
+        config.api_key = Some("${env:NAMRA_TEST_UNSET_KEY}".into());
+        assert!(factory.build(&config).is_err());
+    }
+}
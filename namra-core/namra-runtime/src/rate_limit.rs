@@ -0,0 +1,274 @@
+//! Rate limits from `security.rate_limit` and the tenancy resource quota
+//!
+//! `security.rate_limit` (such as `100/minute`) limits the agent, and
+//! `tenancy.resource_quota.max_requests_per_hour` limits every agent of the
+//! tenant together. Each limit is a token bucket, with separate buckets for
+//! LLM calls and tool calls. Buckets live in the run store
+//! ([`SqliteStorage`]), so limits hold across restarts and between processes
+//! sharing the store. A call over a limit waits for its token; if the wait
+//! would outlast the run's timeout it fails with [`RuntimeError::RateLimited`].
+
+use chrono::Utc;
+use namra_config::{parse_rate_limit, AgentConfig, RateLimit};
+use namra_storage::{RateBucket, SqliteStorage};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::{Result, RuntimeError};
+
+/// Kind of call a rate limit bucket counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    Llm,
+    Tool,
+}
+
+impl fmt::Display for CallKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallKind::Llm => write!(f, "llm"),
+            CallKind::Tool => write!(f, "tool"),
+        }
+    }
+}
+
+/// Token buckets a run's calls must take from
+pub struct RateLimiter {
+    /// Bucket scope (such as `agent:reporter`) and its limit
+    limits: Vec<(String, RateLimit)>,
+    store: Arc<Mutex<SqliteStorage>>,
+}
+
+impl RateLimiter {
+    /// Limiter keeping its buckets in `store`
+    pub fn new(store: Arc<Mutex<SqliteStorage>>) -> Self {
+        Self {
+            limits: Vec::new(),
+            store,
+        }
+    }
+
+    /// Add a limit shared by every limiter using `scope` on the same store
+    pub fn limit(mut self, scope: impl Into<String>, limit: RateLimit) -> Self {
+        self.limits.push((scope.into(), limit));
+        self
+    }
+
+    /// Limiter for the agent's configured limits, if it has any
+    ///
+    /// Without a run store the buckets are kept in memory.
+    pub(crate) fn from_config(
+        config: &AgentConfig,
+        store: Option<Arc<Mutex<SqliteStorage>>>,
+    ) -> Result<Option<Self>> {
+        let agent_limit = config
+            .middleware
+            .as_ref()
+            .and_then(|m| m.security.as_ref())
+            .filter(|s| s.enabled)
+            .and_then(|s| s.rate_limit.as_deref())
+            .map(parse_rate_limit)
+            .transpose()
+            .map_err(|e| RuntimeError::ConfigError(format!("{:#}", e)))?;
+
+        let tenant_limit = config.tenancy.as_ref().and_then(|tenancy| {
+            let quota = tenancy.resource_quota.as_ref()?;
+            Some((
+                tenancy.tenant_id.as_str(),
+                RateLimit::per_hour(quota.max_requests_per_hour),
+            ))
+        });
+
+        if agent_limit.is_none() && tenant_limit.is_none() {
+            return Ok(None);
+        }
+
+        let store = match store {
+            Some(store) => store,
+            None => Arc::new(Mutex::new(SqliteStorage::open_memory().map_err(|e| {
+                RuntimeError::Other(format!("Could not create rate limit store: {}", e))
+            })?)),
+        };
+
+        let mut limiter = Self::new(store);
+        if let Some(limit) = agent_limit {
+            limiter = limiter.limit(format!("agent:{}", config.name), limit);
+        }
+        if let Some((tenant, limit)) = tenant_limit {
+            limiter = limiter.limit(format!("tenant:{}", tenant), limit);
+        }
+        Ok(Some(limiter))
+    }
+
+    /// Take a token for a `kind` call from every bucket
+    ///
+    /// Tokens are taken from all buckets at once, so a call that is refused
+    /// by one limit doesn't use up the others. Waits while a bucket is empty,
+    /// failing instead if the total wait would exceed `max_wait`.
+    pub async fn acquire(&self, kind: CallKind, max_wait: Duration) -> Result<()> {
+        let buckets: Vec<RateBucket> = self
+            .limits
+            .iter()
+            .map(|(scope, limit)| {
+                let capacity = limit.requests as f64;
+                RateBucket {
+                    key: format!("{}:{}", scope, kind),
+                    capacity,
+                    refill_per_sec: capacity / limit.period.as_secs_f64(),
+                }
+            })
+            .collect();
+        let mut waited = Duration::ZERO;
+
+        while let Some((key, wait)) = self.take_tokens(&buckets)? {
+            if waited + wait > max_wait {
+                return Err(RuntimeError::RateLimited {
+                    key,
+                    retry_after: wait,
+                });
+            }
+            tracing::info!(
+                rate_limit.key = %key,
+                rate_limit.wait_ms = wait.as_millis() as u64,
+                "Rate limit reached, waiting"
+            );
+            tokio::time::sleep(wait).await;
+            waited += wait;
+        }
+        Ok(())
+    }
+
+    /// Key of the bucket to wait for and how long, if no token was taken
+    fn take_tokens(&self, buckets: &[RateBucket]) -> Result<Option<(String, Duration)>> {
+        let taken = self
+            .store
+            .lock()
+            .map_err(|_| RuntimeError::Other("Rate limit store lock poisoned".to_string()))?
+            .take_rate_tokens(buckets, Utc::now())
+            .map_err(|e| RuntimeError::Other(format!("Could not update rate limit: {}", e)))?;
+        Ok(taken.map(|(bucket, wait)| (bucket.key.clone(), wait)))
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_config;
+    use namra_config::{MiddlewareConfig, ResourceQuota, TenancyConfig};
+
+    #[test]
+    fn test_limits_from_config() {
+        let mut config = test_config();
+        assert!(RateLimiter::from_config(&config, None).unwrap().is_none());
+
+        config.middleware = Some(MiddlewareConfig {
+            security: Some(serde_yaml::from_str("{rate_limit: 100/minute}").unwrap()),
+            ..Default::default()
+        });
+        config.tenancy = Some(TenancyConfig {
+            tenant_id: "team-a".to_string(),
+            isolation_level: "shared".to_string(),
+            resource_quota: Some(ResourceQuota {
+                max_requests_per_hour: 500,
+                max_cost_per_hour: 10.0,
+            }),
+        });
+
+        let limiter = RateLimiter::from_config(&config, None).unwrap().unwrap();
+        let scopes: Vec<&str> = limiter.limits.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(scopes, vec!["agent:test", "tenant:team-a"]);
+        assert_eq!(limiter.limits[1].1, RateLimit::per_hour(500));
+    }
+
+    #[tokio::test]
+    async fn test_llm_and_tool_calls_are_limited_separately() {
+        let store = Arc::new(Mutex::new(SqliteStorage::open_memory().unwrap()));
+        let limit = RateLimit {
+            requests: 1,
+            period: Duration::from_secs(3600),
+        };
+        let limiter = RateLimiter::new(store.clone()).limit("agent:a", limit);
+
+        limiter
+            .acquire(CallKind::Llm, Duration::ZERO)
+            .await
+            .unwrap();
+        limiter
+            .acquire(CallKind::Tool, Duration::ZERO)
+            .await
+            .unwrap();
+        let err = limiter
+            .acquire(CallKind::Llm, Duration::from_secs(60))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RuntimeError::RateLimited { ref key, .. } if key == "agent:a:llm"));
+
+        // Another limiter on the same store shares the bucket
+        let other = RateLimiter::new(store).limit("agent:a", limit);
+        assert!(other.acquire(CallKind::Tool, Duration::ZERO).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refused_call_keeps_its_tokens() {
+        let store = Arc::new(Mutex::new(SqliteStorage::open_memory().unwrap()));
+        let limit = |requests| RateLimit {
+            requests,
+            period: Duration::from_secs(3600),
+        };
+        let tenant = RateLimiter::new(store.clone()).limit("tenant:t", limit(1));
+        let limiter = RateLimiter::new(store)
+            .limit("agent:a", limit(2))
+            .limit("tenant:t", limit(1));
+
+        tenant.acquire(CallKind::Llm, Duration::ZERO).await.unwrap();
+        for _ in 0..3 {
+            let err = limiter
+                .acquire(CallKind::Llm, Duration::ZERO)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, RuntimeError::RateLimited { ref key, .. } if key == "tenant:t:llm")
+            );
+        }
+
+        // The agent bucket still has both of its tokens
+        let agent = limiter.limits[0].clone();
+        let limiter = RateLimiter::new(limiter.store).limit(agent.0, agent.1);
+        for _ in 0..2 {
+            limiter
+                .acquire(CallKind::Llm, Duration::ZERO)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_next_token() {
+        let store = Arc::new(Mutex::new(SqliteStorage::open_memory().unwrap()));
+        let limiter = RateLimiter::new(store).limit(
+            "agent:a",
+            RateLimit {
+                requests: 20,
+                period: Duration::from_secs(1),
+            },
+        );
+
+        let start = std::time::Instant::now();
+        for _ in 0..21 {
+            limiter
+                .acquire(CallKind::Llm, Duration::from_secs(1))
+                .await
+                .unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,646 @@
+//! Circuit breaking and provider fallback from `middleware.resilience`
+//!
+//! [`ResilientLLM`] guards the agent's LLM with a circuit breaker and routes
+//! requests to a fallback adapter when the primary provider fails with a
+//! transient error or its circuit is open. [`ResilientTool`] guards each tool
+//! with its own breaker. Calls slower than `timeout`, or the breaker's
+//! `timeout` if that is shorter, are abandoned; slow guarded calls count as
+//! failures.
+//!
+//! Breakers are shared by every executor in the process, keyed by the
+//! dependency (provider, model and endpoint for the LLM; name for a tool) and
+//! the breaker settings, so a dependency that keeps failing stays open across
+//! runs instead of being retried by each new executor.
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use namra_config::{parse_duration, AgentConfig, ResilienceConfig};
+use namra_llm::adapter::{LLMAdapter, LLMError, LLMResult, LLMStream};
+use namra_llm::types::{LLMRequest, LLMResponse};
+use namra_middleware::resilience::{CircuitBreaker, CircuitState};
+use namra_tools::{Result as ToolResult, Tool, ToolError, ToolOutput};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+use crate::error::{Result, RuntimeError};
+use crate::llm_factory::LLMFactory;
+
+/// LLM adapter with a circuit breaker and an optional fallback provider
+pub struct ResilientLLM {
+    primary: Arc<dyn LLMAdapter>,
+    breaker: Option<Arc<CircuitBreaker>>,
+    call_timeout: Option<Duration>,
+    fallback: Option<(Arc<dyn LLMAdapter>, String)>,
+}
+
+impl ResilientLLM {
+    /// Wrap the primary adapter
+    pub fn new(primary: Arc<dyn LLMAdapter>) -> Self {
+        Self {
+            primary,
+            breaker: None,
+            call_timeout: None,
+            fallback: None,
+        }
+    }
+
+    /// Guard the primary adapter with a circuit breaker
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Give up on requests to either provider after `limit`
+    ///
+    /// Streams only have to start within the limit.
+    pub fn with_timeout(mut self, limit: Duration) -> Self {
+        self.call_timeout = Some(limit);
+        self
+    }
+
+    /// Send requests to `fallback` with `model` when the primary is unavailable
+    pub fn with_fallback(
+        mut self,
+        fallback: Arc<dyn LLMAdapter>,
+        model: impl Into<String>,
+    ) -> Self {
+        self.fallback = Some((fallback, model.into()));
+        self
+    }
+
+    /// State of the primary provider's circuit, if it has one
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.breaker.as_ref().map(|b| b.state())
+    }
+
+    /// Call the primary adapter through the circuit breaker
+    async fn call_primary<T, F, Fut>(&self, call: F) -> LLMResult<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = LLMResult<T>>,
+    {
+        let Some(breaker) = &self.breaker else {
+            return self.timed(call()).await;
+        };
+
+        let Some(permit) = breaker.try_acquire() else {
+            return Err(LLMError::CircuitOpen(breaker.name().to_string()));
+        };
+
+        let result = self.timed(call()).await;
+
+        // Only outages count against the provider; a rejected request
+        // still shows it is reachable
+        match &result {
+            Err(e) if e.is_retryable() => permit.failure(),
+            _ => permit.success(),
+        }
+
+        result
+    }
+
+    /// Run a provider call within the timeout
+    async fn timed<T>(&self, call: impl Future<Output = LLMResult<T>>) -> LLMResult<T> {
+        match self.call_timeout {
+            Some(limit) => tokio::time::timeout(limit, call).await.unwrap_or_else(|_| {
+                Err(LLMError::Timeout(format!("no response after {:?}", limit)))
+            }),
+            None => call.await,
+        }
+    }
+
+    /// The fallback to use after `error`, if any
+    fn fallback_for(&self, error: &LLMError) -> Option<&(Arc<dyn LLMAdapter>, String)> {
+        let unavailable = matches!(error, LLMError::CircuitOpen(_)) || error.is_retryable();
+        if !unavailable {
+            return None;
+        }
+
+        let fallback = self.fallback.as_ref()?;
+        tracing::warn!(
+            error = %error,
+            fallback.provider = fallback.0.provider_name(),
+            fallback.model = %fallback.1,
+            "Primary LLM unavailable, using fallback"
+        );
+        Some(fallback)
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for ResilientLLM {
+    fn provider_name(&self) -> &str {
+        self.primary.provider_name()
+    }
+
+    async fn generate(&self, request: LLMRequest) -> LLMResult<LLMResponse> {
+        let error = match self
+            .call_primary(|| self.primary.generate(request.clone()))
+            .await
+        {
+            Err(e) => e,
+            ok => return ok,
+        };
+
+        let Some((fallback, model)) = self.fallback_for(&error) else {
+            return Err(error);
+        };
+
+        let mut request = request;
+        request.model = model.clone();
+        let mut response = self.timed(fallback.generate(request)).await?;
+        response.metadata.insert(
+            "fallback".to_string(),
+            fallback_metadata(fallback, model, &error),
+        );
+        Ok(response)
+    }
+
+    async fn stream(&self, request: LLMRequest) -> LLMResult<LLMStream> {
+        let error = match self
+            .call_primary(|| self.primary.stream(request.clone()))
+            .await
+        {
+            Err(e) => e,
+            ok => return ok,
+        };
+
+        let Some((fallback, model)) = self.fallback_for(&error) else {
+            return Err(error);
+        };
+
+        let mut request = request;
+        request.model = model.clone();
+        let metadata = fallback_metadata(fallback, model, &error);
+        let stream = self
+            .timed(fallback.stream(request))
+            .await?
+            .map(move |chunk| {
+                chunk.map(|mut chunk| {
+                    chunk
+                        .metadata
+                        .insert("fallback".to_string(), metadata.clone());
+                    chunk
+                })
+            });
+        Ok(Box::pin(stream))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.primary.supports_streaming()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.primary.supports_tools()
+    }
+
+    fn max_context_tokens(&self, model: &str) -> Option<u32> {
+        self.primary.max_context_tokens(model)
+    }
+
+    /// Prices the fallback model with the fallback provider's rates
+    fn estimate_cost(&self, input_tokens: u32, output_tokens: u32, model: &str) -> Option<f64> {
+        let primary = || {
+            self.primary
+                .estimate_cost(input_tokens, output_tokens, model)
+        };
+        match &self.fallback {
+            Some((fallback, fallback_model)) if fallback_model == model => fallback
+                .estimate_cost(input_tokens, output_tokens, model)
+                .or_else(primary),
+            _ => primary(),
+        }
+    }
+}
+
+/// `fallback` metadata entry of a response served by the fallback provider
+fn fallback_metadata(fallback: &Arc<dyn LLMAdapter>, model: &str, error: &LLMError) -> Value {
+    serde_json::json!({
+        "provider": fallback.provider_name(),
+        "model": model,
+        "reason": error.to_string(),
+    })
+}
+
+/// Tool with a time limit and an optional circuit breaker
+pub struct ResilientTool {
+    inner: Arc<dyn Tool>,
+    breaker: Option<Arc<CircuitBreaker>>,
+    call_timeout: Option<Duration>,
+}
+
+impl ResilientTool {
+    /// Wrap a tool
+    pub fn new(inner: Arc<dyn Tool>) -> Self {
+        Self {
+            inner,
+            breaker: None,
+            call_timeout: None,
+        }
+    }
+
+    /// Guard the tool with a circuit breaker
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Give up on calls after `limit`
+    pub fn with_timeout(mut self, limit: Duration) -> Self {
+        self.call_timeout = Some(limit);
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for ResilientTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters(&self) -> Value {
+        self.inner.parameters()
+    }
+
+    async fn execute(&self, input: Value) -> ToolResult<ToolOutput> {
+        let permit = match &self.breaker {
+            Some(breaker) => match breaker.try_acquire() {
+                Some(permit) => Some(permit),
+                None => {
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "Circuit open for tool {}; it has failed repeatedly",
+                        self.inner.name()
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        let result = match self.call_timeout {
+            Some(limit) => tokio::time::timeout(limit, self.inner.execute(input))
+                .await
+                .unwrap_or(Err(ToolError::Timeout(limit.as_secs()))),
+            None => self.inner.execute(input).await,
+        };
+
+        // A tool that answered is healthy, even if it reported a failure;
+        // bad arguments from the model or a refused call say nothing about
+        // its health either
+        if let Some(permit) = permit {
+            match &result {
+                Ok(_) | Err(ToolError::InvalidInput(_) | ToolError::PermissionDenied(_)) => {
+                    permit.success()
+                }
+                Err(_) => permit.failure(),
+            }
+        }
+
+        result
+    }
+}
+
+/// Resilience settings, if enabled for the agent
+fn resilience_config(config: &AgentConfig) -> Option<&ResilienceConfig> {
+    config
+        .middleware
+        .as_ref()
+        .and_then(|m| m.resilience.as_ref())
+        .filter(|r| r.enabled)
+}
+
+/// Settings shared by the LLM and tool wrappers
+struct Settings {
+    /// Time limit for each call
+    call_timeout: Duration,
+    /// Failure threshold and half-open timeout of the breakers, if enabled
+    breaker: Option<(u32, Duration)>,
+}
+
+impl Settings {
+    fn new(resilience: &ResilienceConfig) -> Result<Self> {
+        let parse = |value: &str, field: &str| {
+            parse_duration(value)
+                .map_err(|e| RuntimeError::ConfigError(format!("Invalid {}: {}", field, e)))
+        };
+
+        let mut call_timeout = parse(&resilience.timeout, "resilience timeout")?;
+        let breaker = match resilience.circuit_breaker.as_ref().filter(|b| b.enabled) {
+            Some(breaker) => {
+                let timeout = parse(&breaker.timeout, "circuit breaker timeout")?;
+                call_timeout = call_timeout.min(timeout);
+                Some((
+                    breaker.failure_threshold,
+                    parse(
+                        &breaker.half_open_timeout,
+                        "circuit breaker half_open_timeout",
+                    )?,
+                ))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            call_timeout,
+            breaker,
+        })
+    }
+
+    /// The process-wide breaker named `name`, if breakers are enabled
+    fn breaker(&self, name: String) -> Option<Arc<CircuitBreaker>> {
+        type Key = (String, u32, Duration);
+        static BREAKERS: OnceLock<Mutex<HashMap<Key, Arc<CircuitBreaker>>>> = OnceLock::new();
+
+        let (threshold, half_open) = self.breaker?;
+        let mut breakers = BREAKERS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let breaker = breakers
+            .entry((name.clone(), threshold, half_open))
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(name, threshold, half_open)));
+        Some(breaker.clone())
+    }
+}
+
+/// Wrap the agent's LLM according to `middleware.resilience`
+///
+/// `fallback` overrides the adapter built from `fallback_llm`.
+pub(crate) fn wrap_llm(
+    config: &AgentConfig,
+    llm: Arc<dyn LLMAdapter>,
+    fallback: Option<Arc<dyn LLMAdapter>>,
+) -> Result<Arc<dyn LLMAdapter>> {
+    let Some(resilience) = resilience_config(config) else {
+        return Ok(llm);
+    };
+
+    let settings = Settings::new(resilience)?;
+    let mut resilient = ResilientLLM::new(llm.clone()).with_timeout(settings.call_timeout);
+
+    if let Some(breaker) = settings.breaker(llm_breaker_name(config, llm.as_ref())) {
+        resilient = resilient.with_circuit_breaker(breaker);
+    }
+
+    if resilience.fallback_strategy.as_deref() != Some("fail_fast") {
+        let fallback_config = resilience.fallback_llm.as_ref();
+        let model = fallback_config
+            .map(|c| c.model.clone())
+            .unwrap_or_else(|| config.llm.model.clone());
+
+        let fallback = match (fallback, fallback_config) {
+            (Some(adapter), _) => Some(adapter),
+            (None, Some(fallback_config)) => Some(LLMFactory::new().build(fallback_config)?),
+            (None, None) => None,
+        };
+
+        if let Some(fallback) = fallback {
+            resilient = resilient.with_fallback(fallback, model);
+        }
+    }
+
+    Ok(Arc::new(resilient))
+}
+
+/// Breaker name for the agent's LLM
+///
+/// A model or endpoint that fails doesn't open the circuit for agents using
+/// another one of the same provider.
+fn llm_breaker_name(config: &AgentConfig, llm: &dyn LLMAdapter) -> String {
+    let mut name = format!("llm:{}/{}", llm.provider_name(), config.llm.model);
+    if let Some(base_url) = &config.llm.base_url {
+        name.push_str(&format!(" at {}", base_url));
+    }
+    name
+}
+
+/// Give each tool the `middleware.resilience` timeout, and its own circuit
+/// breaker if one is enabled
+pub(crate) fn wrap_tools(
+    config: &AgentConfig,
+    tools: HashMap<String, Arc<dyn Tool>>,
+) -> Result<HashMap<String, Arc<dyn Tool>>> {
+    let Some(resilience) = resilience_config(config) else {
+        return Ok(tools);
+    };
+    let settings = Settings::new(resilience)?;
+
+    Ok(tools
+        .into_iter()
+        .map(|(name, tool)| {
+            let mut resilient = ResilientTool::new(tool).with_timeout(settings.call_timeout);
+            if let Some(breaker) = settings.breaker(format!("tool:{}", name)) {
+                resilient = resilient.with_circuit_breaker(breaker);
+            }
+            let tool: Arc<dyn Tool> = Arc::new(resilient);
+            (name, tool)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{test_config, text_response, ScriptedLLM};
+    use namra_llm::types::Message;
+
+    fn request() -> LLMRequest {
+        LLMRequest::new("primary-model", vec![Message::user("Hi")])
+    }
+
+    /// An LLM whose provider is down
+    fn outage() -> Arc<dyn LLMAdapter> {
+        Arc::new(
+            ScriptedLLM::new(vec![]).failing_with(|| LLMError::ApiError {
+                status: 529,
+                message: "overloaded".to_string(),
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_and_opens_circuit() {
+        let fallback = Arc::new(ScriptedLLM::new(vec![
+            text_response("from fallback"),
+            text_response("still fallback"),
+        ]));
+        let breaker = Arc::new(CircuitBreaker::new("llm", 1, Duration::from_secs(60)));
+
+        let llm = ResilientLLM::new(outage())
+            .with_circuit_breaker(breaker)
+            .with_fallback(fallback.clone(), "backup-model");
+
+        let response = llm.generate(request()).await.unwrap();
+        assert_eq!(response.content, "from fallback");
+        assert_eq!(response.metadata["fallback"]["model"], "backup-model");
+        assert_eq!(llm.circuit_state(), Some(CircuitState::Open));
+
+        // With the circuit open the primary is skipped entirely
+        let response = llm.generate(request()).await.unwrap();
+        assert!(response.metadata["fallback"]["reason"]
+            .as_str()
+            .unwrap()
+            .contains("Circuit open"));
+        assert_eq!(fallback.requests()[1].model, "backup-model");
+    }
+
+    #[tokio::test]
+    async fn test_streamed_fallback_is_tagged_and_priced() {
+        let fallback = ScriptedLLM::new(vec![text_response("from fallback")]).with_cost(0.5);
+        let llm = ResilientLLM::new(outage()).with_fallback(Arc::new(fallback), "backup-model");
+
+        let chunks: Vec<_> = llm.stream(request()).await.unwrap().collect().await;
+        assert!(chunks.iter().all(|chunk| {
+            chunk.as_ref().unwrap().metadata["fallback"]["model"] == "backup-model"
+        }));
+
+        assert_eq!(llm.estimate_cost(10, 10, "backup-model"), Some(0.5));
+        assert_eq!(llm.estimate_cost(10, 10, "primary-model"), None);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_without_fallback_fails() {
+        let breaker = Arc::new(CircuitBreaker::new("llm", 1, Duration::from_secs(60)));
+        let llm = ResilientLLM::new(outage()).with_circuit_breaker(breaker);
+
+        assert!(matches!(
+            llm.generate(request()).await,
+            Err(LLMError::ApiError { status: 529, .. })
+        ));
+        assert!(matches!(
+            llm.generate(request()).await,
+            Err(LLMError::CircuitOpen(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tool_circuit_opens_after_failures() {
+        use crate::testing::SlowTool;
+
+        let breaker = Arc::new(CircuitBreaker::new("tool:slow", 2, Duration::from_secs(60)));
+        let tool = ResilientTool::new(Arc::new(SlowTool::default()))
+            .with_circuit_breaker(breaker)
+            .with_timeout(Duration::from_millis(5));
+
+        // Calls slower than the timeout count as failures
+        let slow = serde_json::json!({"id": "a", "delay_ms": 50});
+        for _ in 0..2 {
+            assert!(matches!(
+                tool.execute(slow.clone()).await,
+                Err(ToolError::Timeout(_))
+            ));
+        }
+
+        let err = tool.execute(slow).await.err().unwrap();
+        assert!(err.to_string().contains("Circuit open"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_tool_output_keeps_circuit_closed() {
+        use crate::testing::SlowTool;
+
+        let breaker = Arc::new(CircuitBreaker::new("tool:slow", 1, Duration::from_secs(60)));
+        let tool =
+            ResilientTool::new(Arc::new(SlowTool::default())).with_circuit_breaker(breaker.clone());
+
+        let failed = serde_json::json!({"id": "a", "delay_ms": 0, "fail": true});
+        for _ in 0..3 {
+            assert!(!tool.execute(failed.clone()).await.unwrap().success);
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_wraps_only_when_enabled() {
+        use namra_config::{CircuitBreakerConfig, MiddlewareConfig};
+
+        let mut config = test_config();
+        let tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        assert!(wrap_tools(&config, tools).unwrap().is_empty());
+
+        config.middleware = Some(MiddlewareConfig {
+            observability: None,
+            security: None,
+            governance: None,
+            resilience: Some(ResilienceConfig {
+                enabled: true,
+                circuit_breaker: Some(CircuitBreakerConfig {
+                    half_open_timeout: "soon".to_string(),
+                    ..Default::default()
+                }),
+                timeout: "30s".to_string(),
+                fallback_strategy: None,
+                fallback_llm: None,
+            }),
+            caching: None,
+        });
+
+        let llm: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![]));
+        assert!(wrap_llm(&config, llm, None).is_err());
+    }
+
+    #[test]
+    fn test_llm_breakers_are_per_model_and_endpoint() {
+        let settings = Settings {
+            call_timeout: Duration::from_secs(30),
+            breaker: Some((3, Duration::from_secs(60))),
+        };
+        let llm = ScriptedLLM::new(vec![]);
+        let mut config = test_config();
+        let breaker = |config: &AgentConfig| settings.breaker(llm_breaker_name(config, &llm));
+
+        let first = breaker(&config).unwrap();
+        assert!(Arc::ptr_eq(&first, &breaker(&config).unwrap()));
+
+        config.llm.base_url = Some("http://localhost:8080/v1".to_string());
+        let local = breaker(&config).unwrap();
+        assert!(!Arc::ptr_eq(&first, &local));
+
+        config.llm.model = "other-model".to_string();
+        assert!(!Arc::ptr_eq(&local, &breaker(&config).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_executors_share_breakers_and_timeout() {
+        use crate::testing::SlowTool;
+        use namra_config::MiddlewareConfig;
+
+        let mut config = test_config();
+        let resilience = |breaker: &str| {
+            Some(MiddlewareConfig {
+                resilience: Some(
+                    serde_yaml::from_str(&format!("{{enabled: true, timeout: 5ms, {}}}", breaker))
+                        .unwrap(),
+                ),
+                ..Default::default()
+            })
+        };
+        let tools = || {
+            let tool: Arc<dyn Tool> = Arc::new(SlowTool::default());
+            HashMap::from([("shared_slow".to_string(), tool)])
+        };
+        let slow = serde_json::json!({"id": "a", "delay_ms": 50});
+
+        // The timeout applies without a circuit breaker
+        config.middleware = resilience("circuit_breaker: {enabled: false}");
+        let wrapped = wrap_tools(&config, tools()).unwrap();
+        assert!(matches!(
+            wrapped["shared_slow"].execute(slow.clone()).await,
+            Err(ToolError::Timeout(_))
+        ));
+
+        // A circuit opened through one executor's tools stays open for the next
+        config.middleware = resilience("circuit_breaker: {failure_threshold: 1}");
+        let first = wrap_tools(&config, tools()).unwrap();
+        assert!(first["shared_slow"].execute(slow.clone()).await.is_err());
+        let second = wrap_tools(&config, tools()).unwrap();
+        let err = second["shared_slow"].execute(slow).await.err().unwrap();
+        assert!(err.to_string().contains("Circuit open"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Chain-of-thought strategy
+//!
+//! The model is asked to reason through the problem in explicit numbered steps
+//! before committing to an answer on a final `ANSWER:` line. Each step is
+//! recorded as a thought. Tools are offered through native tool calling; any
+//! tool results are fed back and the model continues its reasoning.
+
+use async_trait::async_trait;
+use namra_config::AgentConfig;
+use namra_llm::adapter::LLMAdapter;
+use namra_llm::types::{Message, MessageRole};
+use namra_tools::Tool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::context::ExecutionContext;
+use crate::error::Result;
+use crate::strategy::common::{
+    build_request, call_llm, check_limits, execute_tools, extract_answer, summarize_history,
+    tool_concurrency, tool_result_message,
+};
+use crate::strategy::{tool_definitions, Strategy};
+
+/// Instruction appended to the system prompt
+const COT_INSTRUCTION: &str = "Think through the problem step by step before answering. \
+Write each step on its own line as 'Step N: ...'. When you are done reasoning, \
+give the final answer on a last line starting with 'ANSWER:'.";
+
+/// Chain-of-thought strategy implementation
+pub struct ChainOfThoughtStrategy;
+
+impl ChainOfThoughtStrategy {
+    /// Create a new chain-of-thought strategy
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Add the step-by-step instruction to the system prompt
+    ///
+    /// Skipped when already present, e.g. for a resumed run.
+    fn add_instruction(&self, messages: &mut Vec<Message>) {
+        match messages.iter_mut().find(|m| m.role == MessageRole::System) {
+            Some(system) if system.content.contains(COT_INSTRUCTION) => {}
+            Some(system) => {
+                system.content = format!("{}\n\n{}", system.content, COT_INSTRUCTION);
+            }
+            None => messages.insert(0, Message::system(COT_INSTRUCTION)),
+        }
+    }
+
+    /// Split a response into its reasoning steps
+    ///
+    /// Falls back to the whole text (minus the answer line) when the model
+    /// didn't number its steps.
+    fn extract_steps(&self, response: &str) -> Vec<String> {
+        let reasoning = match response.rfind("ANSWER:") {
+            Some(pos) => &response[..pos],
+            None => response,
+        };
+
+        let mut steps: Vec<String> = Vec::new();
+        for line in reasoning.lines() {
+            let line = line.trim();
+            if line.starts_with("Step ") && line.contains(':') {
+                steps.push(line.to_string());
+            } else if let Some(step) = steps.last_mut().filter(|_| !line.is_empty()) {
+                // Continuation of a multi-line step
+                step.push('\n');
+                step.push_str(line);
+            }
+        }
+
+        if steps.is_empty() && !reasoning.trim().is_empty() {
+            steps.push(reasoning.trim().to_string());
+        }
+
+        steps
+    }
+}
+
+impl Default for ChainOfThoughtStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Strategy for ChainOfThoughtStrategy {
+    async fn execute(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        tools: &HashMap<String, Arc<dyn Tool>>,
+        context: &mut ExecutionContext,
+    ) -> Result<String> {
+        self.add_instruction(&mut context.messages);
+
+        let tool_definitions = if llm.supports_tools() && !tools.is_empty() {
+            Some(tool_definitions(tools))
+        } else {
+            None
+        };
+
+        loop {
+            check_limits(context)?;
+            context.increment_iteration();
+
+            summarize_history(config, llm, context).await?;
+            let request = build_request(config, context.messages.clone(), tool_definitions.clone());
+            let response = call_llm(config, llm, request, context).await?;
+
+            for step in self.extract_steps(&response.content) {
+                context.record_thought(step);
+            }
+
+            context.add_message(response.to_message());
+
+            // Feed tool results back and let the model continue reasoning
+            let tool_calls = response.tool_calls.clone().unwrap_or_default();
+            if !tool_calls.is_empty() {
+                let calls = tool_calls
+                    .iter()
+                    .map(|call| (call.name.clone(), call.arguments.clone()))
+                    .collect();
+                let outputs =
+                    execute_tools(config, tools, calls, tool_concurrency(config), context).await?;
+
+                for (call, output) in tool_calls.into_iter().zip(outputs) {
+                    context.add_message(tool_result_message(output, call.id));
+                }
+                continue;
+            }
+
+            return Ok(extract_answer(&response.content));
+        }
+    }
+
+    fn name(&self) -> &str {
+        "chain_of_thought"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{test_config, text_response, ScriptedLLM};
+    use std::time::Duration;
+
+    #[test]
+    fn test_extract_steps() {
+        let strategy = ChainOfThoughtStrategy::new();
+
+        let steps = strategy
+            .extract_steps("Step 1: 17 * 3 = 51\nStep 2: 51 + 4 = 55\nwhich is odd\nANSWER: 55");
+        assert_eq!(
+            steps,
+            vec!["Step 1: 17 * 3 = 51", "Step 2: 51 + 4 = 55\nwhich is odd"]
+        );
+
+        let steps = strategy.extract_steps("It is obviously 4.\nANSWER: 4");
+        assert_eq!(steps, vec!["It is obviously 4."]);
+
+        assert!(strategy.extract_steps("ANSWER: 4").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reasons_then_answers() {
+        let llm = Arc::new(ScriptedLLM::new(vec![text_response(
+            "Step 1: There are 3 boxes of 4 apples.\nStep 2: 3 * 4 = 12\nANSWER: 12 apples",
+        )]));
+        let adapter: Arc<dyn LLMAdapter> = llm.clone();
+
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        context.add_message(Message::system("You are good at math."));
+        context.add_message(Message::user("How many apples?"));
+
+        let answer = ChainOfThoughtStrategy::new()
+            .execute(&test_config(), &adapter, &HashMap::new(), &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "12 apples");
+        assert_eq!(context.thoughts.len(), 2);
+        assert_eq!(context.iteration, 1);
+
+        // The instruction was merged into the existing system prompt
+        let system = &llm.requests()[0].messages[0];
+        assert!(system.content.starts_with("You are good at math."));
+        assert!(system.content.contains("step by step"));
+    }
+}
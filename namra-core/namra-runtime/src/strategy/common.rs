@@ -0,0 +1,797 @@
+//! Building blocks shared by the execution strategies
+//!
+//! LLM calls (streaming or not), usage accounting, content capture, and tool
+//! execution behave the same whichever strategy drives the run.
+
+use futures::StreamExt;
+use namra_config::AgentConfig;
+use namra_llm::adapter::LLMAdapter;
+use namra_llm::types::{
+    FinishReason, LLMRequest, LLMResponse, Message, MessageRole, ToolDefinition,
+};
+use namra_llm::StreamAccumulator;
+use namra_memory::ConversationMemory;
+use namra_middleware::audit::{redact, AuditEvent};
+use namra_middleware::observability::{
+    llm_request_span, record_llm_metrics, record_llm_prompts, record_llm_response,
+    record_tool_input, record_tool_output, record_tool_result, tool_execution_span,
+};
+use namra_middleware::security::GuardStage;
+use namra_tools::{Tool, ToolOutput};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tracing::Instrument;
+
+use crate::approval::ApprovalDecision;
+use crate::context::{ExecutionContext, StreamEvent, ToolCallRecord};
+use crate::error::{Result, RuntimeError};
+use crate::rate_limit::CallKind;
+
+/// Content capture settings from the observability config
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ContentCapture {
+    pub enabled: bool,
+    pub max_size: usize,
+}
+
+impl ContentCapture {
+    pub fn from_config(config: &AgentConfig) -> Self {
+        let (enabled, max_size) = config
+            .middleware
+            .as_ref()
+            .and_then(|m| m.observability.as_ref())
+            .map(|obs| (obs.capture_content, obs.max_content_size))
+            .unwrap_or((false, 4000));
+
+        Self { enabled, max_size }
+    }
+}
+
+/// Fail if the run has hit its iteration limit or timed out
+pub(crate) fn check_limits(context: &ExecutionContext) -> Result<()> {
+    if context.is_max_iterations_reached() {
+        return Err(RuntimeError::MaxIterationsReached(context.max_iterations));
+    }
+
+    if context.is_timed_out() {
+        return Err(RuntimeError::Timeout(context.timeout.as_secs()));
+    }
+
+    Ok(())
+}
+
+/// Build an LLM request from the agent's LLM settings
+pub(crate) fn build_request(
+    config: &AgentConfig,
+    messages: Vec<Message>,
+    tools: Option<Vec<ToolDefinition>>,
+) -> LLMRequest {
+    LLMRequest {
+        messages,
+        model: config.llm.model.clone(),
+        temperature: Some(config.llm.temperature),
+        max_tokens: Some(config.llm.max_tokens),
+        top_p: config.llm.top_p,
+        stream: false,
+        tools,
+        stop_sequences: (!config.execution.stop_sequences.is_empty())
+            .then(|| config.execution.stop_sequences.clone()),
+        extra: HashMap::new(),
+    }
+}
+
+/// Prompt sent after a response was cut off at the max_tokens limit
+const CONTINUE_PROMPT: &str =
+    "Your previous response was cut off. Continue exactly where it stopped, without repeating anything.";
+
+/// Call the LLM and account for the response in the context
+///
+/// Streams when the context has an event receiver, records prompt and response
+/// content on the span if capture is enabled, and adds token usage and cost.
+///
+/// Responses that stop at the max_tokens limit are continued with follow-up
+/// requests, up to `execution.max_continuations`, and the parts joined. If the
+/// text is still incomplete, or a tool call was cut off, the call fails with
+/// [`RuntimeError::Truncated`] so the partial text is never taken as an answer.
+pub(crate) async fn call_llm(
+    config: &AgentConfig,
+    llm: &Arc<dyn LLMAdapter>,
+    request: LLMRequest,
+    context: &mut ExecutionContext,
+) -> Result<LLMResponse> {
+    let capture = ContentCapture::from_config(config);
+    let prompts = capture
+        .enabled
+        .then(|| format_messages_for_span(&request.messages));
+
+    let mut response = generate(llm, request.clone(), context).await?;
+    add_usage(config, llm, &response, context)?;
+
+    let mut continuations = 0;
+    while response.finish_reason == FinishReason::Length {
+        let cut_off_tool_call = response.tool_calls.as_ref().is_some_and(|c| !c.is_empty());
+        if cut_off_tool_call || continuations >= config.execution.max_continuations {
+            return Err(RuntimeError::Truncated {
+                partial: response.content,
+            });
+        }
+        if context.is_timed_out() {
+            return Err(RuntimeError::Timeout(context.timeout.as_secs()));
+        }
+        continuations += 1;
+
+        let mut follow_up = request.clone();
+        follow_up
+            .messages
+            .push(Message::assistant(response.content.clone()));
+        follow_up.messages.push(Message::user(CONTINUE_PROMPT));
+
+        let next = generate(llm, follow_up, context).await?;
+        add_usage(config, llm, &next, context)?;
+
+        response.content.push_str(&next.content);
+        response.tool_calls = next.tool_calls;
+        response.finish_reason = next.finish_reason;
+        response.usage.input_tokens += next.usage.input_tokens;
+        response.usage.output_tokens += next.usage.output_tokens;
+        response.usage.total_tokens += next.usage.total_tokens;
+        response.usage.cost = match (response.usage.cost, next.usage.cost) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+    }
+
+    if continuations > 0 {
+        let total = context
+            .metadata
+            .get("continuations")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        context.metadata.insert(
+            "continuations".to_string(),
+            Value::from(total + continuations as u64),
+        );
+    }
+
+    // Record LLM prompts/response content if capture is enabled
+    if let Some(prompts) = prompts {
+        let current_span = tracing::Span::current();
+        record_llm_prompts(&current_span, &prompts, capture.max_size);
+        record_llm_response(&current_span, &response.content, capture.max_size);
+    }
+
+    Ok(response)
+}
+
+/// Summarize the older part of the run's conversation once it grows past
+/// the limits from `memory.summarization`
+///
+/// The summary replaces those messages in the context, and its usage counts
+/// towards the run like any other LLM call.
+pub(crate) async fn summarize_history(
+    config: &AgentConfig,
+    llm: &Arc<dyn LLMAdapter>,
+    context: &mut ExecutionContext,
+) -> Result<()> {
+    let Some(summarizer) = context.summarizer.clone() else {
+        return Ok(());
+    };
+    if !summarizer.should_summarize(&context.messages) {
+        return Ok(());
+    }
+
+    context.acquire_rate(CallKind::Llm).await?;
+    let mut messages = context.messages.clone();
+    if let Some(response) = summarizer.compact(&mut messages).await? {
+        context.messages = messages;
+        add_summary_usage(config, llm, &response, context)?;
+    }
+    Ok(())
+}
+
+/// Summarize a long conversation session kept in memory, like
+/// [`summarize_history`] does for the run's own messages
+pub(crate) async fn summarize_session(
+    config: &AgentConfig,
+    llm: &Arc<dyn LLMAdapter>,
+    memory: &ConversationMemory,
+    session: &str,
+    context: &mut ExecutionContext,
+) -> Result<()> {
+    if !memory.needs_summary(session).await? {
+        return Ok(());
+    }
+
+    context.acquire_rate(CallKind::Llm).await?;
+    if let Some(response) = memory.summarize(session).await? {
+        add_summary_usage(config, llm, &response, context)?;
+    }
+    Ok(())
+}
+
+/// Account for a summarizing response, counted in the `summarizations`
+/// metadata entry
+fn add_summary_usage(
+    config: &AgentConfig,
+    llm: &Arc<dyn LLMAdapter>,
+    response: &LLMResponse,
+    context: &mut ExecutionContext,
+) -> Result<()> {
+    let total = context
+        .metadata
+        .get("summarizations")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    context
+        .metadata
+        .insert("summarizations".to_string(), Value::from(total + 1));
+    add_usage(config, llm, response, context)
+}
+
+/// Model of the fallback provider that served a response, if one did
+pub(crate) fn fallback_model(response: &LLMResponse) -> Option<&str> {
+    response
+        .metadata
+        .get("fallback")
+        .and_then(|f| f.get("model"))
+        .and_then(Value::as_str)
+}
+
+/// Add a response's token usage and estimated cost to the context
+///
+/// Responses served by a fallback provider are priced at the fallback model
+/// and counted in the `llm_fallbacks` metadata entry; responses served from
+/// the response cache are counted in `cache_hits`. Fails once the run's cost
+/// budget is reached.
+fn add_usage(
+    config: &AgentConfig,
+    llm: &Arc<dyn LLMAdapter>,
+    response: &LLMResponse,
+    context: &mut ExecutionContext,
+) -> Result<()> {
+    context.add_tokens(response.usage.clone());
+
+    let fallback_model = fallback_model(response);
+    if fallback_model.is_some() {
+        let total = context
+            .metadata
+            .get("llm_fallbacks")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        context
+            .metadata
+            .insert("llm_fallbacks".to_string(), Value::from(total + 1));
+    }
+    if response.metadata.contains_key("cache") {
+        let total = context
+            .metadata
+            .get("cache_hits")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        context
+            .metadata
+            .insert("cache_hits".to_string(), Value::from(total + 1));
+    }
+
+    let cost = response
+        .usage
+        .cost
+        .or_else(|| {
+            llm.estimate_cost(
+                response.usage.input_tokens,
+                response.usage.output_tokens,
+                fallback_model.unwrap_or(&config.llm.model),
+            )
+        })
+        .unwrap_or(0.0);
+    context.add_cost(cost);
+
+    context.audit(AuditEvent::LlmCall {
+        provider: llm.provider_name().to_string(),
+        model: fallback_model.unwrap_or(&config.llm.model).to_string(),
+        input_tokens: response.usage.input_tokens,
+        output_tokens: response.usage.output_tokens,
+        cost,
+    });
+
+    context.check_budget()
+}
+
+/// Generate a response, streaming it when the context has an event receiver
+///
+/// Streamed text is emitted as [`StreamEvent::Token`]s and the chunks are folded
+/// back into a complete response, so callers handle both paths the same way.
+/// Tokens are held back when a security guard checks the response, since they
+/// would reach the receiver before the guard could redact or reject them.
+async fn generate(
+    llm: &Arc<dyn LLMAdapter>,
+    mut request: LLMRequest,
+    context: &ExecutionContext,
+) -> Result<LLMResponse> {
+    context.acquire_rate(CallKind::Llm).await?;
+
+    if !context.is_streaming() || !llm.supports_streaming() {
+        request.stream = false;
+        return Ok(llm.generate(request).await?);
+    }
+
+    request.stream = true;
+    let span = llm_request_span(llm.provider_name(), &request.model);
+
+    async move {
+        let mut stream = llm.stream(request).await?;
+        let mut accumulator = StreamAccumulator::new();
+        let emit_tokens = !context
+            .guards
+            .as_ref()
+            .is_some_and(|g| g.checks(GuardStage::Response));
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            if emit_tokens && !chunk.content.is_empty() {
+                context.emit(StreamEvent::Token(chunk.content.clone()));
+            }
+            accumulator.push(&chunk);
+        }
+
+        let response = accumulator.finish();
+
+        let current_span = tracing::Span::current();
+        record_llm_metrics(
+            &current_span,
+            response.usage.input_tokens,
+            response.usage.output_tokens,
+            response.usage.cost.unwrap_or(0.0),
+        );
+        context.emit(StreamEvent::Usage(response.usage.clone()));
+
+        Ok(response)
+    }
+    .instrument(span)
+    .await
+}
+
+/// Number of tool calls from one turn that may run at once
+///
+/// One unless `execution.parallel_tool_calls` is enabled, in which case
+/// `execution.max_parallel_tool_calls` caps the concurrency.
+pub(crate) fn tool_concurrency(config: &AgentConfig) -> usize {
+    if config.execution.parallel_tool_calls {
+        config.execution.max_parallel_tool_calls.max(1) as usize
+    } else {
+        1
+    }
+}
+
+/// Execute a single tool call and record it in the context
+///
+/// Unknown tools and tool errors are returned as failed outputs so they can
+/// be fed back to the model instead of aborting the run.
+pub(crate) async fn execute_tool(
+    config: &AgentConfig,
+    tools: &HashMap<String, Arc<dyn Tool>>,
+    tool_name: &str,
+    tool_input: Value,
+    context: &mut ExecutionContext,
+) -> Result<ToolOutput> {
+    let calls = vec![(tool_name.to_string(), tool_input)];
+    let mut outputs = execute_tools(config, tools, calls, 1, context).await?;
+    Ok(outputs.remove(0))
+}
+
+/// Execute several tool calls, at most `max_concurrency` at a time
+///
+/// Outputs are returned and recorded in the order of `calls`, regardless of
+/// which call finishes first. Outputs of calls that ran pass through the
+/// run's security guards. Calls that need approval are reviewed before
+/// any call of the turn runs: denied calls come back as failed outputs, and
+/// if a decision is pending nothing runs and the run pauses with
+/// [`RuntimeError::ApprovalPending`].
+pub(crate) async fn execute_tools(
+    config: &AgentConfig,
+    tools: &HashMap<String, Arc<dyn Tool>>,
+    calls: Vec<(String, Value)>,
+    max_concurrency: usize,
+    context: &mut ExecutionContext,
+) -> Result<Vec<ToolOutput>> {
+    let denials = review_tool_calls(&calls, context).await?;
+    let ran: Vec<(String, bool)> = calls
+        .iter()
+        .zip(&denials)
+        .map(|((name, _), denial)| (name.clone(), denial.is_none()))
+        .collect();
+
+    let shared: &ExecutionContext = context;
+    let results: Vec<(ToolOutput, ToolCallRecord)> =
+        futures::stream::iter(calls.into_iter().zip(denials))
+            .map(|((name, input), denial)| async move {
+                match denial {
+                    Some(reason) => denied_tool_call(&name, input, reason, shared),
+                    None => run_tool(config, tools, &name, input, shared).await,
+                }
+            })
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await;
+
+    // Check what the tools returned before the model sees it
+    results
+        .into_iter()
+        .zip(ran)
+        .map(|((mut output, record), (name, ran))| {
+            context.record_tool_call(record);
+            if ran {
+                output.content =
+                    context.guard(GuardStage::ToolOutput, Some(&name), output.content)?;
+            }
+            Ok(output)
+        })
+        .collect()
+}
+
+/// Review the calls that need approval, returning the denial for each call
+async fn review_tool_calls(
+    calls: &[(String, Value)],
+    context: &ExecutionContext,
+) -> Result<Vec<Option<String>>> {
+    let mut denials = vec![None; calls.len()];
+    let Some(gate) = &context.approvals else {
+        return Ok(denials);
+    };
+
+    let mut pending = Vec::new();
+    for ((name, input), denial) in calls.iter().zip(denials.iter_mut()) {
+        if !gate.requires(name) {
+            continue;
+        }
+        let decision = gate.review(&context.id, name, input).await?;
+        let (label, reason) = match &decision {
+            ApprovalDecision::Approved => ("approved", None),
+            ApprovalDecision::Denied(reason) => ("denied", reason.clone()),
+            ApprovalDecision::Pending(_) => ("pending", None),
+        };
+        context.audit(AuditEvent::ApprovalDecision {
+            tool: name.clone(),
+            decision: label.to_string(),
+            reason,
+        });
+
+        match decision {
+            ApprovalDecision::Approved => {}
+            ApprovalDecision::Denied(reason) => {
+                *denial = Some(reason.unwrap_or_else(|| "no reason given".to_string()));
+            }
+            ApprovalDecision::Pending(id) => pending.push(id),
+        }
+    }
+
+    if !pending.is_empty() {
+        return Err(RuntimeError::ApprovalPending { approvals: pending });
+    }
+    Ok(denials)
+}
+
+/// Failed output for a call the approver denied, without running the tool
+fn denied_tool_call(
+    tool_name: &str,
+    tool_input: Value,
+    reason: String,
+    context: &ExecutionContext,
+) -> (ToolOutput, ToolCallRecord) {
+    let output = ToolOutput::failure(
+        format!(
+            "Error: Tool call was denied by the approver ({}). Do not retry it; continue without it.",
+            reason
+        ),
+        0,
+    );
+
+    context.emit(StreamEvent::ToolResult {
+        name: tool_name.to_string(),
+        success: false,
+    });
+
+    context.audit(AuditEvent::ToolCall {
+        tool: tool_name.to_string(),
+        input: redact(&tool_input),
+        success: false,
+        execution_time_ms: 0,
+    });
+
+    let record = ToolCallRecord {
+        tool_name: tool_name.to_string(),
+        input: tool_input,
+        output: Some(output.content.clone()),
+        success: false,
+        execution_time_ms: 0,
+        timestamp: SystemTime::now(),
+    };
+
+    (output, record)
+}
+
+/// Feed back results for tool calls a paused run stopped on
+///
+/// When the last message requested tools that have no results yet, the calls
+/// are reviewed again (now with the recorded decisions) and executed.
+pub(crate) async fn finish_pending_tool_calls(
+    config: &AgentConfig,
+    tools: &HashMap<String, Arc<dyn Tool>>,
+    context: &mut ExecutionContext,
+) -> Result<()> {
+    let pending = match context.messages.last() {
+        Some(message) if message.role == MessageRole::Assistant => {
+            message.tool_calls.clone().unwrap_or_default()
+        }
+        _ => return Ok(()),
+    };
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let calls = pending
+        .iter()
+        .map(|call| (call.name.clone(), call.arguments.clone()))
+        .collect();
+    let outputs = execute_tools(config, tools, calls, tool_concurrency(config), context).await?;
+
+    for (call, output) in pending.into_iter().zip(outputs) {
+        context.add_message(tool_result_message(output, call.id));
+    }
+    Ok(())
+}
+
+/// Run a tool call in its own span without recording it in the context
+async fn run_tool(
+    config: &AgentConfig,
+    tools: &HashMap<String, Arc<dyn Tool>>,
+    tool_name: &str,
+    tool_input: Value,
+    context: &ExecutionContext,
+) -> (ToolOutput, ToolCallRecord) {
+    let capture = ContentCapture::from_config(config);
+    let tool_start = SystemTime::now();
+    let span = tool_execution_span(tool_name);
+
+    context.emit(StreamEvent::ToolCall {
+        name: tool_name.to_string(),
+        arguments: tool_input.clone(),
+    });
+
+    // Record tool input if capture is enabled
+    if capture.enabled {
+        let input_str = serde_json::to_string(&tool_input).unwrap_or_default();
+        record_tool_input(&span, &input_str, capture.max_size);
+    }
+
+    let tool_result = match tools.get(tool_name) {
+        Some(tool) => {
+            async {
+                context.acquire_rate(CallKind::Tool).await?;
+                tool.execute(tool_input.clone())
+                    .await
+                    .map_err(RuntimeError::from)
+            }
+            .instrument(span.clone())
+            .await
+        }
+        None => Err(RuntimeError::ToolNotFound(tool_name.to_string())),
+    };
+
+    let tool_time = tool_start.elapsed().unwrap_or_default().as_millis() as u64;
+    let output =
+        tool_result.unwrap_or_else(|e| ToolOutput::failure(format!("Error: {}", e), tool_time));
+
+    // Record tool execution metrics on span
+    record_tool_result(&span, output.success, tool_time);
+
+    // Record tool output if capture is enabled
+    if capture.enabled {
+        record_tool_output(&span, &output.content, capture.max_size);
+    }
+
+    context.emit(StreamEvent::ToolResult {
+        name: tool_name.to_string(),
+        success: output.success,
+    });
+
+    context.audit(AuditEvent::ToolCall {
+        tool: tool_name.to_string(),
+        input: redact(&tool_input),
+        success: output.success,
+        execution_time_ms: tool_time,
+    });
+
+    let record = ToolCallRecord {
+        tool_name: tool_name.to_string(),
+        input: tool_input,
+        output: Some(output.content.clone()),
+        success: output.success,
+        execution_time_ms: tool_time,
+        timestamp: tool_start,
+    };
+
+    (output, record)
+}
+
+/// Tool result message linked to the originating tool call
+pub(crate) fn tool_result_message(output: ToolOutput, tool_call_id: String) -> Message {
+    if output.success {
+        Message::tool(output.content, tool_call_id)
+    } else {
+        Message::tool_error(output.content, tool_call_id)
+    }
+}
+
+/// Extract the text after the last `ANSWER:` marker, or the whole response
+pub(crate) fn extract_answer(response: &str) -> String {
+    match response.rfind("ANSWER:") {
+        Some(pos) => response[pos + 7..].trim().to_string(),
+        None => response.trim().to_string(),
+    }
+}
+
+/// Parse a JSON value from an LLM response, tolerating code fences and prose
+pub(crate) fn parse_json_response<T: serde::de::DeserializeOwned>(response: &str) -> Option<T> {
+    let start = response.find(['{', '['])?;
+    let end = response.rfind(['}', ']'])?;
+    if end < start {
+        return None;
+    }
+    serde_json::from_str(&response[start..=end]).ok()
+}
+
+/// Format messages for span attribute (compact representation)
+fn format_messages_for_span(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .map(|m| {
+            let role = match m.role {
+                MessageRole::System => "system",
+                MessageRole::User => "user",
+                MessageRole::Assistant => "assistant",
+                MessageRole::Tool => "tool",
+            };
+            format!("[{}]: {}", role, m.content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n---\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_check_limits() {
+        let mut context = ExecutionContext::new(1, Duration::from_secs(60));
+        assert!(check_limits(&context).is_ok());
+
+        context.increment_iteration();
+        assert!(matches!(
+            check_limits(&context),
+            Err(RuntimeError::MaxIterationsReached(1))
+        ));
+    }
+
+    #[test]
+    fn test_parse_json_response() {
+        let value: Value =
+            parse_json_response("Here you go:\n```json\n{\"steps\": [1, 2]}\n```").unwrap();
+        assert_eq!(value["steps"][1], 2);
+
+        assert!(parse_json_response::<Value>("no json here").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_tools_preserves_order() {
+        use crate::testing::test_config;
+        use namra_tools::{CalculatorTool, StringTool};
+
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("calculator".to_string(), Arc::new(CalculatorTool::new()));
+        tools.insert("string".to_string(), Arc::new(StringTool::new()));
+
+        let calls = vec![
+            (
+                "string".to_string(),
+                serde_json::json!({"operation": "uppercase", "text": "a"}),
+            ),
+            ("missing".to_string(), serde_json::json!({})),
+            (
+                "calculator".to_string(),
+                serde_json::json!({"expression": "2 + 3"}),
+            ),
+        ];
+
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        let outputs = execute_tools(&test_config(), &tools, calls, 3, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(outputs[0].content, "A");
+        assert!(!outputs[1].success);
+        assert_eq!(outputs[2].content, "2 + 3 = 5");
+
+        let names: Vec<&str> = context
+            .tool_calls
+            .iter()
+            .map(|r| r.tool_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["string", "missing", "calculator"]);
+    }
+
+    #[test]
+    fn test_build_request_passes_stop_sequences() {
+        use crate::testing::test_config;
+
+        let mut config = test_config();
+        assert!(build_request(&config, vec![], None)
+            .stop_sequences
+            .is_none());
+
+        config.execution.stop_sequences = vec!["Observation:".to_string()];
+        let request = build_request(&config, vec![], None);
+        assert_eq!(request.stop_sequences.unwrap(), vec!["Observation:"]);
+    }
+
+    #[tokio::test]
+    async fn test_call_llm_continues_truncated_response() {
+        use crate::testing::{test_config, text_response, truncated_response, ScriptedLLM};
+
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            truncated_response("The answer"),
+            truncated_response(" is"),
+            text_response(" 42."),
+        ]));
+        let adapter: Arc<dyn LLMAdapter> = llm.clone();
+
+        let config = test_config();
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        let request = build_request(&config, vec![Message::user("question")], None);
+        let response = call_llm(&config, &adapter, request, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "The answer is 42.");
+        assert_eq!(response.finish_reason, FinishReason::Stop);
+        assert_eq!(response.usage.total_tokens, 45);
+        assert_eq!(context.total_tokens(), 45);
+        assert_eq!(context.metadata["continuations"], 2);
+
+        // Follow-ups carry the text so far and ask the model to go on
+        let requests = llm.requests();
+        let follow_up = &requests[2].messages;
+        assert_eq!(follow_up[1].content, "The answer is");
+        assert_eq!(follow_up[2].content, CONTINUE_PROMPT);
+    }
+
+    #[tokio::test]
+    async fn test_call_llm_fails_when_still_truncated() {
+        use crate::testing::{test_config, truncated_response, ScriptedLLM};
+
+        let adapter: Arc<dyn LLMAdapter> =
+            Arc::new(ScriptedLLM::new(vec![truncated_response("The answer")]));
+
+        let mut config = test_config();
+        config.execution.max_continuations = 0;
+
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        let request = build_request(&config, vec![Message::user("question")], None);
+        let result = call_llm(&config, &adapter, request, &mut context).await;
+
+        assert!(matches!(
+            result,
+            Err(RuntimeError::Truncated { partial }) if partial == "The answer"
+        ));
+    }
+
+    #[test]
+    fn test_extract_answer() {
+        assert_eq!(extract_answer("Step 1: think\nANSWER: 42"), "42");
+        assert_eq!(extract_answer("  plain  "), "plain");
+    }
+}
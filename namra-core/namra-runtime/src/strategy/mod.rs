@@ -1,10 +1,16 @@
 //! Execution strategies
 
+pub mod chain_of_thought;
+pub(crate) mod common;
+pub mod plan_execute;
 pub mod react;
+pub mod registry;
+pub mod tree_of_thoughts;
 
 use async_trait::async_trait;
 use namra_config::AgentConfig;
 use namra_llm::adapter::LLMAdapter;
+use namra_llm::types::ToolDefinition;
 use namra_tools::Tool;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -36,3 +42,40 @@ pub trait Strategy: Send + Sync {
     /// Strategy name
     fn name(&self) -> &str;
 }
+
+/// Build tool definitions for the LLM from the registered tools
+///
+/// Tools are exposed under their registry key (which is the configured name)
+/// and sorted so requests are deterministic.
+pub(crate) fn tool_definitions(tools: &HashMap<String, Arc<dyn Tool>>) -> Vec<ToolDefinition> {
+    let mut definitions: Vec<ToolDefinition> = tools
+        .iter()
+        .map(|(name, tool)| ToolDefinition {
+            name: name.clone(),
+            description: tool.description().to_string(),
+            input_schema: tool.parameters(),
+        })
+        .collect();
+    definitions.sort_by(|a, b| a.name.cmp(&b.name));
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namra_tools::{CalculatorTool, StringTool};
+
+    #[test]
+    fn test_tool_definitions_use_registry_names() {
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("string".to_string(), Arc::new(StringTool::new()));
+        tools.insert("math".to_string(), Arc::new(CalculatorTool::new()));
+
+        let definitions = tool_definitions(&tools);
+
+        assert_eq!(definitions.len(), 2);
+        assert_eq!(definitions[0].name, "math");
+        assert_eq!(definitions[1].name, "string");
+        assert_eq!(definitions[0].input_schema["required"][0], "expression");
+    }
+}
@@ -0,0 +1,661 @@
+//! Plan-and-execute strategy
+//!
+//! Instead of deciding one action at a time, the agent commits to a plan up front:
+//! 1. PLAN: Ask the LLM for a JSON step list; steps may name a tool and its arguments.
+//!    Plans that repeat a step id or depend on unknown steps are rejected
+//! 2. EXECUTE: Run steps whose dependencies are done, calling the tool or asking the
+//!    LLM to carry out reasoning steps. With `parallel_steps`, all ready steps run at once,
+//!    their tool calls concurrently if `execution.parallel_tool_calls` is enabled
+//! 3. REVISE: When a step fails, ask the LLM to re-plan the remaining work
+//!    (up to `max_revisions` times); a revised plan may not reuse the ids of
+//!    steps that already ran
+//! 4. ANSWER: Combine the step results into the final answer
+//!
+//! The plan, each revision, and every step result are stored under the `plan`
+//! key of the execution context metadata.
+
+use async_trait::async_trait;
+use namra_config::{AgentConfig, PlanExecuteConfig};
+use namra_llm::adapter::LLMAdapter;
+use namra_llm::types::Message;
+use namra_tools::Tool;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::context::ExecutionContext;
+use crate::error::{Result, RuntimeError};
+use crate::strategy::common::{
+    build_request, call_llm, check_limits, execute_tools, extract_answer, parse_json_response,
+    tool_concurrency,
+};
+use crate::strategy::{tool_definitions, Strategy};
+
+/// A single step of a plan
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlanStep {
+    /// Step identifier, referenced by `depends_on`
+    pub id: u32,
+
+    /// What the step should accomplish
+    pub description: String,
+
+    /// Tool to call, or `None` for a reasoning step handled by the LLM
+    #[serde(default)]
+    pub tool: Option<String>,
+
+    /// Tool arguments
+    #[serde(default)]
+    pub arguments: Value,
+
+    /// Steps that must finish before this one starts
+    #[serde(default)]
+    pub depends_on: Vec<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PlanDocument {
+    Object { steps: Vec<PlanStep> },
+    Steps(Vec<PlanStep>),
+}
+
+/// Outcome of an executed step
+#[derive(Debug, Clone, Serialize)]
+struct StepResult {
+    step: u32,
+    success: bool,
+    output: String,
+}
+
+/// A version of the plan and why it was made
+#[derive(Debug, Clone, Serialize)]
+struct PlanRevision {
+    revision: u32,
+    reason: Option<String>,
+    steps: Vec<PlanStep>,
+}
+
+/// Plan history stored in the context metadata
+#[derive(Debug, Default, Serialize)]
+struct PlanRecord {
+    revisions: Vec<PlanRevision>,
+    results: Vec<StepResult>,
+}
+
+impl PlanRecord {
+    fn render_results(&self) -> String {
+        if self.results.is_empty() {
+            return "(no steps completed)".to_string();
+        }
+        self.results
+            .iter()
+            .map(|r| {
+                let status = if r.success { "ok" } else { "failed" };
+                format!("Step {} ({}): {}", r.step, status, r.output)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Plan-and-execute strategy implementation
+pub struct PlanExecuteStrategy {
+    settings: PlanExecuteConfig,
+}
+
+impl PlanExecuteStrategy {
+    /// Create a new plan-and-execute strategy
+    pub fn new(settings: PlanExecuteConfig) -> Self {
+        Self { settings }
+    }
+
+    /// Ask the LLM for a plan, given extra instructions
+    ///
+    /// Steps may depend on each other and on the `completed` steps of earlier
+    /// plans.
+    async fn request_plan(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        tools: &HashMap<String, Arc<dyn Tool>>,
+        context: &mut ExecutionContext,
+        instructions: String,
+        completed: &HashSet<u32>,
+    ) -> Result<Vec<PlanStep>> {
+        let tool_list: Vec<Value> = tool_definitions(tools)
+            .into_iter()
+            .map(|t| {
+                serde_json::json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "parameters": t.input_schema,
+                })
+            })
+            .collect();
+
+        let prompt = format!(
+            "{}\n\nRespond with JSON only, in this format:\n\
+{{\"steps\": [{{\"id\": 1, \"description\": \"...\", \"tool\": \"tool name or null\", \"arguments\": {{}}, \"depends_on\": []}}]}}\n\
+Use a tool only when a step needs one, with arguments matching its parameters. \
+Steps without a tool are reasoning steps you will carry out yourself. \
+List in depends_on the ids of steps whose results a step needs.\n\nAvailable tools:\n{}",
+            instructions,
+            serde_json::to_string_pretty(&tool_list).unwrap_or_default()
+        );
+
+        let mut messages = context.messages.clone();
+        messages.push(Message::user(prompt));
+
+        let response =
+            call_llm(config, llm, build_request(config, messages, None), context).await?;
+
+        let steps = match parse_json_response::<PlanDocument>(&response.content) {
+            Some(PlanDocument::Object { steps }) | Some(PlanDocument::Steps(steps)) => steps,
+            None => {
+                return Err(RuntimeError::PlanFailed(
+                    "LLM did not return a valid plan".to_string(),
+                ))
+            }
+        };
+
+        if steps.is_empty() {
+            return Err(RuntimeError::PlanFailed(
+                "LLM returned an empty plan".to_string(),
+            ));
+        }
+
+        let mut ids = HashSet::new();
+        if let Some(step) = steps.iter().find(|s| !ids.insert(s.id)) {
+            return Err(RuntimeError::PlanFailed(format!(
+                "plan has more than one step {}",
+                step.id
+            )));
+        }
+        for step in &steps {
+            let unknown: Vec<u32> = step
+                .depends_on
+                .iter()
+                .copied()
+                .filter(|d| !ids.contains(d) && !completed.contains(d))
+                .collect();
+            if !unknown.is_empty() {
+                return Err(RuntimeError::PlanFailed(format!(
+                    "step {} depends on unknown steps {:?}",
+                    step.id, unknown
+                )));
+            }
+        }
+
+        Ok(steps)
+    }
+
+    /// Carry out a reasoning step with the LLM
+    async fn run_reasoning_step(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        context: &mut ExecutionContext,
+        record: &PlanRecord,
+        step: &PlanStep,
+    ) -> Result<StepResult> {
+        let prompt = format!(
+            "Results so far:\n{}\n\nCarry out this step of the plan: {}\nReply with the result of the step only.",
+            record.render_results(),
+            step.description
+        );
+
+        let mut messages = context.messages.clone();
+        messages.push(Message::user(prompt));
+
+        let response =
+            call_llm(config, llm, build_request(config, messages, None), context).await?;
+
+        Ok(StepResult {
+            step: step.id,
+            success: true,
+            output: response.content.trim().to_string(),
+        })
+    }
+
+    /// Run a batch of ready steps, tool steps concurrently when
+    /// `execution.parallel_tool_calls` allows
+    async fn run_steps(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        tools: &HashMap<String, Arc<dyn Tool>>,
+        context: &mut ExecutionContext,
+        record: &PlanRecord,
+        batch: &[PlanStep],
+    ) -> Result<Vec<StepResult>> {
+        let tool_steps: Vec<&PlanStep> = batch.iter().filter(|s| s.tool.is_some()).collect();
+        let calls: Vec<(String, Value)> = tool_steps
+            .iter()
+            .map(|s| {
+                let arguments = match &s.arguments {
+                    Value::Null => serde_json::json!({}),
+                    arguments => arguments.clone(),
+                };
+                (s.tool.clone().unwrap_or_default(), arguments)
+            })
+            .collect();
+
+        let outputs =
+            execute_tools(config, tools, calls, tool_concurrency(config), context).await?;
+
+        let mut results: HashMap<u32, StepResult> = tool_steps
+            .iter()
+            .zip(outputs)
+            .map(|(step, output)| {
+                (
+                    step.id,
+                    StepResult {
+                        step: step.id,
+                        success: output.success,
+                        output: output.content,
+                    },
+                )
+            })
+            .collect();
+
+        for step in batch.iter().filter(|s| s.tool.is_none()) {
+            let result = self
+                .run_reasoning_step(config, llm, context, record, step)
+                .await?;
+            results.insert(step.id, result);
+        }
+
+        // Keep results in plan order
+        Ok(batch.iter().filter_map(|s| results.remove(&s.id)).collect())
+    }
+
+    fn save(&self, record: &PlanRecord, context: &mut ExecutionContext) {
+        context.metadata.insert(
+            "plan".to_string(),
+            serde_json::to_value(record).unwrap_or(Value::Null),
+        );
+    }
+
+    fn record_plan(&self, context: &mut ExecutionContext, revision: &PlanRevision) {
+        let steps = revision
+            .steps
+            .iter()
+            .map(|s| match &s.tool {
+                Some(tool) => format!("{}. {} [{}]", s.id, s.description, tool),
+                None => format!("{}. {}", s.id, s.description),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let heading = match &revision.reason {
+            Some(reason) => format!("Revised plan ({}):", reason),
+            None => "Plan:".to_string(),
+        };
+        context.record_thought(format!("{}\n{}", heading, steps));
+    }
+}
+
+impl Default for PlanExecuteStrategy {
+    fn default() -> Self {
+        Self::new(PlanExecuteConfig::default())
+    }
+}
+
+#[async_trait]
+impl Strategy for PlanExecuteStrategy {
+    async fn execute(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        tools: &HashMap<String, Arc<dyn Tool>>,
+        context: &mut ExecutionContext,
+    ) -> Result<String> {
+        let mut record = PlanRecord::default();
+
+        // PLAN
+        check_limits(context)?;
+        context.increment_iteration();
+        let mut pending = self
+            .request_plan(
+                config,
+                llm,
+                tools,
+                context,
+                "Before acting, write a step-by-step plan for the request above.".to_string(),
+                &HashSet::new(),
+            )
+            .await?;
+
+        let revision = PlanRevision {
+            revision: 0,
+            reason: None,
+            steps: pending.clone(),
+        };
+        self.record_plan(context, &revision);
+        record.revisions.push(revision);
+        self.save(&record, context);
+
+        while !pending.is_empty() {
+            check_limits(context)?;
+            context.increment_iteration();
+
+            // EXECUTE steps whose dependencies are no longer pending
+            let pending_ids: HashSet<u32> = pending.iter().map(|s| s.id).collect();
+            let ready: Vec<PlanStep> = pending
+                .iter()
+                .filter(|s| s.depends_on.iter().all(|d| !pending_ids.contains(d)))
+                .cloned()
+                .collect();
+
+            let failure = if ready.is_empty() {
+                Some(format!(
+                    "steps {:?} have circular dependencies",
+                    pending_ids.iter().collect::<Vec<_>>()
+                ))
+            } else {
+                let batch = if self.settings.parallel_steps {
+                    ready
+                } else {
+                    ready[..1].to_vec()
+                };
+
+                let results = self
+                    .run_steps(config, llm, tools, context, &record, &batch)
+                    .await?;
+                pending.retain(|s| !batch.iter().any(|b| b.id == s.id));
+
+                let failure = results
+                    .iter()
+                    .find(|r| !r.success)
+                    .map(|r| format!("step {} failed: {}", r.step, r.output));
+                record.results.extend(results);
+                failure
+            };
+            self.save(&record, context);
+
+            // REVISE the remaining work after a failure
+            if let Some(reason) = failure {
+                let revisions = record.revisions.len() as u32 - 1;
+                if revisions >= self.settings.max_revisions {
+                    return Err(RuntimeError::PlanFailed(reason));
+                }
+
+                check_limits(context)?;
+                context.increment_iteration();
+
+                let next_id = record
+                    .revisions
+                    .iter()
+                    .flat_map(|r| r.steps.iter().map(|s| s.id))
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                pending = self
+                    .request_plan(
+                        config,
+                        llm,
+                        tools,
+                        context,
+                        format!(
+                            "The plan for the request above ran into a problem: {}\n\nResults so far:\n{}\n\n\
+Write a revised plan for the remaining work. Number new steps from {}; depends_on may reference completed steps.",
+                            reason,
+                            record.render_results(),
+                            next_id
+                        ),
+                        &record.results.iter().map(|r| r.step).collect(),
+                    )
+                    .await?;
+
+                let reused: Vec<u32> = pending
+                    .iter()
+                    .map(|s| s.id)
+                    .filter(|id| record.results.iter().any(|r| r.step == *id))
+                    .collect();
+                if !reused.is_empty() {
+                    return Err(RuntimeError::PlanFailed(format!(
+                        "revised plan reuses the ids of completed steps {:?}",
+                        reused
+                    )));
+                }
+
+                let revision = PlanRevision {
+                    revision: revisions + 1,
+                    reason: Some(reason),
+                    steps: pending.clone(),
+                };
+                self.record_plan(context, &revision);
+                record.revisions.push(revision);
+                self.save(&record, context);
+            }
+        }
+
+        // ANSWER from the step results
+        check_limits(context)?;
+        context.increment_iteration();
+
+        let prompt = format!(
+            "Step results:\n{}\n\nUsing these results, answer the request above. \
+Give the final answer on a line starting with 'ANSWER:'.",
+            record.render_results()
+        );
+        let mut messages = context.messages.clone();
+        messages.push(Message::user(prompt));
+
+        let response =
+            call_llm(config, llm, build_request(config, messages, None), context).await?;
+
+        Ok(extract_answer(&response.content))
+    }
+
+    fn name(&self) -> &str {
+        "plan_execute"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{test_config, text_response, ScriptedLLM, SlowTool};
+    use namra_tools::{CalculatorTool, StringTool};
+    use std::time::Duration;
+
+    fn test_tools() -> HashMap<String, Arc<dyn Tool>> {
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("calculator".to_string(), Arc::new(CalculatorTool::new()));
+        tools.insert("string".to_string(), Arc::new(StringTool::new()));
+        tools
+    }
+
+    fn test_context() -> ExecutionContext {
+        let mut context = ExecutionContext::new(10, Duration::from_secs(30));
+        context.add_message(Message::user("Do the thing"));
+        context
+    }
+
+    #[tokio::test]
+    async fn test_executes_plan_in_dependency_order() {
+        let plan = serde_json::json!({"steps": [
+            {"id": 1, "description": "Add", "tool": "calculator", "arguments": {"expression": "2 + 3"}},
+            {"id": 2, "description": "Shout", "tool": "string", "arguments": {"operation": "uppercase", "text": "hi"}},
+            {"id": 3, "description": "Combine", "tool": null, "depends_on": [1, 2]}
+        ]});
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            text_response(&format!("```json\n{}\n```", plan)),
+            text_response("5 and HI"),
+            text_response("ANSWER: 5 and HI"),
+        ]));
+        let adapter: Arc<dyn LLMAdapter> = llm.clone();
+
+        let strategy = PlanExecuteStrategy::new(PlanExecuteConfig {
+            parallel_steps: true,
+            max_revisions: 0,
+        });
+
+        let mut context = test_context();
+        let answer = strategy
+            .execute(&test_config(), &adapter, &test_tools(), &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "5 and HI");
+        // Plan, one wave with both tool steps, the reasoning step, the answer
+        assert_eq!(context.iteration, 4);
+        assert_eq!(context.tool_calls[0].tool_name, "calculator");
+        assert_eq!(context.tool_calls[1].tool_name, "string");
+
+        // The reasoning step saw both tool results
+        let requests = llm.requests();
+        let step_prompt = &requests[1].messages.last().unwrap().content;
+        assert!(step_prompt.contains("Step 2 (ok): HI"));
+
+        let stored = &context.metadata["plan"];
+        assert_eq!(stored["revisions"].as_array().unwrap().len(), 1);
+        assert_eq!(stored["results"][2]["output"], "5 and HI");
+    }
+
+    #[tokio::test]
+    async fn test_revises_plan_after_failed_step() {
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            text_response(
+                r#"{"steps": [{"id": 1, "description": "Search", "tool": "search", "arguments": {}}]}"#,
+            ),
+            text_response(
+                r#"[{"id": 2, "description": "Compute", "tool": "calculator", "arguments": {"expression": "6 * 7"}}]"#,
+            ),
+            text_response("ANSWER: 42"),
+        ]));
+        let adapter: Arc<dyn LLMAdapter> = llm.clone();
+
+        let mut context = test_context();
+        let answer = PlanExecuteStrategy::default()
+            .execute(&test_config(), &adapter, &test_tools(), &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "42");
+
+        let revisions = context.metadata["plan"]["revisions"].as_array().unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert!(revisions[1]["reason"]
+            .as_str()
+            .unwrap()
+            .contains("Tool not found: search"));
+        assert!(context.thoughts[1].starts_with("Revised plan"));
+    }
+
+    #[tokio::test]
+    async fn test_revised_plan_cannot_reuse_step_ids() {
+        let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![
+            text_response(
+                r#"{"steps": [
+                    {"id": 1, "description": "Add", "tool": "calculator", "arguments": {"expression": "1 + 1"}},
+                    {"id": 2, "description": "Search", "tool": "search", "depends_on": [1]}
+                ]}"#,
+            ),
+            text_response(r#"[{"id": 1, "description": "Think", "tool": null}]"#),
+        ]));
+
+        let mut context = test_context();
+        let result = PlanExecuteStrategy::default()
+            .execute(&test_config(), &adapter, &test_tools(), &mut context)
+            .await;
+
+        assert!(
+            matches!(result, Err(RuntimeError::PlanFailed(ref reason)) if reason.contains("[1]"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_duplicate_step_ids() {
+        let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![text_response(
+            r#"{"steps": [
+                {"id": 1, "description": "Add", "tool": "calculator", "arguments": {"expression": "1 + 1"}},
+                {"id": 1, "description": "Think", "tool": null}
+            ]}"#,
+        )]));
+
+        let mut context = test_context();
+        let result = PlanExecuteStrategy::default()
+            .execute(&test_config(), &adapter, &test_tools(), &mut context)
+            .await;
+
+        assert!(
+            matches!(result, Err(RuntimeError::PlanFailed(ref reason)) if reason.contains("more than one step 1"))
+        );
+        assert!(context.tool_calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_dependencies_on_unknown_steps() {
+        let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![text_response(
+            r#"{"steps": [
+                {"id": 1, "description": "Add", "tool": "calculator", "arguments": {"expression": "1 + 1"}},
+                {"id": 2, "description": "Think", "tool": null, "depends_on": [1, 7]}
+            ]}"#,
+        )]));
+
+        let mut context = test_context();
+        let result = PlanExecuteStrategy::default()
+            .execute(&test_config(), &adapter, &test_tools(), &mut context)
+            .await;
+
+        assert!(
+            matches!(result, Err(RuntimeError::PlanFailed(ref reason)) if reason.contains("step 2 depends on unknown steps [7]"))
+        );
+        assert!(context.tool_calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tool_steps_follow_parallel_tool_calls() {
+        let plan = serde_json::json!({"steps": [
+            {"id": 1, "description": "A", "tool": "slow", "arguments": {"id": "a", "delay_ms": 40}},
+            {"id": 2, "description": "B", "tool": "slow", "arguments": {"id": "b", "delay_ms": 40}}
+        ]});
+
+        for (parallel, peak) in [(false, 1), (true, 2)] {
+            let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![
+                text_response(&plan.to_string()),
+                text_response("ANSWER: done"),
+            ]));
+            let slow = Arc::new(SlowTool::default());
+            let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+            tools.insert("slow".to_string(), slow.clone());
+
+            let mut config = test_config();
+            config.execution.parallel_tool_calls = parallel;
+            let strategy = PlanExecuteStrategy::new(PlanExecuteConfig {
+                parallel_steps: true,
+                max_revisions: 0,
+            });
+            strategy
+                .execute(&config, &adapter, &tools, &mut test_context())
+                .await
+                .unwrap();
+            assert_eq!(slow.peak(), peak);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fails_when_revisions_exhausted() {
+        let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![text_response(
+            r#"{"steps": [{"id": 1, "description": "Search", "tool": "search"}]}"#,
+        )]));
+
+        let strategy = PlanExecuteStrategy::new(PlanExecuteConfig {
+            parallel_steps: false,
+            max_revisions: 0,
+        });
+
+        let mut context = test_context();
+        let result = strategy
+            .execute(&test_config(), &adapter, &test_tools(), &mut context)
+            .await;
+
+        assert!(matches!(result, Err(RuntimeError::PlanFailed(_))));
+        // The failed plan is still recorded for the run history
+        assert_eq!(context.metadata["plan"]["results"][0]["success"], false);
+    }
+}
@@ -6,24 +6,32 @@
 //! 3. OBSERVE: Get tool result and feed back to LLM
 //!
 //! This continues until the agent provides a final answer or reaches limits.
+//!
+//! Tools are offered to the model through the adapter's native tool-use API and
+//! the structured `tool_calls` in each response drive the ACT phase. Adapters
+//! that don't support tool calling fall back to the `TOOL: name(args)` text
+//! protocol.
+//!
+//! When the model requests several tools in one turn they run concurrently if
+//! `execution.parallel_tool_calls` is enabled (at most
+//! `execution.max_parallel_tool_calls` at a time); results are always fed back
+//! in the order the calls were made.
 
 use async_trait::async_trait;
 use namra_config::AgentConfig;
 use namra_llm::adapter::LLMAdapter;
-use namra_llm::types::{LLMRequest, Message};
-use namra_middleware::observability::{
-    record_llm_prompts, record_llm_response, record_tool_input, record_tool_output,
-    record_tool_result, tool_execution_span,
-};
+use namra_llm::types::Message;
 use namra_tools::Tool;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::SystemTime;
-use tracing::Instrument;
 
-use crate::context::{ExecutionContext, ToolCallRecord};
+use crate::context::ExecutionContext;
 use crate::error::{Result, RuntimeError};
-use crate::strategy::Strategy;
+use crate::strategy::common::{
+    build_request, call_llm, check_limits, execute_tool, execute_tools, extract_answer,
+    summarize_history, tool_concurrency, tool_result_message,
+};
+use crate::strategy::{tool_definitions, Strategy};
 
 /// ReAct strategy implementation
 pub struct ReActStrategy;
@@ -59,16 +67,6 @@ impl ReActStrategy {
         // Final answer if it contains "ANSWER:" or doesn't request a tool
         response.contains("ANSWER:") || !response.contains("TOOL:")
     }
-
-    /// Extract final answer from response
-    fn extract_answer(&self, response: &str) -> String {
-        if let Some(answer_pos) = response.find("ANSWER:") {
-            response[answer_pos + 7..].trim().to_string()
-        } else {
-            // Return the whole response if no ANSWER: prefix
-            response.trim().to_string()
-        }
-    }
 }
 
 impl Default for ReActStrategy {
@@ -86,78 +84,66 @@ impl Strategy for ReActStrategy {
         tools: &HashMap<String, Arc<dyn Tool>>,
         context: &mut ExecutionContext,
     ) -> Result<String> {
-        // Get content capture settings from observability config
-        let (capture_content, max_content_size) = config
-            .middleware
-            .as_ref()
-            .and_then(|m| m.observability.as_ref())
-            .map(|obs| (obs.capture_content, obs.max_content_size))
-            .unwrap_or((false, 4000));
+        // Offer tools through the native tool-use API when the adapter supports it
+        let native_tools = llm.supports_tools();
+        let tool_definitions = if native_tools && !tools.is_empty() {
+            Some(tool_definitions(tools))
+        } else {
+            None
+        };
 
         // Main ReAct loop
         loop {
-            // Check iteration limit
-            if context.is_max_iterations_reached() {
-                return Err(RuntimeError::MaxIterationsReached(context.max_iterations));
-            }
-
-            // Check timeout
-            if context.is_timed_out() {
-                return Err(RuntimeError::Timeout(context.timeout.as_secs()));
-            }
+            // Check iteration limit and timeout
+            check_limits(context)?;
 
             // Increment iteration
             context.increment_iteration();
 
+            // Keep the conversation within the summarization limits
+            summarize_history(config, llm, context).await?;
+
             // Build LLM request with current conversation
-            let request = LLMRequest {
-                messages: context.messages.clone(),
-                model: config.llm.model.clone(),
-                temperature: Some(config.llm.temperature),
-                max_tokens: Some(config.llm.max_tokens),
-                top_p: config.llm.top_p,
-                stream: false,
-                tools: None,
-                stop_sequences: None,
-                extra: HashMap::new(),
-            };
+            let request = build_request(config, context.messages.clone(), tool_definitions.clone());
 
             // Call LLM (THINK phase)
-            let response = llm.generate(request.clone()).await?;
-
-            // Record LLM prompts/response content if capture is enabled
-            if capture_content {
-                let current_span = tracing::Span::current();
-                let prompts_str = format_messages_for_span(&request.messages);
-                record_llm_prompts(&current_span, &prompts_str, max_content_size);
-                record_llm_response(&current_span, &response.content, max_content_size);
-            }
-
-            // Track tokens and cost
-            context.add_tokens(response.usage.clone());
-            let cost = llm
-                .estimate_cost(
-                    response.usage.input_tokens,
-                    response.usage.output_tokens,
-                    &config.llm.model,
-                )
-                .unwrap_or(0.0);
-            context.add_cost(cost);
+            let response = call_llm(config, llm, request, context).await?;
 
             // Record the thought/reasoning
-            context.record_thought(response.content.clone());
+            if !response.content.is_empty() {
+                context.record_thought(response.content.clone());
+            }
 
-            // Add assistant response to context
-            context.add_message(Message::assistant(response.content.clone()));
+            // Add assistant response (including any tool calls) to context
+            context.add_message(response.to_message());
+
+            // Structured tool calls from the adapter (ACT phase)
+            let tool_calls = response.tool_calls.clone().unwrap_or_default();
+            if !tool_calls.is_empty() {
+                let calls = tool_calls
+                    .iter()
+                    .map(|call| (call.name.clone(), call.arguments.clone()))
+                    .collect();
+                let outputs =
+                    execute_tools(config, tools, calls, tool_concurrency(config), context).await?;
+
+                for (call, output) in tool_calls.into_iter().zip(outputs) {
+                    // Feed the result back linked to the originating tool call
+                    context.add_message(tool_result_message(output, call.id));
+                }
 
-            // Try to extract tool call first (ACT phase)
-            // If there's a tool call, execute it even if there's also an ANSWER
-            if let Some((tool_name, argument)) = self.extract_tool_call(&response.content) {
-                // Find the tool
-                let tool = tools
-                    .get(&tool_name)
-                    .ok_or_else(|| RuntimeError::ToolNotFound(tool_name.clone()))?;
+                // Continue loop to let agent reason about the results
+                continue;
+            }
 
+            // Fall back to the text protocol for adapters without tool calling
+            // If there's a tool call, execute it even if there's also an ANSWER
+            let text_tool_call = if native_tools {
+                None
+            } else {
+                self.extract_tool_call(&response.content)
+            };
+            if let Some((tool_name, argument)) = text_tool_call {
                 // Parse argument as JSON
                 let tool_input = if argument.starts_with('{') {
                     // Already JSON
@@ -173,53 +159,20 @@ impl Strategy for ReActStrategy {
                     })
                 };
 
-                // Execute tool (OBSERVE phase) with tracing
-                let tool_start = SystemTime::now();
-                let span = tool_execution_span(&tool_name);
-
-                // Record tool input if capture is enabled
-                if capture_content {
-                    let input_str = serde_json::to_string(&tool_input).unwrap_or_default();
-                    record_tool_input(&span, &input_str, max_content_size);
-                }
-
-                let tool_result = async { tool.execute(tool_input.clone()).await }
-                    .instrument(span.clone())
-                    .await?;
-
-                let tool_time = tool_start.elapsed().unwrap_or_default().as_millis() as u64;
-
-                // Record tool execution metrics on span
-                record_tool_result(&span, tool_result.success, tool_time);
-
-                // Record tool output if capture is enabled
-                if capture_content {
-                    record_tool_output(&span, &tool_result.content, max_content_size);
-                }
-
-                // Record tool call in context
-                context.record_tool_call(ToolCallRecord {
-                    tool_name: tool_name.clone(),
-                    input: tool_input,
-                    output: Some(tool_result.content.clone()),
-                    success: tool_result.success,
-                    execution_time_ms: tool_time,
-                    timestamp: tool_start,
-                });
+                let output = execute_tool(config, tools, &tool_name, tool_input, context).await?;
 
                 // Add tool result as a user message so LLM can observe it
-                let observation =
-                    format!("Tool Result from {}: {}", tool_name, tool_result.content);
+                let observation = format!("Tool Result from {}: {}", tool_name, output.content);
                 context.add_message(Message::user(observation));
 
                 // Continue loop to let agent reason about the result
                 continue;
             }
 
-            // No tool call found, check if this is a final answer
-            if self.is_final_answer(&response.content) {
-                let answer = self.extract_answer(&response.content);
-                return Ok(answer);
+            // No tool call found, check if this is a final answer; with native
+            // tool calling any text-only response is one
+            if native_tools || self.is_final_answer(&response.content) {
+                return Ok(extract_answer(&response.content));
             }
 
             // If no tool call and not a final answer, something went wrong
@@ -234,23 +187,6 @@ impl Strategy for ReActStrategy {
     }
 }
 
-/// Format messages for span attribute (compact representation)
-fn format_messages_for_span(messages: &[Message]) -> String {
-    messages
-        .iter()
-        .map(|m| {
-            let role = match m.role {
-                namra_llm::types::MessageRole::System => "system",
-                namra_llm::types::MessageRole::User => "user",
-                namra_llm::types::MessageRole::Assistant => "assistant",
-                namra_llm::types::MessageRole::Tool => "tool",
-            };
-            format!("[{}]: {}", role, m.content)
-        })
-        .collect::<Vec<_>>()
-        .join("\n---\n")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -282,14 +218,214 @@ mod tests {
         assert!(!strategy.is_final_answer("I need to use TOOL: calculator(2+2)"));
     }
 
-    #[test]
-    fn test_extract_answer() {
-        let strategy = ReActStrategy::new();
+    #[tokio::test]
+    async fn test_native_tool_calls() {
+        use crate::testing::{test_config, text_response, tool_call_response, ScriptedLLM};
+        use namra_llm::types::{MessageRole, ToolCall};
+        use namra_tools::StringTool;
+        use std::time::Duration;
+
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            tool_call_response(vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "string".to_string(),
+                arguments: serde_json::json!({
+                    "operation": "uppercase",
+                    "text": "f(x) = {\"a\": (1)}"
+                }),
+            }]),
+            text_response("ANSWER: done"),
+        ]));
+        let adapter: Arc<dyn LLMAdapter> = llm.clone();
+
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("string".to_string(), Arc::new(StringTool::new()));
+
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        context.add_message(Message::user("shout it"));
+
+        let answer = ReActStrategy::new()
+            .execute(&test_config(), &adapter, &tools, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "done");
+        assert_eq!(context.tool_calls.len(), 1);
+        assert!(context.tool_calls[0].success);
+
+        // Tool result is fed back linked to the originating call
+        let tool_message = &context.messages[2];
+        assert_eq!(tool_message.role, MessageRole::Tool);
+        assert_eq!(tool_message.tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(tool_message.content, "F(X) = {\"A\": (1)}");
+
+        // Tool definitions were sent with every request
+        let requests = llm.requests();
+        assert_eq!(requests[0].tools.as_ref().unwrap()[0].name, "string");
+    }
 
-        let response = "ANSWER: The result is 4";
-        assert_eq!(strategy.extract_answer(response), "The result is 4");
+    #[tokio::test]
+    async fn test_streaming_emits_events() {
+        use crate::context::StreamEvent;
+        use crate::testing::{test_config, text_response, tool_call_response, ScriptedLLM};
+        use namra_llm::types::ToolCall;
+        use namra_tools::StringTool;
+        use std::time::Duration;
+
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            tool_call_response(vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "string".to_string(),
+                arguments: serde_json::json!({"operation": "uppercase", "text": "hi"}),
+            }]),
+            text_response("ANSWER: all done"),
+        ]));
+        let adapter: Arc<dyn LLMAdapter> = llm.clone();
+
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("string".to_string(), Arc::new(StringTool::new()));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        context.events = Some(tx);
+        context.add_message(Message::user("shout it"));
+
+        let answer = ReActStrategy::new()
+            .execute(&test_config(), &adapter, &tools, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "all done");
+        assert!(llm.requests().iter().all(|r| r.stream));
+
+        // Tool deltas were accumulated into a call and usage came from the stream
+        assert_eq!(context.tool_calls[0].output.as_deref(), Some("HI"));
+        assert_eq!(context.total_tokens(), 30);
+
+        context.events = None;
+        let mut tokens = String::new();
+        let mut tool_events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                StreamEvent::Token(text) => tokens.push_str(&text),
+                StreamEvent::ToolCall { name, .. } => tool_events.push(name),
+                StreamEvent::ToolResult { success, .. } => assert!(success),
+                StreamEvent::Usage(usage) => assert_eq!(usage.total_tokens, 15),
+            }
+        }
+        assert_eq!(tokens, "ANSWER: all done");
+        assert_eq!(tool_events, vec!["string"]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool_is_fed_back() {
+        use crate::testing::{test_config, text_response, tool_call_response, ScriptedLLM};
+        use namra_llm::types::ToolCall;
+        use std::time::Duration;
+
+        let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![
+            tool_call_response(vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "missing".to_string(),
+                arguments: serde_json::json!({}),
+            }]),
+            text_response("ANSWER: recovered"),
+        ]));
+
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        let answer = ReActStrategy::new()
+            .execute(&test_config(), &adapter, &HashMap::new(), &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "recovered");
+        assert!(!context.tool_calls[0].success);
+        assert!(context.messages[1].content.contains("Tool not found"));
+        assert!(context.messages[1].is_error);
+    }
+
+    #[tokio::test]
+    async fn test_text_tool_calls_need_an_adapter_without_tool_calling() {
+        use crate::testing::{test_config, text_response, ScriptedLLM, SlowTool};
+        use std::time::Duration;
+
+        let slow = Arc::new(SlowTool::default());
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("slow".to_string(), slow.clone());
+
+        // The adapter calls tools natively, so `TOOL:` in the text is just text
+        let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![text_response(
+            "Reply with TOOL: slow(a) to run it",
+        )]));
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        let answer = ReActStrategy::new()
+            .execute(&test_config(), &adapter, &tools, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "Reply with TOOL: slow(a) to run it");
+        assert_eq!(slow.peak(), 0);
+        assert!(context.tool_calls.is_empty());
+    }
+
+    async fn run_slow_calls(
+        config: &AgentConfig,
+    ) -> (ExecutionContext, Arc<crate::testing::SlowTool>) {
+        use crate::testing::{text_response, tool_call_response, ScriptedLLM, SlowTool};
+        use namra_llm::types::ToolCall;
+        use std::time::Duration;
+
+        let calls = [("a", 60), ("b", 40), ("c", 20)]
+            .into_iter()
+            .map(|(id, delay)| ToolCall {
+                id: format!("call_{}", id),
+                name: "slow".to_string(),
+                arguments: serde_json::json!({"id": id, "delay_ms": delay}),
+            })
+            .collect();
+        let adapter: Arc<dyn LLMAdapter> = Arc::new(ScriptedLLM::new(vec![
+            tool_call_response(calls),
+            text_response("ANSWER: fetched"),
+        ]));
+
+        let slow = Arc::new(SlowTool::default());
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("slow".to_string(), slow.clone());
+
+        let mut context = ExecutionContext::new(5, Duration::from_secs(30));
+        ReActStrategy::new()
+            .execute(config, &adapter, &tools, &mut context)
+            .await
+            .unwrap();
+
+        (context, slow)
+    }
+
+    #[tokio::test]
+    async fn test_parallel_tool_calls_keep_order() {
+        let mut config = crate::testing::test_config();
+        config.execution.parallel_tool_calls = true;
+        config.execution.max_parallel_tool_calls = 2;
+
+        let (context, slow) = run_slow_calls(&config).await;
+        assert_eq!(slow.peak(), 2);
+
+        // Results follow call order even though later calls finished first
+        let results: Vec<(&str, &str)> = context.messages[1..4]
+            .iter()
+            .map(|m| (m.tool_call_id.as_deref().unwrap(), m.content.as_str()))
+            .collect();
+        assert_eq!(
+            results,
+            vec![("call_a", "a"), ("call_b", "b"), ("call_c", "c")]
+        );
+        assert_eq!(context.tool_calls[2].output.as_deref(), Some("c"));
+    }
 
-        let response = "The result is 4";
-        assert_eq!(strategy.extract_answer(response), "The result is 4");
+    #[tokio::test]
+    async fn test_tool_calls_run_serially_by_default() {
+        let (context, slow) = run_slow_calls(&crate::testing::test_config()).await;
+        assert_eq!(slow.peak(), 1);
+        assert_eq!(context.tool_calls.len(), 3);
     }
 }
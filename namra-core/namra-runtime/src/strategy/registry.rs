@@ -0,0 +1,125 @@
+//! Strategy registry for selecting an execution strategy from configuration
+
+use namra_config::AgentConfig;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::{Result, RuntimeError};
+use crate::strategy::chain_of_thought::ChainOfThoughtStrategy;
+use crate::strategy::plan_execute::PlanExecuteStrategy;
+use crate::strategy::react::ReActStrategy;
+use crate::strategy::tree_of_thoughts::TreeOfThoughtsStrategy;
+use crate::strategy::Strategy;
+
+/// Function that builds a strategy from the agent configuration
+pub type StrategyFactory = Arc<dyn Fn(&AgentConfig) -> Box<dyn Strategy> + Send + Sync>;
+
+/// Registry of strategies keyed by their `execution.strategy` name
+pub struct StrategyRegistry {
+    factories: HashMap<String, StrategyFactory>,
+}
+
+impl StrategyRegistry {
+    /// Create a registry with the built-in strategies
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register("react", Arc::new(|_| Box::new(ReActStrategy::new())));
+        registry.register(
+            "chain_of_thought",
+            Arc::new(|_| Box::new(ChainOfThoughtStrategy::new())),
+        );
+        registry.register(
+            "tree_of_thoughts",
+            Arc::new(|config| {
+                let settings = config
+                    .execution
+                    .tree_of_thoughts
+                    .clone()
+                    .unwrap_or_default();
+                Box::new(TreeOfThoughtsStrategy::new(settings))
+            }),
+        );
+        registry.register(
+            "plan_execute",
+            Arc::new(|config| {
+                let settings = config.execution.plan_execute.clone().unwrap_or_default();
+                Box::new(PlanExecuteStrategy::new(settings))
+            }),
+        );
+
+        registry
+    }
+
+    /// Create a registry without any strategies
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// Register a strategy factory, replacing any existing one with the same name
+    pub fn register(&mut self, name: impl Into<String>, factory: StrategyFactory) {
+        self.factories.insert(name.into(), factory);
+    }
+
+    /// Build the strategy named in `execution.strategy`
+    pub fn create(&self, config: &AgentConfig) -> Result<Box<dyn Strategy>> {
+        let name = config.execution.strategy.as_str();
+        let factory = self.factories.get(name).ok_or_else(|| {
+            RuntimeError::ConfigError(format!(
+                "Unknown execution strategy: {}. Available: {}",
+                name,
+                self.names().join(", ")
+            ))
+        })?;
+
+        Ok(factory(config))
+    }
+
+    /// Registered strategy names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_config;
+
+    #[test]
+    fn test_creates_configured_strategy() {
+        let registry = StrategyRegistry::new();
+        let mut config = test_config();
+
+        for name in [
+            "react",
+            "chain_of_thought",
+            "tree_of_thoughts",
+            "plan_execute",
+        ] {
+            config.execution.strategy = name.to_string();
+            assert_eq!(registry.create(&config).unwrap().name(), name);
+        }
+    }
+
+    #[test]
+    fn test_unknown_strategy() {
+        let mut config = test_config();
+        config.execution.strategy = "guesswork".to_string();
+
+        let err = StrategyRegistry::new().create(&config).err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("Unknown execution strategy: guesswork"));
+    }
+}
@@ -0,0 +1,332 @@
+//! Tree-of-thoughts strategy
+//!
+//! Explores several reasoning paths instead of committing to the first one:
+//! 1. PROPOSE: From each state, ask the LLM for `branching_factor` candidate next steps
+//! 2. EVALUATE: Score each candidate path with a separate LLM call (0-10)
+//! 3. PRUNE: Keep the `beam_width` best paths and repeat up to `max_depth` levels
+//!
+//! The search stops early once the best path reaches an `ANSWER:` step;
+//! otherwise the LLM is asked for a final answer from the best path. Each
+//! search level counts as one iteration. This strategy does not call tools.
+
+use async_trait::async_trait;
+use namra_config::{AgentConfig, TreeOfThoughtsConfig};
+use namra_llm::adapter::LLMAdapter;
+use namra_llm::types::Message;
+use namra_tools::Tool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::context::ExecutionContext;
+use crate::error::Result;
+use crate::strategy::common::{build_request, call_llm, check_limits, extract_answer};
+use crate::strategy::Strategy;
+
+/// A partial reasoning path in the search tree
+#[derive(Debug, Clone, Default)]
+struct ThoughtPath {
+    steps: Vec<String>,
+    score: f32,
+}
+
+impl ThoughtPath {
+    fn extend(&self, step: String) -> Self {
+        let mut steps = self.steps.clone();
+        steps.push(step);
+        Self { steps, score: 0.0 }
+    }
+
+    fn is_solution(&self) -> bool {
+        self.steps
+            .last()
+            .is_some_and(|step| step.contains("ANSWER:"))
+    }
+
+    /// Render the steps as a numbered list for prompts
+    fn render(&self) -> String {
+        if self.steps.is_empty() {
+            return "(no steps yet)".to_string();
+        }
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| format!("{}. {}", i + 1, step))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Tree-of-thoughts strategy implementation
+pub struct TreeOfThoughtsStrategy {
+    settings: TreeOfThoughtsConfig,
+}
+
+impl TreeOfThoughtsStrategy {
+    /// Create a new tree-of-thoughts strategy
+    pub fn new(settings: TreeOfThoughtsConfig) -> Self {
+        Self { settings }
+    }
+
+    /// Ask the LLM for candidate next steps from a path
+    async fn propose(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        context: &mut ExecutionContext,
+        path: &ThoughtPath,
+    ) -> Result<Vec<String>> {
+        let n = self.settings.branching_factor;
+        let prompt = format!(
+            "Reasoning so far:\n{}\n\nPropose {} different possible next steps toward solving the problem. \
+Each must be a single reasoning step. If a step reaches the final answer, write it as 'ANSWER: <answer>'. \
+Reply with exactly {} lines numbered '1.', '2.', and so on.",
+            path.render(),
+            n,
+            n
+        );
+
+        let mut messages = context.messages.clone();
+        messages.push(Message::user(prompt));
+
+        let response =
+            call_llm(config, llm, build_request(config, messages, None), context).await?;
+
+        Ok(parse_proposals(&response.content, n as usize))
+    }
+
+    /// Score a path with the LLM (0.0 - 1.0)
+    async fn evaluate(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        context: &mut ExecutionContext,
+        path: &ThoughtPath,
+    ) -> Result<f32> {
+        let prompt = format!(
+            "Evaluate this partial solution to the problem:\n{}\n\n\
+How likely is this reasoning to lead to a correct answer? Reply with only a score from 0 to 10.",
+            path.render()
+        );
+
+        let mut messages = context.messages.clone();
+        messages.push(Message::user(prompt));
+
+        // Scoring should be deterministic
+        let mut request = build_request(config, messages, None);
+        request.temperature = Some(0.0);
+
+        let response = call_llm(config, llm, request, context).await?;
+
+        Ok(parse_score(&response.content))
+    }
+
+    /// Ask for a final answer from the best path
+    async fn conclude(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        context: &mut ExecutionContext,
+        path: &ThoughtPath,
+    ) -> Result<String> {
+        let prompt = format!(
+            "Reasoning:\n{}\n\nBased on this reasoning, give the final answer on a line starting with 'ANSWER:'.",
+            path.render()
+        );
+
+        let mut messages = context.messages.clone();
+        messages.push(Message::user(prompt));
+
+        let response =
+            call_llm(config, llm, build_request(config, messages, None), context).await?;
+
+        Ok(extract_answer(&response.content))
+    }
+}
+
+impl Default for TreeOfThoughtsStrategy {
+    fn default() -> Self {
+        Self::new(TreeOfThoughtsConfig::default())
+    }
+}
+
+#[async_trait]
+impl Strategy for TreeOfThoughtsStrategy {
+    async fn execute(
+        &self,
+        config: &AgentConfig,
+        llm: &Arc<dyn LLMAdapter>,
+        _tools: &HashMap<String, Arc<dyn Tool>>,
+        context: &mut ExecutionContext,
+    ) -> Result<String> {
+        let mut frontier = vec![ThoughtPath::default()];
+        let mut explored = 0;
+
+        for depth in 1..=self.settings.max_depth {
+            check_limits(context)?;
+            context.increment_iteration();
+
+            // PROPOSE and EVALUATE candidates from every path in the beam
+            let mut candidates = Vec::new();
+            for path in &frontier {
+                for step in self.propose(config, llm, context, path).await? {
+                    let mut candidate = path.extend(step);
+                    candidate.score = self.evaluate(config, llm, context, &candidate).await?;
+                    candidates.push(candidate);
+                }
+            }
+
+            if candidates.is_empty() {
+                break;
+            }
+            explored += candidates.len();
+
+            // PRUNE to the best paths
+            candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+            candidates.truncate(self.settings.beam_width as usize);
+            frontier = candidates;
+
+            let best = &frontier[0];
+            context.record_thought(format!(
+                "Depth {} (score {:.1}): {}",
+                depth,
+                best.score * 10.0,
+                best.steps.last().map(String::as_str).unwrap_or_default()
+            ));
+
+            if best.is_solution() {
+                break;
+            }
+        }
+
+        let best = frontier.swap_remove(0);
+
+        context.metadata.insert(
+            "tree_of_thoughts".to_string(),
+            serde_json::json!({
+                "branching_factor": self.settings.branching_factor,
+                "max_depth": self.settings.max_depth,
+                "beam_width": self.settings.beam_width,
+                "explored": explored,
+                "best_path": best.steps,
+                "best_score": best.score,
+            }),
+        );
+
+        if best.is_solution() {
+            let last = best.steps.last().map(String::as_str).unwrap_or_default();
+            return Ok(extract_answer(last));
+        }
+
+        check_limits(context)?;
+        context.increment_iteration();
+        self.conclude(config, llm, context, &best).await
+    }
+
+    fn name(&self) -> &str {
+        "tree_of_thoughts"
+    }
+}
+
+/// Parse numbered proposal lines ("1. ...", "2) ...")
+fn parse_proposals(response: &str, limit: usize) -> Vec<String> {
+    response
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                return None;
+            }
+            let rest = line[digits..].strip_prefix(['.', ')'])?.trim();
+            (!rest.is_empty()).then(|| rest.to_string())
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Parse the first number in an evaluator response into a 0.0 - 1.0 score
+fn parse_score(response: &str) -> f32 {
+    let number: String = response
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    number
+        .trim_end_matches('.')
+        .parse::<f32>()
+        .map(|score| (score / 10.0).clamp(0.0, 1.0))
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{test_config, text_response, ScriptedLLM};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_proposals() {
+        let proposals = parse_proposals("Ideas:\n1. Try A\n2) Try B\n\n3. Try C", 2);
+        assert_eq!(proposals, vec!["Try A", "Try B"]);
+    }
+
+    #[test]
+    fn test_parse_score() {
+        assert_eq!(parse_score("8"), 0.8);
+        assert_eq!(parse_score("Score: 7.5/10"), 0.75);
+        assert_eq!(parse_score("15"), 1.0);
+        assert_eq!(parse_score("no idea"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_search_follows_best_branch() {
+        let llm = Arc::new(ScriptedLLM::new(vec![
+            // Depth 1: propose and score two branches
+            text_response("1. Try A\n2. Try B"),
+            text_response("3"),
+            text_response("8"),
+            // Depth 2: expand only the best branch (beam width 1)
+            text_response("1. B then C\n2. ANSWER: 42"),
+            text_response("5"),
+            text_response("9"),
+        ]));
+        let adapter: Arc<dyn LLMAdapter> = llm.clone();
+
+        let strategy = TreeOfThoughtsStrategy::new(TreeOfThoughtsConfig {
+            branching_factor: 2,
+            max_depth: 3,
+            beam_width: 1,
+        });
+
+        let mut context = ExecutionContext::new(10, Duration::from_secs(30));
+        context.add_message(Message::user("What is the answer?"));
+
+        let answer = strategy
+            .execute(&test_config(), &adapter, &HashMap::new(), &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(answer, "42");
+        assert_eq!(context.iteration, 2);
+        assert_eq!(context.thoughts.len(), 2);
+
+        // The second expansion built on the higher-scored branch
+        let requests = llm.requests();
+        assert_eq!(requests.len(), 6);
+        assert!(requests[3]
+            .messages
+            .last()
+            .unwrap()
+            .content
+            .contains("1. Try B"));
+        assert_eq!(requests[1].temperature, Some(0.0));
+
+        let tree = &context.metadata["tree_of_thoughts"];
+        assert_eq!(
+            tree["best_path"],
+            serde_json::json!(["Try B", "ANSWER: 42"])
+        );
+        assert_eq!(tree["explored"], 4);
+    }
+}
@@ -0,0 +1,297 @@
+//! Test helpers shared by runtime unit tests
+
+use crate::cache::Embedder;
+use async_trait::async_trait;
+use namra_config::{AgentConfig, ExecutionConfig, LLMConfig};
+use namra_llm::adapter::{LLMAdapter, LLMError, LLMResult, LLMStream};
+use namra_llm::types::{
+    FinishReason, LLMRequest, LLMResponse, MessageRole, StreamChunk, TokenUsage, ToolCall,
+    ToolCallDelta,
+};
+use namra_tools::{Result as ToolResult, Tool, ToolOutput};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// LLM adapter that replays a fixed script of responses
+pub struct ScriptedLLM {
+    responses: Mutex<VecDeque<LLMResponse>>,
+    requests: Mutex<Vec<LLMRequest>>,
+    failure: Option<fn() -> LLMError>,
+    delay: Duration,
+    cost: Option<f64>,
+}
+
+impl ScriptedLLM {
+    pub fn new(responses: Vec<LLMResponse>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into()),
+            requests: Mutex::new(Vec::new()),
+            failure: None,
+            delay: Duration::ZERO,
+            cost: None,
+        }
+    }
+
+    /// Fail every request with the error from `failure`
+    pub fn failing_with(mut self, failure: fn() -> LLMError) -> Self {
+        self.failure = Some(failure);
+        self
+    }
+
+    /// Wait for `delay` before answering each request
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Estimate `cost` for every response
+    pub fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<LLMRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for ScriptedLLM {
+    fn provider_name(&self) -> &str {
+        "scripted"
+    }
+
+    async fn generate(&self, request: LLMRequest) -> LLMResult<LLMResponse> {
+        self.requests.lock().unwrap().push(request);
+        tokio::time::sleep(self.delay).await;
+        if let Some(failure) = self.failure {
+            return Err(failure());
+        }
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| LLMError::Unknown("script exhausted".to_string()))
+    }
+
+    /// Replays the next scripted response as word-sized chunks
+    async fn stream(&self, request: LLMRequest) -> LLMResult<LLMStream> {
+        let response = self.generate(request).await?;
+
+        let mut chunks: Vec<StreamChunk> = response
+            .content
+            .split_inclusive(' ')
+            .map(|word| StreamChunk {
+                content: word.to_string(),
+                tool_call_delta: None,
+                is_final: false,
+                usage: None,
+                finish_reason: None,
+                metadata: HashMap::new(),
+            })
+            .collect();
+
+        for (index, call) in response.tool_calls.iter().flatten().enumerate() {
+            chunks.push(StreamChunk {
+                content: String::new(),
+                tool_call_delta: Some(ToolCallDelta {
+                    index,
+                    id: Some(call.id.clone()),
+                    name: Some(call.name.clone()),
+                    arguments: Some(call.arguments.to_string()),
+                }),
+                is_final: false,
+                usage: None,
+                finish_reason: None,
+                metadata: HashMap::new(),
+            });
+        }
+
+        chunks.push(StreamChunk {
+            content: String::new(),
+            tool_call_delta: None,
+            is_final: true,
+            usage: Some(response.usage),
+            finish_reason: Some(response.finish_reason),
+            metadata: HashMap::new(),
+        });
+
+        Ok(Box::pin(futures::stream::iter(chunks.into_iter().map(Ok))))
+    }
+
+    fn max_context_tokens(&self, _model: &str) -> Option<u32> {
+        None
+    }
+
+    fn estimate_cost(&self, _input_tokens: u32, _output_tokens: u32, _model: &str) -> Option<f64> {
+        self.cost
+    }
+}
+
+/// A plain text response
+pub fn text_response(content: &str) -> LLMResponse {
+    LLMResponse {
+        content: content.to_string(),
+        role: MessageRole::Assistant,
+        tool_calls: None,
+        usage: TokenUsage::new(10, 5),
+        finish_reason: FinishReason::Stop,
+        metadata: HashMap::new(),
+    }
+}
+
+/// A text response cut off at the max_tokens limit
+pub fn truncated_response(content: &str) -> LLMResponse {
+    LLMResponse {
+        finish_reason: FinishReason::Length,
+        ..text_response(content)
+    }
+}
+
+/// A response requesting the given tool calls
+pub fn tool_call_response(calls: Vec<ToolCall>) -> LLMResponse {
+    LLMResponse {
+        content: String::new(),
+        role: MessageRole::Assistant,
+        tool_calls: Some(calls),
+        usage: TokenUsage::new(10, 5),
+        finish_reason: FinishReason::ToolCalls,
+        metadata: HashMap::new(),
+    }
+}
+
+/// Minimal agent configuration for strategy tests
+pub fn test_config() -> AgentConfig {
+    AgentConfig {
+        name: "test".to_string(),
+        version: "1.0.0".to_string(),
+        description: None,
+        metadata: HashMap::new(),
+        llm: LLMConfig {
+            provider: "scripted".to_string(),
+            model: "test-model".to_string(),
+            temperature: 0.0,
+            max_tokens: 1024,
+            top_p: None,
+            stream: false,
+            retry: None,
+            base_url: None,
+            api_key: None,
+        },
+        tools: vec![],
+        memory: None,
+        middleware: None,
+        execution: ExecutionConfig::default(),
+        system_prompt: String::new(),
+        tenancy: None,
+    }
+}
+
+/// Tool that sleeps briefly and records the peak number of concurrent calls
+///
+/// Sleeps for `delay_ms` and echoes its `id` argument, so callers can check
+/// that results keep call order when later calls finish first. With `fail`
+/// set, the output is marked as failed.
+#[derive(Default)]
+pub struct SlowTool {
+    active: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl SlowTool {
+    /// Most calls that were in flight at the same time
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Tool for SlowTool {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    fn description(&self) -> &str {
+        "Sleeps for delay_ms, then echoes its id"
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({"type": "object", "properties": {
+            "id": {"type": "string"},
+            "delay_ms": {"type": "integer"},
+            "fail": {"type": "boolean"}
+        }})
+    }
+
+    async fn execute(&self, input: Value) -> ToolResult<ToolOutput> {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+
+        let id = input["id"].as_str().unwrap_or_default().to_string();
+        let delay = input["delay_ms"].as_u64().unwrap_or(20);
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        if input["fail"].as_bool().unwrap_or(false) {
+            return Ok(ToolOutput::failure(id, delay));
+        }
+        Ok(ToolOutput::success(id, delay))
+    }
+}
+
+/// Embedder that hashes words and word pairs into a fixed-size vector
+///
+/// Stands in for an embedding model in tests; texts sharing most of their
+/// words embed close together whatever they mean.
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dimensions: usize,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(512)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashingEmbedder {
+    async fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        let mut vector = vec![0.0f32; self.dimensions];
+        let features = words
+            .iter()
+            .cloned()
+            .chain(words.windows(2).map(|pair| pair.join(" ")));
+        for feature in features {
+            let hash = fnv1a(feature.as_bytes());
+            let sign = if hash & 1 == 0 { 1.0 } else { -1.0 };
+            vector[(hash >> 1) as usize % self.dimensions] += sign;
+        }
+        Ok(vector)
+    }
+}
+
+/// 64-bit FNV-1a, stable across builds so stored embeddings stay comparable
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
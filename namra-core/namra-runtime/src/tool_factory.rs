@@ -1,11 +1,11 @@
 //! Tool factory for creating tool instances from configuration
 
 use namra_config::{
-    AgentConfig, FileSystemBackend, FileSystemToolConfig, HttpToolConfig, ToolConfig,
+    AgentConfig, FileSystemBackend, FileSystemToolConfig, HttpToolConfig, SecretValue, ToolConfig,
 };
 use namra_tools::{
     AzureBackend, AzureConfig, CalculatorTool, FileSystemTool, GCSBackend, GCSConfig, HttpTool,
-    LocalBackend, S3Backend, S3Config, SFTPBackend, SFTPConfig, StringTool, Tool,
+    LocalBackend, S3Backend, S3Config, SFTPBackend, SFTPConfig, StringTool, Tool, UrlPolicy,
 };
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -36,19 +36,44 @@ impl ToolFactory {
         tools.insert("string".to_string(), Arc::new(StringTool::new()));
 
         // Add configured tools from agent config
+        let policy = Self::url_policy(config);
         for tool_config in &config.tools {
-            let (name, tool) = self.build_tool_from_config(tool_config)?;
+            let (name, tool) = self.build_tool_from_config(tool_config, &policy)?;
             tools.insert(name, tool);
         }
 
         Ok(tools)
     }
 
+    /// URL policy for the HTTP tools from `middleware.security`
+    ///
+    /// Private network addresses are blocked unless the security config
+    /// allows them.
+    pub fn url_policy(config: &AgentConfig) -> UrlPolicy {
+        let security = config
+            .middleware
+            .as_ref()
+            .and_then(|m| m.security.as_ref())
+            .filter(|s| s.enabled);
+
+        match security {
+            Some(security) => UrlPolicy::new()
+                .allowed_domains(&security.allowed_domains)
+                .blocked_domains(&security.blocked_domains)
+                .allow_private_networks(security.allow_private_networks),
+            None => UrlPolicy::new(),
+        }
+    }
+
     /// Build a single tool from configuration
-    fn build_tool_from_config(&self, tool_config: &ToolConfig) -> Result<(String, Arc<dyn Tool>)> {
+    fn build_tool_from_config(
+        &self,
+        tool_config: &ToolConfig,
+        policy: &UrlPolicy,
+    ) -> Result<(String, Arc<dyn Tool>)> {
         match tool_config {
             ToolConfig::BuiltinHttp { name, config } => {
-                let tool = self.build_http_tool(config)?;
+                let tool = self.build_http_tool(config, policy.clone())?;
                 Ok((name.clone(), Arc::new(tool)))
             }
 
@@ -92,17 +117,46 @@ impl ToolFactory {
     }
 
     /// Build a configured HTTP tool
-    fn build_http_tool(&self, config: &HttpToolConfig) -> Result<ConfiguredHttpTool> {
+    ///
+    /// The host of the configured URL was chosen by the operator, so it is
+    /// trusted even on a private network; other hosts reached through the
+    /// model-supplied path or redirects get the full `policy` checks.
+    fn build_http_tool(
+        &self,
+        config: &HttpToolConfig,
+        policy: UrlPolicy,
+    ) -> Result<ConfiguredHttpTool> {
         // Parse timeout
         let timeout = self.parse_timeout(&config.timeout)?;
 
+        let unresolved = config
+            .auth
+            .iter()
+            .chain(config.headers.values())
+            .any(|secret| !secret.is_resolved());
+        if unresolved {
+            return Err(RuntimeError::ConfigError(format!(
+                "HTTP tool for '{}' has unresolved secret references",
+                config.url
+            )));
+        }
+
+        let base_url = url::Url::parse(&config.url).map_err(|e| {
+            RuntimeError::ConfigError(format!("Invalid HTTP tool URL '{}': {}", config.url, e))
+        })?;
+        let policy = match base_url.host_str() {
+            Some(host) => policy.trust_host(host.trim_start_matches('[').trim_end_matches(']')),
+            None => policy,
+        };
+
         Ok(ConfiguredHttpTool {
             name: "http_configured".to_string(),
             base_url: config.url.clone(),
             method: config.method.clone(),
             headers: config.headers.clone(),
+            auth: config.auth.clone(),
             timeout,
-            http_tool: HttpTool::with_timeout(timeout),
+            http_tool: HttpTool::with_timeout(timeout).with_policy(policy),
         })
     }
 
@@ -218,7 +272,8 @@ pub struct ConfiguredHttpTool {
     name: String,
     base_url: String,
     method: String,
-    headers: HashMap<String, String>,
+    headers: HashMap<String, SecretValue>,
+    auth: Option<SecretValue>,
     #[allow(dead_code)]
     timeout: Duration,
     http_tool: HttpTool,
@@ -288,10 +343,24 @@ impl Tool for ConfiguredHttpTool {
         }
 
         // Build the full request
+        // Secrets were checked to be resolved when the tool was built
+        let mut headers: serde_json::Map<String, Value> = self
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.clone(), json!(value.expose()?))))
+            .collect();
+        if let Some(auth) = self.auth.as_ref().and_then(SecretValue::expose) {
+            let value = if auth.contains(' ') {
+                auth.to_string()
+            } else {
+                format!("Bearer {}", auth)
+            };
+            headers.insert("Authorization".to_string(), json!(value));
+        }
         let mut request = json!({
             "url": url,
             "method": self.method.clone(),
-            "headers": self.headers.clone(),
+            "headers": headers,
         });
 
         // Add body if provided
@@ -347,6 +416,8 @@ mod tests {
                 top_p: None,
                 stream: true,
                 retry: None,
+                base_url: None,
+                api_key: None,
             },
             tools: vec![],
             memory: None,
@@ -364,4 +435,81 @@ mod tests {
         assert!(tools.contains_key("string"));
         assert_eq!(tools.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_configured_http_tool_enforces_url_policy() {
+        let mut config = crate::testing::test_config();
+        config.middleware = Some(namra_config::MiddlewareConfig {
+            security: Some(serde_yaml::from_str("{blocked_domains: [evil.example]}").unwrap()),
+            ..Default::default()
+        });
+        let http_config = |url: &str| HttpToolConfig {
+            url: url.to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            auth: None,
+            timeout: "5s".to_string(),
+            retry: false,
+        };
+
+        let factory = ToolFactory::new();
+        let policy = ToolFactory::url_policy(&config);
+
+        // Blocked domains apply even to the configured URL
+        let tool = factory
+            .build_http_tool(&http_config("http://evil.example/api"), policy.clone())
+            .unwrap();
+        let result = tool.execute(json!({"path": "steal"})).await;
+        assert!(matches!(
+            result,
+            Err(namra_tools::ToolError::PermissionDenied(_))
+        ));
+
+        // The configured host is trusted on the private network, so the
+        // request gets as far as connecting (nothing listens on port 9)
+        let tool = factory
+            .build_http_tool(&http_config("http://127.0.0.1:9/api"), policy)
+            .unwrap();
+        let result = tool.execute(json!({})).await;
+        assert!(matches!(result, Err(namra_tools::ToolError::HttpError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_configured_http_tool_sends_auth() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_lowercase()
+        });
+
+        let mut config = HttpToolConfig {
+            url,
+            method: "GET".to_string(),
+            headers: HashMap::from([("X-Client".to_string(), "namra".into())]),
+            auth: Some("${env:NAMRA_TEST_UNSET_TOKEN}".into()),
+            timeout: "5s".to_string(),
+            retry: false,
+        };
+        let factory = ToolFactory::new();
+
+        // References must be resolved before the tool is built
+        assert!(factory.build_http_tool(&config, UrlPolicy::new()).is_err());
+
+        config.auth = Some("tok123".into());
+        let tool = factory.build_http_tool(&config, UrlPolicy::new()).unwrap();
+        tool.execute(json!({})).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.contains("authorization: bearer tok123"));
+        assert!(request.contains("x-client: namra"));
+    }
 }
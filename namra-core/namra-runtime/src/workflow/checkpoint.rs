@@ -0,0 +1,242 @@
+//! Workflow checkpoints
+//!
+//! With `config.checkpoint.enabled` a workflow run saves its progress to the
+//! run history database: the nodes that ran, their outputs, the steps still
+//! pending, and the branches waiting at join nodes. A run that crashed, timed
+//! out, or stopped on a failed node can then be resumed from its last
+//! checkpoint.
+
+use namra_config::{parse_duration, CheckpointConfig, WorkflowConfig};
+use namra_storage::SqliteStorage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::{Result, RuntimeError};
+use crate::workflow::executor::NodeResult;
+use crate::workflow::join::Arrival;
+
+/// `interval` value that saves a checkpoint after every node
+const EVERY_NODE: &str = "node";
+
+/// What a workflow run does next
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PendingStep {
+    /// Run a node with an input
+    Run { node_id: String, input: String },
+    /// Follow the outgoing edges of the node result at this index of
+    /// [`WorkflowCheckpoint::nodes`]
+    Route { node: usize },
+}
+
+/// Saved progress of a workflow run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowCheckpoint {
+    /// Workflow run ID
+    pub run_id: String,
+
+    /// Workflow the run started with
+    pub workflow: WorkflowConfig,
+
+    /// Directory `agent_config` paths are resolved against
+    pub base_dir: PathBuf,
+
+    /// Input of the entry point
+    pub input: String,
+
+    /// Nodes that have run, in the order they finished
+    pub nodes: Vec<NodeResult>,
+
+    /// Steps still to run or running; none once the exit point has run
+    pub pending: Vec<PendingStep>,
+
+    /// Branches waiting at join nodes (join node ID -> arrivals)
+    #[serde(default)]
+    pub arrivals: BTreeMap<String, Vec<Arrival>>,
+
+    /// Times each join node has joined its branches (join node ID ->
+    /// generation); branches from an earlier generation are ignored
+    #[serde(default)]
+    pub generations: BTreeMap<String, usize>,
+
+    /// Tokens used by LLM routers
+    pub routing_tokens: u32,
+
+    /// Cost of LLM routers in USD
+    pub routing_cost: f64,
+
+    /// Time spent running the workflow so far, in milliseconds
+    pub elapsed_ms: u64,
+}
+
+impl WorkflowCheckpoint {
+    /// Progress of a run that has not started yet
+    pub fn new(workflow: WorkflowConfig, base_dir: impl Into<PathBuf>, input: &str) -> Self {
+        let entry = PendingStep::Run {
+            node_id: workflow.entry_point.clone(),
+            input: input.to_string(),
+        };
+        Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            workflow,
+            base_dir: base_dir.into(),
+            input: input.to_string(),
+            nodes: Vec::new(),
+            pending: vec![entry],
+            arrivals: BTreeMap::new(),
+            generations: BTreeMap::new(),
+            routing_tokens: 0,
+            routing_cost: 0.0,
+            elapsed_ms: 0,
+        }
+    }
+
+    /// Mark a pending step as done
+    pub(crate) fn finish(&mut self, step: &PendingStep) {
+        if let Some(idx) = self.pending.iter().position(|s| s == step) {
+            self.pending.remove(idx);
+        }
+    }
+
+    /// Latest checkpoint of the run whose ID is or starts with `id`
+    ///
+    /// Fails if the checkpoint has a node without attempts or a step routing
+    /// from a node that isn't in it, which a resumed run couldn't follow.
+    pub fn load(storage: &Mutex<SqliteStorage>, id: &str) -> Result<Option<Self>> {
+        let state = storage
+            .lock()
+            .map_err(|_| RuntimeError::Other("Run history lock poisoned".to_string()))?
+            .get_workflow_checkpoint(id)
+            .map_err(|e| RuntimeError::Other(format!("Failed to load checkpoint: {}", e)))?;
+        let invalid =
+            |e: String| RuntimeError::Other(format!("Invalid checkpoint for run {}: {}", id, e));
+        let Some(checkpoint) = state
+            .map(serde_json::from_value::<Self>)
+            .transpose()
+            .map_err(|e| invalid(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        if let Some(node) = checkpoint.nodes.iter().find(|n| n.attempts.is_empty()) {
+            return Err(invalid(format!("node {} has no attempts", node.node_id)));
+        }
+        for step in &checkpoint.pending {
+            if let PendingStep::Route { node } = step {
+                if *node >= checkpoint.nodes.len() {
+                    return Err(invalid(format!("no node result {} to route from", node)));
+                }
+            }
+        }
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Save the checkpoint, replacing the run's earlier one
+    pub fn save(&self, storage: &Mutex<SqliteStorage>) -> Result<()> {
+        let state = serde_json::to_value(self)
+            .map_err(|e| RuntimeError::Other(format!("Failed to serialize checkpoint: {}", e)))?;
+        storage
+            .lock()
+            .map_err(|_| RuntimeError::Other("Run history lock poisoned".to_string()))?
+            .save_workflow_checkpoint(&self.run_id, &self.workflow.name, &state)
+            .map_err(|e| RuntimeError::Other(format!("Failed to save checkpoint: {}", e)))
+    }
+
+    /// Remove the run's checkpoint
+    pub fn delete(&self, storage: &Mutex<SqliteStorage>) -> Result<()> {
+        storage
+            .lock()
+            .map_err(|_| RuntimeError::Other("Run history lock poisoned".to_string()))?
+            .delete_workflow_checkpoint(&self.run_id)
+            .map_err(|e| RuntimeError::Other(format!("Failed to delete checkpoint: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// How often a run saves its checkpoint
+///
+/// Checkpoints are taken at node boundaries: after every node, or after the
+/// first node to finish once `interval` has passed since the last one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CheckpointPolicy {
+    /// Never save checkpoints
+    Disabled,
+    /// Save at a node boundary at most once per interval; zero means always
+    Every(Duration),
+}
+
+impl CheckpointPolicy {
+    /// Policy for a workflow's `checkpoint` settings
+    pub(crate) fn from_config(config: Option<&CheckpointConfig>) -> Result<Self> {
+        let Some(config) = config.filter(|c| c.enabled) else {
+            return Ok(CheckpointPolicy::Disabled);
+        };
+        if config.interval.trim() == EVERY_NODE {
+            return Ok(CheckpointPolicy::Every(Duration::ZERO));
+        }
+        parse_duration(&config.interval)
+            .map(CheckpointPolicy::Every)
+            .map_err(|e| {
+                RuntimeError::ConfigError(format!(
+                    "Invalid checkpoint interval '{}': {}. Use '{}' or a duration like '30s'",
+                    config.interval, e, EVERY_NODE
+                ))
+            })
+    }
+
+    /// Whether a checkpoint is due when the last one was saved at `last`
+    pub(crate) fn due(&self, last: Instant) -> bool {
+        match self {
+            CheckpointPolicy::Disabled => false,
+            CheckpointPolicy::Every(interval) => last.elapsed() >= *interval,
+        }
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        *self != CheckpointPolicy::Disabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(enabled: bool, interval: &str) -> CheckpointConfig {
+        CheckpointConfig {
+            enabled,
+            interval: interval.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_policy() {
+        let policy = |config: &CheckpointConfig| CheckpointPolicy::from_config(Some(config));
+
+        assert_eq!(
+            CheckpointPolicy::from_config(None).unwrap(),
+            CheckpointPolicy::Disabled
+        );
+        assert_eq!(
+            policy(&checkpoint(false, "node")).unwrap(),
+            CheckpointPolicy::Disabled
+        );
+        assert_eq!(
+            policy(&checkpoint(true, "node")).unwrap(),
+            CheckpointPolicy::Every(Duration::ZERO)
+        );
+        assert_eq!(
+            policy(&checkpoint(true, "30s")).unwrap(),
+            CheckpointPolicy::Every(Duration::from_secs(30))
+        );
+        assert!(policy(&checkpoint(true, "often")).is_err());
+
+        let started = Instant::now();
+        assert!(CheckpointPolicy::Every(Duration::ZERO).due(started));
+        assert!(!CheckpointPolicy::Every(Duration::from_secs(30)).due(started));
+        assert!(!CheckpointPolicy::Disabled.due(started));
+    }
+}
@@ -0,0 +1,111 @@
+//! Values of the variables that `expression` edge conditions read
+//!
+//! The rule language itself lives in [`namra_config::RouteRules`], so that
+//! `namra validate` checks rules with the same parser that runs them.
+
+use serde_json::Value;
+
+use crate::workflow::executor::NodeResult;
+
+pub use namra_config::RouteRules;
+
+/// The node `rules` choose for the outcome of `node`, if any rule holds
+pub fn route<'a>(rules: &'a RouteRules, node: &NodeResult) -> Option<&'a str> {
+    rules.route(&variables(node))
+}
+
+/// Values of the variables for the outcome of `node`
+fn variables(node: &NodeResult) -> Value {
+    let result = node.result();
+    serde_json::json!({
+        "output": result.response,
+        "json": parse_json_output(&result.response),
+        "success": result.success,
+        "cost": node.total_cost(),
+        "tokens": node.total_tokens(),
+    })
+}
+
+/// JSON in a response: the whole response, a fenced block, or the outermost
+/// object, or `null` if there is none
+fn parse_json_output(output: &str) -> Value {
+    let output = output.trim();
+    if let Ok(value) = serde_json::from_str(output) {
+        return value;
+    }
+
+    let fenced = output
+        .split_once("```json")
+        .and_then(|(_, rest)| rest.split_once("```"))
+        .map(|(block, _)| block);
+    let object = output
+        .find('{')
+        .zip(output.rfind('}'))
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| &output[start..=end]);
+    fenced
+        .into_iter()
+        .chain(object)
+        .find_map(|candidate| serde_json::from_str(candidate.trim()).ok())
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ExecutionResult;
+
+    fn node(response: &str, success: bool, cost: f64) -> NodeResult {
+        let mut result = ExecutionResult::success(
+            "run".to_string(),
+            response.to_string(),
+            1,
+            Vec::new(),
+            120,
+            cost,
+            10,
+            Vec::new(),
+        );
+        result.success = success;
+        NodeResult {
+            node_id: "triage".to_string(),
+            input: String::new(),
+            attempts: vec![result],
+        }
+    }
+
+    #[test]
+    fn test_rules_route_on_node_outcome() {
+        let rules = RouteRules::parse(
+            r#"
+            json.tags contains 'outage' => technical
+            output matches "(?i)\brefund" => refunds
+            !success || cost > 0.5 || tokens > 1000 => review
+            else => general
+            "#,
+        )
+        .unwrap();
+
+        let chosen = |response: &str, success: bool, cost: f64| {
+            route(&rules, &node(response, success, cost))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            chosen(
+                "Here you go:\n```json\n{\"tags\": [\"outage\"]}\n```",
+                true,
+                0.0
+            ),
+            "technical"
+        );
+        assert_eq!(
+            chosen(r#"Tagged {"tags": ["outage"]} as asked"#, true, 0.0),
+            "technical"
+        );
+        assert_eq!(chosen("I want a Refund now", true, 0.0), "refunds");
+        assert_eq!(chosen("anything", false, 0.0), "review");
+        assert_eq!(chosen("anything", true, 0.9), "review");
+        assert_eq!(chosen("anything", true, 0.1), "general");
+    }
+}
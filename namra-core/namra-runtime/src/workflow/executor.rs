@@ -0,0 +1,1374 @@
+//! Workflow executor - runs agent nodes along a workflow's edges
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use namra_config::{
+    load_agent_config, parse_duration, validate_config, MergePolicy, WorkflowConfig, WorkflowEdge,
+    WorkflowNode,
+};
+use namra_llm::types::LLMResponse;
+use namra_storage::SqliteStorage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::context::{ExecutionResult, StopReason};
+use crate::error::{Result, RuntimeError};
+use crate::executor::{AgentExecutor, AgentExecutorBuilder};
+use crate::llm_factory::LLMFactory;
+use crate::strategy::common::fallback_model;
+use crate::strategy::registry::StrategyRegistry;
+use crate::tool_factory::ToolFactory;
+use crate::workflow::checkpoint::{CheckpointPolicy, PendingStep, WorkflowCheckpoint};
+use crate::workflow::condition::{self, RouteRules};
+use crate::workflow::join::{Arrival, Join};
+use crate::workflow::router::LlmRouter;
+
+/// Outcome of one node of a workflow run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeResult {
+    /// Node ID
+    pub node_id: String,
+
+    /// Input the node was given
+    pub input: String,
+
+    /// Result of every attempt, the last one deciding the outcome
+    pub attempts: Vec<ExecutionResult>,
+}
+
+impl NodeResult {
+    /// Result of the node's last attempt
+    pub fn result(&self) -> &ExecutionResult {
+        self.attempts.last().expect("a node runs at least once")
+    }
+
+    /// Whether the node succeeded
+    pub fn success(&self) -> bool {
+        self.result().success
+    }
+
+    /// Tokens used by every attempt
+    pub fn total_tokens(&self) -> u32 {
+        self.attempts.iter().map(|a| a.total_tokens).sum()
+    }
+
+    /// Cost of every attempt in USD
+    pub fn total_cost(&self) -> f64 {
+        self.attempts.iter().map(|a| a.total_cost).sum()
+    }
+}
+
+/// What a step of a workflow run produced
+enum StepOutput {
+    /// A node ran
+    Ran(Result<NodeResult>),
+    /// The edges of the node result at index `from` chose the next nodes
+    Routed {
+        from: usize,
+        next: Result<(Vec<String>, Option<LLMResponse>)>,
+    },
+}
+
+/// Result of a workflow run
+#[derive(Debug, Clone)]
+pub struct WorkflowResult {
+    /// Workflow run ID
+    pub id: String,
+
+    /// Workflow name
+    pub workflow: String,
+
+    /// Whether the exit point ran successfully
+    pub success: bool,
+
+    /// Response of the exit point
+    pub output: String,
+
+    /// Why the workflow failed
+    pub error: Option<String>,
+
+    /// Nodes in the order they finished
+    pub nodes: Vec<NodeResult>,
+
+    /// Tokens used by every node and LLM router
+    pub total_tokens: u32,
+
+    /// Cost of every node and LLM router in USD
+    pub total_cost: f64,
+
+    /// Execution time in milliseconds
+    pub execution_time_ms: u64,
+}
+
+/// Runs the agent nodes of a workflow
+pub struct WorkflowExecutor {
+    /// Workflow configuration
+    config: WorkflowConfig,
+
+    /// Executors of the agent nodes (node ID -> executor)
+    agents: HashMap<String, AgentExecutor>,
+
+    /// How to choose the nodes after each node (node ID -> route)
+    routes: HashMap<String, Route>,
+
+    /// Join nodes (node ID -> join)
+    joins: HashMap<String, Join>,
+
+    /// Directory `agent_config` paths were resolved against
+    base_dir: PathBuf,
+
+    /// When to save checkpoints
+    checkpoints: CheckpointPolicy,
+
+    /// Database checkpoints are saved to
+    storage: Option<Arc<Mutex<SqliteStorage>>>,
+}
+
+/// Way of choosing the next node, from a node's outgoing edge
+#[derive(Debug)]
+enum Route {
+    /// Always the same nodes, run in parallel when there are several
+    Direct(Vec<String>),
+    /// The node of the first rule that holds
+    Rules(RouteRules),
+    /// The node a model chooses
+    Llm(LlmRouter),
+}
+
+impl Route {
+    /// Route for an edge, checking that its target type and rules are valid
+    fn from_edge(edge: &WorkflowEdge) -> Result<Self> {
+        match edge {
+            WorkflowEdge::Direct { to, .. } => Ok(Route::Direct(vec![to.clone()])),
+            WorkflowEdge::Conditional { to, .. } => match to.target_type.as_str() {
+                "expression" => RouteRules::parse(&to.logic)
+                    .map(Route::Rules)
+                    .map_err(|e| RuntimeError::ConfigError(format!("{:#}", e))),
+                "llm_router" => Ok(Route::Llm(LlmRouter::new(
+                    to.logic.clone(),
+                    to.routes.clone(),
+                )?)),
+                other => Err(RuntimeError::ConfigError(format!(
+                    "Unsupported edge type: {}. Must be one of: expression, llm_router",
+                    other
+                ))),
+            },
+        }
+    }
+
+    /// Nodes the route can choose
+    fn targets(&self) -> Vec<&str> {
+        match self {
+            Route::Direct(nodes) => nodes.iter().map(String::as_str).collect(),
+            Route::Rules(rules) => rules.targets().collect(),
+            Route::Llm(router) => router.targets().collect(),
+        }
+    }
+}
+
+impl WorkflowExecutor {
+    /// Run the workflow from its entry point with `input`
+    ///
+    /// Returns a failed result, keeping the nodes that ran, if a node fails,
+    /// the run exceeds `max_execution_time`, a node has no edge to follow, or
+    /// no branch is left to reach the exit point.
+    pub async fn execute(&self, input: &str) -> Result<WorkflowResult> {
+        let state = WorkflowCheckpoint::new(self.config.clone(), &self.base_dir, input);
+        tracing::info!(workflow = %self.config.name, run_id = %state.run_id, "Starting workflow");
+        self.run(state).await
+    }
+
+    /// Continue a run from its checkpoint
+    ///
+    /// The steps that were pending when the checkpoint was saved run first,
+    /// e.g. a node that failed or timed out, and the run gets a fresh
+    /// `max_execution_time`.
+    pub async fn resume(&self, checkpoint: WorkflowCheckpoint) -> Result<WorkflowResult> {
+        if checkpoint.workflow.name != self.config.name {
+            return Err(RuntimeError::ConfigError(format!(
+                "Checkpoint of run {} is for workflow '{}', not '{}'",
+                checkpoint.run_id, checkpoint.workflow.name, self.config.name
+            )));
+        }
+        if checkpoint.pending.is_empty() {
+            return Err(RuntimeError::Other(format!(
+                "Workflow run {} has nothing left to run",
+                checkpoint.run_id
+            )));
+        }
+        tracing::info!(
+            workflow = %self.config.name,
+            run_id = %checkpoint.run_id,
+            nodes = checkpoint.nodes.len(),
+            "Resuming workflow"
+        );
+        self.run(checkpoint).await
+    }
+
+    /// Run the pending steps of `state` until the exit point has run or a
+    /// step fails, saving checkpoints along the way
+    ///
+    /// Steps run concurrently, so the branches of a fan-out make progress
+    /// at the same time.
+    async fn run(&self, mut state: WorkflowCheckpoint) -> Result<WorkflowResult> {
+        let started = Instant::now();
+        let max_time = parse_duration(&self.config.config.max_execution_time)
+            .map_err(|e| RuntimeError::ConfigError(format!("Invalid max_execution_time: {}", e)))?;
+        let deadline = started + max_time;
+        let elapsed_before = state.elapsed_ms;
+        let mut last_checkpoint = started;
+
+        let start = move |step: PendingStep, nodes: &[NodeResult]| {
+            let routed = match &step {
+                PendingStep::Route { node } => nodes.get(*node).cloned(),
+                PendingStep::Run { .. } => None,
+            };
+            async move {
+                let output = match &step {
+                    PendingStep::Run { node_id, input } => {
+                        StepOutput::Ran(self.run_node(node_id, input, deadline).await)
+                    }
+                    PendingStep::Route { node } => StepOutput::Routed {
+                        from: *node,
+                        next: match routed {
+                            Some(routed) => self.next_nodes(&routed).await,
+                            None => Err(RuntimeError::Other(format!(
+                                "Checkpoint has no node result {}",
+                                node
+                            ))),
+                        },
+                    },
+                };
+                (step, output)
+            }
+        };
+
+        let mut running = FuturesUnordered::new();
+        for step in &state.pending {
+            running.push(start(step.clone(), &state.nodes));
+        }
+
+        let error = loop {
+            if Instant::now() >= deadline {
+                break Some(self.timed_out(max_time));
+            }
+            let Some((step, output)) = running.next().await else {
+                break Some(format!(
+                    "Workflow stopped before its exit point '{}' ran",
+                    self.config.exit_point
+                ));
+            };
+
+            match output {
+                StepOutput::Ran(node) => {
+                    let node = node?;
+                    let node_id = node.node_id.clone();
+                    let outcome = node.result().clone();
+                    state.nodes.push(node);
+
+                    if !outcome.success {
+                        break Some(if Instant::now() >= deadline {
+                            self.timed_out(max_time)
+                        } else {
+                            format!(
+                                "Node '{}' failed: {}",
+                                node_id,
+                                outcome.error.as_deref().unwrap_or("unknown error")
+                            )
+                        });
+                    }
+                    state.finish(&step);
+                    if node_id == self.config.exit_point {
+                        state.pending.clear();
+                        break None;
+                    }
+
+                    let route = PendingStep::Route {
+                        node: state.nodes.len() - 1,
+                    };
+                    state.pending.push(route.clone());
+                    running.push(start(route, &state.nodes));
+
+                    if self.checkpoints.due(last_checkpoint) {
+                        state.elapsed_ms = elapsed_before + started.elapsed().as_millis() as u64;
+                        self.save_checkpoint(&state);
+                        last_checkpoint = Instant::now();
+                    }
+                }
+                StepOutput::Routed { from, next } => {
+                    let (targets, routing) = match next {
+                        Ok(next) => next,
+                        Err(e) => break Some(e.to_string()),
+                    };
+                    let from_id = state.nodes[from].node_id.clone();
+                    let output = state.nodes[from].result().response.clone();
+                    if let Some(response) = routing {
+                        state.routing_tokens +=
+                            response.usage.input_tokens + response.usage.output_tokens;
+                        state.routing_cost += self.routing_cost(&from_id, &response);
+                    }
+
+                    // How many times the node ran before, so that a branch
+                    // of an earlier pass through a loop is told apart
+                    let generation = state.nodes[..from]
+                        .iter()
+                        .filter(|n| n.node_id == from_id)
+                        .count();
+
+                    state.finish(&step);
+                    for to in targets {
+                        let arrival = Arrival {
+                            from: from_id.clone(),
+                            output: output.clone(),
+                        };
+                        if let Some(next) = self.arrive(&mut state, &to, arrival, generation) {
+                            state.pending.push(next.clone());
+                            running.push(start(next, &state.nodes));
+                        }
+                    }
+                }
+            }
+        };
+
+        state.elapsed_ms = elapsed_before + started.elapsed().as_millis() as u64;
+        if self.checkpoints.enabled() {
+            if error.is_none() {
+                self.delete_checkpoint(&state);
+            } else {
+                self.save_checkpoint(&state);
+            }
+        }
+
+        let output = match (&error, state.nodes.last()) {
+            (None, Some(exit)) => exit.result().response.clone(),
+            _ => String::new(),
+        };
+        let nodes = state.nodes;
+        Ok(WorkflowResult {
+            id: state.run_id,
+            workflow: self.config.name.clone(),
+            success: error.is_none(),
+            output,
+            error,
+            total_tokens: nodes.iter().map(NodeResult::total_tokens).sum::<u32>()
+                + state.routing_tokens,
+            total_cost: nodes.iter().map(NodeResult::total_cost).sum::<f64>() + state.routing_cost,
+            execution_time_ms: state.elapsed_ms,
+            nodes,
+        })
+    }
+
+    /// Step for a branch reaching node `to`
+    ///
+    /// A join node collects branch outputs and runs once enough of them have
+    /// arrived; until then there is no step. `generation` is the number of
+    /// times the branch's node ran before: each time a join runs it starts
+    /// a new generation, so a loop back through it joins again, while
+    /// branches of an earlier generation that arrive late are ignored.
+    fn arrive(
+        &self,
+        state: &mut WorkflowCheckpoint,
+        to: &str,
+        arrival: Arrival,
+        generation: usize,
+    ) -> Option<PendingStep> {
+        let Some(join) = self.joins.get(to) else {
+            return Some(PendingStep::Run {
+                node_id: to.to_string(),
+                input: arrival.output,
+            });
+        };
+        if generation < state.generations.get(to).copied().unwrap_or_default() {
+            tracing::debug!(join = to, from = %arrival.from, "Branch arrived after join ran");
+            return None;
+        }
+
+        let arrivals = state.arrivals.entry(to.to_string()).or_default();
+        arrivals.push(arrival);
+        if arrivals.len() < join.wait() {
+            return None;
+        }
+
+        tracing::info!(
+            join = to,
+            branches = arrivals.len(),
+            "Joining workflow branches"
+        );
+        let input = join.merge(arrivals);
+        state.arrivals.remove(to);
+        *state.generations.entry(to.to_string()).or_default() += 1;
+        Some(PendingStep::Run {
+            node_id: to.to_string(),
+            input,
+        })
+    }
+
+    /// Save a checkpoint of `state`; a failure to save doesn't stop the run
+    fn save_checkpoint(&self, state: &WorkflowCheckpoint) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        match state.save(storage) {
+            Ok(()) => tracing::debug!(run_id = %state.run_id, "Saved workflow checkpoint"),
+            Err(e) => tracing::warn!(run_id = %state.run_id, "Could not save checkpoint: {}", e),
+        }
+    }
+
+    /// Remove the checkpoint of a completed run
+    fn delete_checkpoint(&self, state: &WorkflowCheckpoint) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        if let Err(e) = state.delete(storage) {
+            tracing::warn!(run_id = %state.run_id, "Could not delete checkpoint: {}", e);
+        }
+    }
+
+    /// Run one node, retrying it if `retry_failed_nodes` is set
+    ///
+    /// Each attempt is cut off at the node's `timeout` or the workflow's
+    /// deadline, whichever comes first.
+    async fn run_node(&self, node_id: &str, input: &str, deadline: Instant) -> Result<NodeResult> {
+        if self
+            .joins
+            .get(node_id)
+            .is_some_and(|join| join.merge_policy() != MergePolicy::Agent)
+        {
+            // The merged branches are the join's output
+            return Ok(NodeResult {
+                node_id: node_id.to_string(),
+                input: input.to_string(),
+                attempts: vec![merged(input)],
+            });
+        }
+
+        let (node, agent) = self.node(node_id)?;
+        let timeout = node
+            .timeout
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+            .map_err(|e| {
+                RuntimeError::ConfigError(format!("Invalid timeout for node '{}': {}", node_id, e))
+            })?;
+
+        let execution = &self.config.config;
+        let max_attempts = if execution.retry_failed_nodes {
+            execution.max_retries + 1
+        } else {
+            1
+        };
+
+        let mut attempts = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let limit = timeout.map_or(remaining, |t| t.min(remaining));
+
+            tracing::info!(
+                node = node_id,
+                attempt = attempts.len() + 1,
+                "Running workflow node"
+            );
+            let started = Instant::now();
+            let result = match tokio::time::timeout(limit, agent.execute(input, None)).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => failed_attempt(e.to_string(), StopReason::from(&e), started),
+                Err(_) => failed_attempt(
+                    format!("Node timed out after {:.1}s", limit.as_secs_f64()),
+                    StopReason::Timeout,
+                    started,
+                ),
+            };
+
+            let retry = !result.success
+                && retryable(&result.stop_reason)
+                && attempts.len() + 1 < max_attempts as usize
+                && Instant::now() < deadline;
+            attempts.push(result);
+            if !retry {
+                break;
+            }
+            tracing::warn!(node = node_id, "Workflow node failed, retrying");
+        }
+
+        Ok(NodeResult {
+            node_id: node.id.clone(),
+            input: input.to_string(),
+            attempts,
+        })
+    }
+
+    /// The node with `node_id` and its agent
+    fn node(&self, node_id: &str) -> Result<(&WorkflowNode, &AgentExecutor)> {
+        let node = self
+            .config
+            .nodes
+            .iter()
+            .find(|n| n.id == node_id)
+            .ok_or_else(|| RuntimeError::ConfigError(format!("Unknown node: {}", node_id)))?;
+        let agent = self
+            .agents
+            .get(node_id)
+            .ok_or_else(|| RuntimeError::ConfigError(format!("Node '{}' has no agent", node_id)))?;
+        Ok((node, agent))
+    }
+
+    /// The nodes to run after `node`, chosen by its outgoing edges
+    ///
+    /// Also returns the response of an LLM router, for usage accounting.
+    async fn next_nodes(&self, node: &NodeResult) -> Result<(Vec<String>, Option<LLMResponse>)> {
+        let from = node.node_id.as_str();
+        let route = self.routes.get(from).ok_or_else(|| {
+            RuntimeError::ConfigError(format!(
+                "Node '{}' has no outgoing edge and is not the exit point",
+                from
+            ))
+        })?;
+
+        let next = match route {
+            Route::Direct(to) => (to.clone(), None),
+            Route::Rules(rules) => {
+                let to = condition::route(rules, node).ok_or_else(|| {
+                    RuntimeError::Other(format!("No rule of the edge from node '{}' holds", from))
+                })?;
+                (vec![to.to_string()], None)
+            }
+            Route::Llm(router) => {
+                let (_, agent) = self.node(from)?;
+                let (to, response) = router
+                    .route(
+                        agent.llm().as_ref(),
+                        &agent.config().llm.model,
+                        &node.result().response,
+                    )
+                    .await?;
+                (vec![to], Some(response))
+            }
+        };
+
+        tracing::info!(from, to = %next.0.join(", "), "Following workflow edges");
+        Ok(next)
+    }
+
+    /// Estimated cost of an LLM router's response, priced at the model of the
+    /// node the edge starts from, or at the fallback model that answered
+    fn routing_cost(&self, from: &str, response: &LLMResponse) -> f64 {
+        self.agents
+            .get(from)
+            .and_then(|agent| {
+                agent.llm().estimate_cost(
+                    response.usage.input_tokens,
+                    response.usage.output_tokens,
+                    fallback_model(response).unwrap_or(&agent.config().llm.model),
+                )
+            })
+            .unwrap_or(0.0)
+    }
+
+    fn timed_out(&self, max_time: Duration) -> String {
+        format!(
+            "Workflow exceeded max_execution_time of {:.1}s",
+            max_time.as_secs_f64()
+        )
+    }
+
+    /// Get workflow configuration
+    pub fn config(&self) -> &WorkflowConfig {
+        &self.config
+    }
+
+    /// Executor of an agent node
+    pub fn agent(&self, node_id: &str) -> Option<&AgentExecutor> {
+        self.agents.get(node_id)
+    }
+}
+
+/// Routes of a workflow's edges, checking that they lead to its nodes
+fn routes(config: &WorkflowConfig) -> Result<HashMap<String, Route>> {
+    let mut routes = HashMap::new();
+    for edge in &config.edges {
+        let from = edge.source();
+        let route = Route::from_edge(edge).map_err(|e| match e {
+            RuntimeError::ConfigError(message) => {
+                RuntimeError::ConfigError(format!("Invalid edge from node '{}': {}", from, message))
+            }
+            e => e,
+        })?;
+
+        let unknown = route
+            .targets()
+            .into_iter()
+            .find(|to| !config.nodes.iter().any(|n| n.id == *to));
+        if let Some(to) = unknown {
+            return Err(RuntimeError::ConfigError(format!(
+                "Edge from node '{}' leads to unknown node '{}'",
+                from, to
+            )));
+        }
+
+        match (routes.get_mut(from), route) {
+            (None, route) => {
+                routes.insert(from.to_string(), route);
+            }
+            (Some(Route::Direct(targets)), Route::Direct(more)) => targets.extend(more),
+            _ => {
+                return Err(RuntimeError::ConfigError(format!(
+                    "Node '{}' has more than one outgoing edge, which only direct edges can have",
+                    from
+                )))
+            }
+        }
+    }
+    Ok(routes)
+}
+
+/// Joins of a workflow's `join` nodes, from the edges leading to them
+fn joins(
+    config: &WorkflowConfig,
+    routes: &HashMap<String, Route>,
+) -> Result<HashMap<String, Join>> {
+    let mut joins = HashMap::new();
+    for node in config.nodes.iter().filter(|n| n.node_type == "join") {
+        let mut sources: Vec<String> = Vec::new();
+        for from in config.edges.iter().map(WorkflowEdge::source) {
+            let leads_here = routes
+                .get(from)
+                .is_some_and(|route| route.targets().contains(&node.id.as_str()));
+            if leads_here && !sources.iter().any(|s| s == from) {
+                sources.push(from.to_string());
+            }
+        }
+        let join = Join::new(&node.id, sources, &node.join.clone().unwrap_or_default())?;
+        joins.insert(node.id.clone(), join);
+    }
+    Ok(joins)
+}
+
+/// Whether running a node again could change a failure
+fn retryable(reason: &StopReason) -> bool {
+    !matches!(
+        reason,
+        StopReason::BudgetExceeded | StopReason::AwaitingApproval
+    )
+}
+
+/// Result of a join node that merges its branches without an agent
+fn merged(input: &str) -> ExecutionResult {
+    ExecutionResult::success(
+        uuid::Uuid::new_v4().to_string(),
+        input.to_string(),
+        0,
+        Vec::new(),
+        0,
+        0.0,
+        0,
+        Vec::new(),
+    )
+}
+
+/// Result of an attempt that produced no execution result of its own
+fn failed_attempt(error: String, reason: StopReason, started: Instant) -> ExecutionResult {
+    ExecutionResult::failure(
+        uuid::Uuid::new_v4().to_string(),
+        error,
+        0,
+        Vec::new(),
+        0,
+        0.0,
+        started.elapsed().as_millis() as u64,
+        Vec::new(),
+    )
+    .with_stop_reason(reason)
+}
+
+/// Builder for workflow executors
+///
+/// Agent nodes run the executor given with [`agent`](Self::agent), or else
+/// one built from their `agent_config` file, which is resolved against
+/// [`base_dir`](Self::base_dir).
+pub struct WorkflowExecutorBuilder {
+    config: Option<WorkflowConfig>,
+    base_dir: PathBuf,
+    agents: HashMap<String, AgentExecutor>,
+    history: Option<Arc<Mutex<SqliteStorage>>>,
+}
+
+impl WorkflowExecutorBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self {
+            config: None,
+            base_dir: PathBuf::from("."),
+            agents: HashMap::new(),
+            history: None,
+        }
+    }
+
+    /// Set the workflow configuration
+    pub fn workflow(mut self, config: WorkflowConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Resolve relative `agent_config` paths against `dir`
+    ///
+    /// Usually the directory of the workflow file.
+    pub fn base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = dir.into();
+        self
+    }
+
+    /// Run node `node_id` with `executor` instead of its `agent_config`
+    pub fn agent(mut self, node_id: impl Into<String>, executor: AgentExecutor) -> Self {
+        self.agents.insert(node_id.into(), executor);
+        self
+    }
+
+    /// Give the agents built from `agent_config` the run history database
+    ///
+    /// Workflow checkpoints are saved to it as well.
+    pub fn run_history(mut self, history: Arc<Mutex<SqliteStorage>>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Build the workflow executor
+    pub fn build(mut self) -> Result<WorkflowExecutor> {
+        let config = self
+            .config
+            .take()
+            .ok_or_else(|| RuntimeError::ConfigError("Workflow config is required".to_string()))?;
+
+        for point in [&config.entry_point, &config.exit_point] {
+            if !config.nodes.iter().any(|n| &n.id == point) {
+                return Err(RuntimeError::ConfigError(format!(
+                    "Workflow '{}' has no node '{}'",
+                    config.name, point
+                )));
+            }
+        }
+
+        for node in &config.nodes {
+            let needs_agent = match node.node_type.as_str() {
+                "agent" => true,
+                "join" => node
+                    .join
+                    .as_ref()
+                    .is_some_and(|join| join.merge == MergePolicy::Agent),
+                other => {
+                    return Err(RuntimeError::ConfigError(format!(
+                        "Unsupported type '{}' for node '{}'. Must be one of: agent, join",
+                        other, node.id
+                    )))
+                }
+            };
+            if node.join.is_some() && node.node_type != "join" {
+                return Err(RuntimeError::ConfigError(format!(
+                    "Node '{}' has join settings but is not a join node",
+                    node.id
+                )));
+            }
+            if needs_agent && !self.agents.contains_key(&node.id) {
+                let agent = self.load_agent(node)?;
+                self.agents.insert(node.id.clone(), agent);
+            }
+        }
+
+        let routes = routes(&config)?;
+        let joins = joins(&config, &routes)?;
+        let checkpoints = CheckpointPolicy::from_config(config.config.checkpoint.as_ref())?;
+        if checkpoints.enabled() && self.history.is_none() {
+            return Err(RuntimeError::ConfigError(format!(
+                "Workflow '{}' saves checkpoints, which needs the run history database",
+                config.name
+            )));
+        }
+
+        Ok(WorkflowExecutor {
+            config,
+            agents: self.agents,
+            routes,
+            joins,
+            base_dir: self.base_dir,
+            checkpoints,
+            storage: self.history,
+        })
+    }
+
+    /// Build the executor for a node's `agent_config`
+    fn load_agent(&self, node: &WorkflowNode) -> Result<AgentExecutor> {
+        let path = node.agent_config.as_deref().ok_or_else(|| {
+            RuntimeError::ConfigError(format!("Node '{}' has no agent_config", node.id))
+        })?;
+        let path = self.base_dir.join(Path::new(path));
+        let config = load_agent_config(&path)
+            .and_then(|config| validate_config(&config).map(|_| config))
+            .map_err(|e| {
+                RuntimeError::ConfigError(format!(
+                    "Invalid agent config {} for node '{}': {:#}",
+                    path.display(),
+                    node.id,
+                    e
+                ))
+            })?;
+
+        let mut builder = AgentExecutorBuilder::new()
+            .llm(LLMFactory::new().build(&config.llm)?)
+            .tools(ToolFactory::new().build_tools(&config)?)
+            .strategy(StrategyRegistry::new().create(&config)?)
+            .config(config);
+        if let Some(history) = &self.history {
+            builder = builder.run_history(history.clone());
+        }
+        builder.build()
+    }
+}
+
+impl Default for WorkflowExecutorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::react::ReActStrategy;
+    use crate::testing::{test_config, text_response, truncated_response, ScriptedLLM};
+    use namra_llm::types::LLMResponse;
+
+    fn workflow(yaml: &str) -> WorkflowConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn agent(llm: Arc<ScriptedLLM>) -> AgentExecutor {
+        let mut config = test_config();
+        config.execution.max_continuations = 0;
+        AgentExecutorBuilder::new()
+            .config(config)
+            .llm(llm)
+            .strategy(Box::new(ReActStrategy::new()))
+            .build()
+            .unwrap()
+    }
+
+    fn scripted(responses: Vec<LLMResponse>) -> Arc<ScriptedLLM> {
+        Arc::new(ScriptedLLM::new(responses))
+    }
+
+    const PIPELINE: &str = r#"
+name: pipeline
+version: 1.0.0
+entry_point: research
+exit_point: write
+nodes:
+  - {id: research, type: agent}
+  - {id: write, type: agent}
+edges:
+  - {from: research, to: write}
+"#;
+
+    #[tokio::test]
+    async fn test_node_output_is_next_node_input() {
+        let research = scripted(vec![text_response("ANSWER: Rust is fast")]);
+        let write = scripted(vec![text_response("ANSWER: A post about speed")]);
+        let executor = WorkflowExecutorBuilder::new()
+            .workflow(workflow(PIPELINE))
+            .agent("research", agent(research))
+            .agent("write", agent(write.clone()))
+            .build()
+            .unwrap();
+
+        let result = executor.execute("Research Rust").await.unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "A post about speed");
+        assert_eq!(result.total_tokens, 30);
+        let order: Vec<&str> = result.nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(order, ["research", "write"]);
+        assert_eq!(result.nodes[1].input, "Rust is fast");
+        assert_eq!(write.requests()[0].messages[0].content, "Rust is fast");
+    }
+
+    #[tokio::test]
+    async fn test_failed_nodes_are_retried() {
+        let mut config = workflow(PIPELINE);
+        config.config.retry_failed_nodes = true;
+        config.config.max_retries = 1;
+
+        let research = scripted(vec![
+            truncated_response("ANSWER: Rust is"),
+            text_response("ANSWER: Rust is fast"),
+        ]);
+        let write = scripted(vec![
+            truncated_response("ANSWER: A post"),
+            truncated_response("ANSWER: A post about"),
+            text_response("ANSWER: never reached"),
+        ]);
+        let executor = WorkflowExecutorBuilder::new()
+            .workflow(config)
+            .agent("research", agent(research))
+            .agent("write", agent(write))
+            .build()
+            .unwrap();
+
+        let result = executor.execute("Research Rust").await.unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.nodes[0].attempts.len(), 2);
+        assert!(result.nodes[0].success());
+        assert_eq!(result.nodes[1].attempts.len(), 2);
+        assert!(result.error.unwrap().starts_with("Node 'write' failed"));
+        assert_eq!(result.total_tokens, 60);
+    }
+
+    #[tokio::test]
+    async fn test_node_timeout_and_max_execution_time() {
+        let mut config = workflow(PIPELINE);
+        config.nodes[0].timeout = Some("20ms".to_string());
+        let slow = || {
+            Arc::new(
+                ScriptedLLM::new(vec![text_response("ANSWER: late")])
+                    .with_delay(Duration::from_secs(5)),
+            )
+        };
+
+        let executor = WorkflowExecutorBuilder::new()
+            .workflow(config.clone())
+            .agent("research", agent(slow()))
+            .agent("write", agent(scripted(vec![])))
+            .build()
+            .unwrap();
+        let result = executor.execute("Research Rust").await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.nodes.len(), 1);
+        assert_eq!(result.nodes[0].result().stop_reason, StopReason::Timeout);
+
+        config.nodes[0].timeout = None;
+        config.config.max_execution_time = "30ms".to_string();
+        let executor = WorkflowExecutorBuilder::new()
+            .workflow(config)
+            .agent("research", agent(slow()))
+            .agent("write", agent(scripted(vec![])))
+            .build()
+            .unwrap();
+        let result = executor.execute("Research Rust").await.unwrap();
+        assert!(result.error.unwrap().contains("max_execution_time"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_run_resumes_from_checkpoint() {
+        let mut config = workflow(PIPELINE);
+        config.config.checkpoint = Some(namra_config::CheckpointConfig {
+            enabled: true,
+            interval: "node".to_string(),
+        });
+        let storage = Arc::new(Mutex::new(SqliteStorage::open_memory().unwrap()));
+
+        // Checkpoints need somewhere to go
+        assert!(WorkflowExecutorBuilder::new()
+            .workflow(config.clone())
+            .agent("research", agent(scripted(vec![])))
+            .agent("write", agent(scripted(vec![])))
+            .build()
+            .is_err());
+
+        let research = scripted(vec![text_response("ANSWER: Rust is fast")]);
+        let executor = WorkflowExecutorBuilder::new()
+            .workflow(config.clone())
+            .agent("research", agent(research.clone()))
+            .agent(
+                "write",
+                agent(scripted(vec![truncated_response("ANSWER: A")])),
+            )
+            .run_history(storage.clone())
+            .build()
+            .unwrap();
+        let failed = executor.execute("Research Rust").await.unwrap();
+        assert!(!failed.success);
+
+        let checkpoint = WorkflowCheckpoint::load(&storage, &failed.id[..8])
+            .unwrap()
+            .unwrap();
+        assert_eq!(checkpoint.run_id, failed.id);
+        assert_eq!(checkpoint.nodes.len(), 2);
+        assert_eq!(
+            checkpoint.pending,
+            [PendingStep::Run {
+                node_id: "write".to_string(),
+                input: "Rust is fast".to_string(),
+            }]
+        );
+
+        // A checkpoint with a node that never ran can't be resumed
+        let mut broken = checkpoint.clone();
+        broken.run_id = "broken".to_string();
+        broken.nodes[0].attempts.clear();
+        broken.save(&storage).unwrap();
+        let err = WorkflowCheckpoint::load(&storage, "broken").unwrap_err();
+        assert!(err.to_string().contains("node research has no attempts"));
+
+        let write = scripted(vec![text_response("ANSWER: A post about speed")]);
+        let executor = WorkflowExecutorBuilder::new()
+            .workflow(config)
+            .agent("research", agent(research.clone()))
+            .agent("write", agent(write.clone()))
+            .run_history(storage.clone())
+            .build()
+            .unwrap();
+        let result = executor.resume(checkpoint).await.unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.id, failed.id);
+        assert_eq!(result.output, "A post about speed");
+        assert_eq!(route_taken(&result), ["research", "write", "write"]);
+        // Completed nodes are not run again
+        assert_eq!(research.requests().len(), 1);
+        assert_eq!(write.requests()[0].messages[0].content, "Rust is fast");
+        // A completed run has nothing left to resume
+        assert!(WorkflowCheckpoint::load(&storage, &failed.id)
+            .unwrap()
+            .is_none());
+    }
+
+    const TRIAGE: &str = r#"
+name: triage
+version: 1.0.0
+entry_point: triage
+exit_point: reply
+nodes:
+  - {id: triage, type: agent}
+  - {id: billing, type: agent}
+  - {id: technical, type: agent}
+  - {id: reply, type: agent}
+edges:
+  - from: triage
+    to:
+      type: expression
+      logic: |
+        json.team == "billing" => billing
+        else => technical
+  - {from: billing, to: reply}
+  - {from: technical, to: reply}
+"#;
+
+    fn triage(config: WorkflowConfig, triage: Arc<ScriptedLLM>) -> WorkflowExecutor {
+        let specialist = |answer: &str| agent(scripted(vec![text_response(answer)]));
+        WorkflowExecutorBuilder::new()
+            .workflow(config)
+            .agent("triage", agent(triage))
+            .agent("billing", specialist("ANSWER: Refund issued"))
+            .agent("technical", specialist("ANSWER: Bug filed"))
+            .agent("reply", specialist("ANSWER: Thanks for waiting"))
+            .build()
+            .unwrap()
+    }
+
+    fn route_taken(result: &WorkflowResult) -> Vec<&str> {
+        result.nodes.iter().map(|n| n.node_id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_expression_edges_choose_next_node() {
+        let llm = scripted(vec![text_response(r#"ANSWER: {"team": "billing"}"#)]);
+        let result = triage(workflow(TRIAGE), llm)
+            .execute("I was charged twice")
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(route_taken(&result), ["triage", "billing", "reply"]);
+        assert_eq!(result.nodes[1].input, r#"{"team": "billing"}"#);
+
+        let llm = scripted(vec![text_response(r#"ANSWER: {"team": "web"}"#)]);
+        let result = triage(workflow(TRIAGE), llm)
+            .execute("The site is down")
+            .await
+            .unwrap();
+        assert_eq!(route_taken(&result), ["triage", "technical", "reply"]);
+
+        // Without a matching rule the workflow stops
+        let config =
+            workflow(&TRIAGE.replace("else => technical", "json.team == 'web' => technical"));
+        let llm = scripted(vec![text_response("ANSWER: no idea")]);
+        let result = triage(config, llm).execute("Hello").await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("No rule"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_router_chooses_next_node() {
+        let config = workflow(&TRIAGE.replace(
+            r#"type: expression
+      logic: |
+        json.team == "billing" => billing
+        else => technical"#,
+            r#"type: llm_router
+      logic: Send the ticket to the team that can resolve it.
+      routes:
+        billing: Charges, invoices and refunds
+        technical: Bugs and outages"#,
+        ));
+        let llm = scripted(vec![
+            text_response("ANSWER: The customer reports an outage"),
+            text_response("technical"),
+        ]);
+
+        let result = triage(config, llm.clone())
+            .execute("The site is down")
+            .await
+            .unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(route_taken(&result), ["triage", "technical", "reply"]);
+        // Three nodes and the routing request
+        assert_eq!(result.total_tokens, 60);
+        let routing = &llm.requests()[1].messages[1].content;
+        assert!(routing.contains("The customer reports an outage"));
+    }
+
+    const REVIEW: &str = r#"
+name: review
+version: 1.0.0
+entry_point: brief
+exit_point: synthesize
+nodes:
+  - {id: brief, type: agent}
+  - {id: legal, type: agent}
+  - {id: finance, type: agent}
+  - {id: security, type: agent}
+  - {id: combine, type: join, join: {merge: json}}
+  - {id: synthesize, type: agent}
+edges:
+  - {from: brief, to: legal}
+  - {from: brief, to: finance}
+  - {from: brief, to: security}
+  - {from: legal, to: combine}
+  - {from: finance, to: combine}
+  - {from: security, to: combine}
+  - {from: combine, to: synthesize}
+"#;
+
+    fn review(config: WorkflowConfig, delays: [u64; 3]) -> WorkflowExecutorBuilder {
+        let specialist = |answer: &str, delay: u64| {
+            agent(Arc::new(
+                ScriptedLLM::new(vec![text_response(answer)])
+                    .with_delay(Duration::from_millis(delay)),
+            ))
+        };
+        WorkflowExecutorBuilder::new()
+            .workflow(config)
+            .agent(
+                "brief",
+                agent(scripted(vec![text_response("ANSWER: Buy Acme")])),
+            )
+            .agent("legal", specialist("ANSWER: No issues", delays[0]))
+            .agent("finance", specialist("ANSWER: Within budget", delays[1]))
+            .agent("security", specialist("ANSWER: Audit needed", delays[2]))
+    }
+
+    #[tokio::test]
+    async fn test_branches_run_in_parallel_and_join() {
+        let synthesize = scripted(vec![text_response("ANSWER: Go ahead after an audit")]);
+        let executor = review(workflow(REVIEW), [200, 200, 200])
+            .agent("synthesize", agent(synthesize.clone()))
+            .build()
+            .unwrap();
+
+        let result = executor.execute("Should we buy Acme?").await.unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Go ahead after an audit");
+        // Three 200ms branches at the same time
+        assert!(
+            result.execution_time_ms < 500,
+            "{}ms",
+            result.execution_time_ms
+        );
+        let legal = result.nodes.iter().find(|n| n.node_id == "legal").unwrap();
+        assert_eq!(legal.input, "Buy Acme");
+
+        let merged: serde_json::Value =
+            serde_json::from_str(&synthesize.requests()[0].messages[0].content).unwrap();
+        assert_eq!(
+            merged,
+            serde_json::json!({
+                "legal": "No issues",
+                "finance": "Within budget",
+                "security": "Audit needed",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_continues_after_first_branches() {
+        let config = workflow(&REVIEW.replace("{merge: json}", "{first: 2, merge: concat}"));
+        let synthesize = scripted(vec![text_response("ANSWER: Go ahead")]);
+        let executor = review(config, [0, 0, 5000])
+            .agent("synthesize", agent(synthesize.clone()))
+            .build()
+            .unwrap();
+
+        let result = executor.execute("Should we buy Acme?").await.unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.execution_time_ms < 1000);
+        assert!(!route_taken(&result).contains(&"security"));
+        assert_eq!(
+            synthesize.requests()[0].messages[0].content,
+            "No issues\n\nWithin budget"
+        );
+
+        // An aggregator agent merges the branches itself
+        let config = workflow(&REVIEW.replace("{merge: json}", "{merge: agent}"));
+        let combine = scripted(vec![text_response("ANSWER: Approve with an audit")]);
+        let synthesize = scripted(vec![text_response("ANSWER: Go ahead")]);
+        let executor = review(config, [0, 0, 0])
+            .agent("combine", agent(combine.clone()))
+            .agent("synthesize", agent(synthesize.clone()))
+            .build()
+            .unwrap();
+
+        let result = executor.execute("Should we buy Acme?").await.unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        let input = &combine.requests()[0].messages[0].content;
+        assert!(input.starts_with("## legal\nNo issues\n\n## finance"));
+        assert_eq!(
+            synthesize.requests()[0].messages[0].content,
+            "Approve with an audit"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_loops_join_again() {
+        let config = workflow(
+            r#"
+name: revise
+version: 1.0.0
+entry_point: draft
+exit_point: publish
+nodes:
+  - {id: draft, type: agent}
+  - {id: legal, type: agent}
+  - {id: style, type: agent}
+  - {id: combine, type: join}
+  - {id: decide, type: agent}
+  - {id: publish, type: agent}
+edges:
+  - {from: draft, to: legal}
+  - {from: draft, to: style}
+  - {from: legal, to: combine}
+  - {from: style, to: combine}
+  - {from: combine, to: decide}
+  - from: decide
+    to:
+      type: expression
+      logic: |
+        json.approved == "yes" => publish
+        else => draft
+"#,
+        );
+        let twice = |first: &str, second: &str| {
+            agent(scripted(vec![text_response(first), text_response(second)]))
+        };
+        let executor = WorkflowExecutorBuilder::new()
+            .workflow(config)
+            .agent("draft", twice("ANSWER: Draft 1", "ANSWER: Draft 2"))
+            .agent("legal", twice("ANSWER: Legal 1", "ANSWER: Legal 2"))
+            .agent("style", twice("ANSWER: Style 1", "ANSWER: Style 2"))
+            .agent(
+                "decide",
+                twice(
+                    r#"ANSWER: {"approved": "no"}"#,
+                    r#"ANSWER: {"approved": "yes"}"#,
+                ),
+            )
+            .agent(
+                "publish",
+                agent(scripted(vec![text_response("ANSWER: Published")])),
+            )
+            .build()
+            .unwrap();
+
+        let result = executor.execute("Write a post").await.unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        let runs = |id: &str| route_taken(&result).iter().filter(|n| **n == id).count();
+        assert_eq!(runs("combine"), 2);
+        assert_eq!(runs("decide"), 2);
+        let combined: Vec<&str> = result
+            .nodes
+            .iter()
+            .filter(|n| n.node_id == "combine")
+            .map(|n| n.result().response.as_str())
+            .collect();
+        assert_eq!(combined, ["Legal 1\n\nStyle 1", "Legal 2\n\nStyle 2"]);
+    }
+
+    #[test]
+    fn test_build_checks_joins() {
+        let invalid = [
+            REVIEW.replace("{merge: json}", "{first: 4}"),
+            REVIEW.replace(
+                "{id: synthesize, type: agent}",
+                "{id: synthesize, type: agent, join: {}}",
+            ),
+            REVIEW.replace(
+                "{from: brief, to: security}",
+                "{from: brief, to: {type: expression, logic: 'else => security'}}",
+            ),
+        ];
+        for yaml in invalid {
+            let result = review(workflow(&yaml), [0, 0, 0])
+                .agent("synthesize", agent(scripted(vec![])))
+                .build();
+            assert!(result.is_err(), "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn test_build_checks_edges() {
+        let invalid = [
+            TRIAGE.replace("else => technical", "else => sales"),
+            TRIAGE.replace("else => technical", "else technical"),
+            TRIAGE.replace("type: expression", "type: lookup"),
+            TRIAGE.replace("{from: billing, to: reply}", "{from: triage, to: reply}"),
+        ];
+        for yaml in invalid {
+            let result = WorkflowExecutorBuilder::new()
+                .workflow(workflow(&yaml))
+                .agent("triage", agent(scripted(vec![])))
+                .agent("billing", agent(scripted(vec![])))
+                .agent("technical", agent(scripted(vec![])))
+                .agent("reply", agent(scripted(vec![])))
+                .build();
+            assert!(result.is_err(), "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn test_build_checks_nodes() {
+        let mut config = workflow(PIPELINE);
+        config.exit_point = "publish".to_string();
+        assert!(WorkflowExecutorBuilder::new()
+            .workflow(config)
+            .build()
+            .is_err());
+
+        // Agent configs are loaded relative to the base directory
+        let dir = tempfile::tempdir().unwrap();
+        let result = WorkflowExecutorBuilder::new()
+            .workflow(workflow(&PIPELINE.replace(
+                "type: agent}",
+                "type: agent, agent_config: missing.yaml}",
+            )))
+            .base_dir(dir.path())
+            .build();
+        let Err(error) = result else {
+            panic!("missing agent config was accepted")
+        };
+        assert!(error.to_string().contains("missing.yaml"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Joins of parallel workflow branches
+//!
+//! A node with several direct edges runs their targets at the same time. A
+//! `join` node waits for the branches that lead to it, all of them or the
+//! first `join.first`, and runs once with their outputs merged by
+//! `join.merge`.
+
+use namra_config::{JoinConfig, MergePolicy};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, RuntimeError};
+
+/// Output of a branch that reached a join node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Arrival {
+    /// Node the branch reached the join from
+    pub from: String,
+
+    /// That node's response
+    pub output: String,
+}
+
+/// Waits for and merges the branches reaching a join node
+#[derive(Debug)]
+pub struct Join {
+    /// Nodes with an edge into the join, in edge order
+    sources: Vec<String>,
+    /// Branches to wait for
+    wait: usize,
+    merge: MergePolicy,
+}
+
+impl Join {
+    /// Join of the branches from `sources`, checking that it can be reached
+    pub fn new(node_id: &str, sources: Vec<String>, config: &JoinConfig) -> Result<Self> {
+        if sources.is_empty() {
+            return Err(RuntimeError::ConfigError(format!(
+                "Join node '{}' has no incoming edges",
+                node_id
+            )));
+        }
+        let wait = config.first.unwrap_or(sources.len());
+        if wait == 0 || wait > sources.len() {
+            return Err(RuntimeError::ConfigError(format!(
+                "Join node '{}' waits for the first {} branches, but {} edges lead to it",
+                node_id,
+                wait,
+                sources.len()
+            )));
+        }
+        Ok(Self {
+            sources,
+            wait,
+            merge: config.merge,
+        })
+    }
+
+    /// Number of branches the join waits for
+    pub fn wait(&self) -> usize {
+        self.wait
+    }
+
+    /// How the join combines branch outputs
+    pub fn merge_policy(&self) -> MergePolicy {
+        self.merge
+    }
+
+    /// Input of the join node for the branches that arrived
+    ///
+    /// Outputs are taken in the order of the join's incoming edges, so the
+    /// result doesn't depend on which branch finished first.
+    pub fn merge(&self, arrivals: &[Arrival]) -> String {
+        let mut arrivals: Vec<&Arrival> = arrivals.iter().collect();
+        arrivals.sort_by_key(|a| {
+            self.sources
+                .iter()
+                .position(|s| *s == a.from)
+                .unwrap_or(usize::MAX)
+        });
+
+        match self.merge {
+            MergePolicy::Concat => arrivals
+                .iter()
+                .map(|a| a.output.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            MergePolicy::Json => {
+                let outputs: serde_json::Map<String, serde_json::Value> = arrivals
+                    .iter()
+                    .map(|a| (a.from.clone(), serde_json::Value::from(a.output.as_str())))
+                    .collect();
+                serde_json::to_string_pretty(&outputs).unwrap_or_default()
+            }
+            MergePolicy::Agent => arrivals
+                .iter()
+                .map(|a| format!("## {}\n{}", a.from, a.output))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(first: Option<usize>, merge: MergePolicy) -> Result<Join> {
+        Join::new(
+            "combine",
+            vec!["legal".to_string(), "finance".to_string()],
+            &JoinConfig { first, merge },
+        )
+    }
+
+    fn arrivals() -> Vec<Arrival> {
+        // Finance finished first
+        [("finance", "Within budget"), ("legal", "No issues")]
+            .into_iter()
+            .map(|(from, output)| Arrival {
+                from: from.to_string(),
+                output: output.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_policies() {
+        let merged = join(None, MergePolicy::Concat).unwrap().merge(&arrivals());
+        assert_eq!(merged, "No issues\n\nWithin budget");
+
+        let merged = join(None, MergePolicy::Json).unwrap().merge(&arrivals());
+        let merged: serde_json::Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(
+            merged,
+            serde_json::json!({"legal": "No issues", "finance": "Within budget"})
+        );
+
+        let merged = join(None, MergePolicy::Agent).unwrap().merge(&arrivals());
+        assert_eq!(merged, "## legal\nNo issues\n\n## finance\nWithin budget");
+    }
+
+    #[test]
+    fn test_join_waits_for_reachable_branches() {
+        assert_eq!(join(None, MergePolicy::Concat).unwrap().wait(), 2);
+        assert_eq!(join(Some(1), MergePolicy::Concat).unwrap().wait(), 1);
+        assert!(join(Some(0), MergePolicy::Concat).is_err());
+        assert!(join(Some(3), MergePolicy::Concat).is_err());
+        assert!(Join::new("combine", vec![], &JoinConfig::default()).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+//! Workflow execution
+//!
+//! A [`WorkflowExecutor`] runs a [`WorkflowConfig`](namra_config::WorkflowConfig)
+//! graph: it starts at the `entry_point`, runs each agent node with
+//! [`AgentExecutor`](crate::AgentExecutor), and follows the node's edges with
+//! the node's response as the next node's input until the `exit_point` has run.
+//!
+//! A conditional edge chooses the next node from the node's outcome, either
+//! with [`RouteRules`] (`type: expression`) or by asking a model
+//! ([`LlmRouter`], `type: llm_router`).
+//!
+//! A node with several direct edges fans out: their targets run concurrently,
+//! each with the node's response. A `join` node fans back in ([`Join`]),
+//! waiting for all or the first N branches and merging their outputs.
+//!
+//! Nodes are limited by their own `timeout` and the whole run by
+//! `config.max_execution_time`. With `config.retry_failed_nodes` a failed node
+//! is run again, up to `config.max_retries` more times.
+//!
+//! With `config.checkpoint` enabled, the run's progress is saved as a
+//! [`WorkflowCheckpoint`] so that [`WorkflowExecutor::resume`] can continue it.
+
+pub mod checkpoint;
+pub mod condition;
+pub mod executor;
+pub mod join;
+pub mod router;
+
+pub use checkpoint::{PendingStep, WorkflowCheckpoint};
+pub use condition::RouteRules;
+pub use executor::{NodeResult, WorkflowExecutor, WorkflowExecutorBuilder, WorkflowResult};
+pub use join::{Arrival, Join};
+pub use router::LlmRouter;
@@ -0,0 +1,158 @@
+//! LLM routing for `llm_router` edges
+//!
+//! The router shows a model the edge's instructions, the output of the node
+//! the edge starts from, and the labeled nodes it may continue with, and runs
+//! the node whose label the model answers with.
+
+use namra_llm::adapter::LLMAdapter;
+use namra_llm::types::{LLMRequest, LLMResponse, Message};
+use std::collections::BTreeMap;
+
+use crate::error::{Result, RuntimeError};
+
+/// Instructions for the routing request
+const ROUTER_PROMPT: &str = "You route a workflow to its next step. Read the output of the \
+previous step and choose the option that should handle it. Answer with the option's label only.";
+
+/// Most tokens the routing answer may use
+const ROUTER_MAX_TOKENS: u32 = 32;
+
+/// Chooses the next node of a workflow with an LLM
+#[derive(Debug)]
+pub struct LlmRouter {
+    instructions: String,
+    routes: BTreeMap<String, String>,
+}
+
+impl LlmRouter {
+    /// Router choosing among `routes` (node ID -> description)
+    pub fn new(instructions: impl Into<String>, routes: BTreeMap<String, String>) -> Result<Self> {
+        if routes.is_empty() {
+            return Err(RuntimeError::ConfigError(
+                "LLM router has no routes".to_string(),
+            ));
+        }
+        Ok(Self {
+            instructions: instructions.into(),
+            routes,
+        })
+    }
+
+    /// Nodes the router can choose
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.routes.keys().map(String::as_str)
+    }
+
+    /// Ask `model` which node should handle `output`
+    ///
+    /// Returns the chosen node and the routing response, for usage
+    /// accounting. Fails if the answer names none of the routes.
+    pub async fn route(
+        &self,
+        llm: &dyn LLMAdapter,
+        model: &str,
+        output: &str,
+    ) -> Result<(String, LLMResponse)> {
+        let options: Vec<String> = self
+            .routes
+            .iter()
+            .map(|(node, description)| format!("- {}: {}", node, description))
+            .collect();
+        let mut prompt = String::new();
+        if !self.instructions.trim().is_empty() {
+            prompt.push_str(self.instructions.trim());
+            prompt.push_str("\n\n");
+        }
+        prompt.push_str(&format!(
+            "Output of the previous step:\n{}\n\nOptions:\n{}",
+            output,
+            options.join("\n")
+        ));
+
+        let request = LLMRequest::new(
+            model.to_string(),
+            vec![Message::system(ROUTER_PROMPT), Message::user(prompt)],
+        )
+        .with_temperature(0.0)
+        .with_max_tokens(ROUTER_MAX_TOKENS);
+        let response = llm.generate(request).await?;
+
+        let node = self.choice(&response.content).ok_or_else(|| {
+            RuntimeError::Other(format!(
+                "LLM router answered '{}', which is not one of: {}",
+                response.content.trim(),
+                self.targets().collect::<Vec<_>>().join(", ")
+            ))
+        })?;
+        Ok((node.to_string(), response))
+    }
+
+    /// The route an answer names: the whole answer, ignoring case and
+    /// surrounding punctuation, or else the only label it mentions
+    fn choice(&self, answer: &str) -> Option<&str> {
+        let answer = answer
+            .trim()
+            .trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '-');
+        if let Some(node) = self.targets().find(|n| n.eq_ignore_ascii_case(answer)) {
+            return Some(node);
+        }
+
+        let words: Vec<String> = answer
+            .split(|c: char| !c.is_alphanumeric() && c != '_' && c != '-')
+            .map(str::to_lowercase)
+            .collect();
+        let mut mentioned = self.targets().filter(|n| words.contains(&n.to_lowercase()));
+        match (mentioned.next(), mentioned.next()) {
+            (Some(node), None) => Some(node),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{text_response, ScriptedLLM};
+
+    fn router() -> LlmRouter {
+        LlmRouter::new(
+            "Route the support ticket.",
+            BTreeMap::from([
+                ("billing".to_string(), "Invoices and payments".to_string()),
+                ("technical".to_string(), "Bugs and outages".to_string()),
+            ]),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_router_asks_model_for_a_label() {
+        let llm = ScriptedLLM::new(vec![
+            text_response("Technical."),
+            text_response("I would pick billing"),
+            text_response("billing or technical"),
+        ]);
+        let router = router();
+
+        let (node, response) = router
+            .route(&llm, "test-model", "The site is down")
+            .await
+            .unwrap();
+        assert_eq!(node, "technical");
+        assert_eq!(response.usage.input_tokens, 10);
+
+        let prompt = &llm.requests()[0].messages[1].content;
+        assert!(prompt.starts_with("Route the support ticket."));
+        assert!(prompt.contains("The site is down"));
+        assert!(prompt.contains("- billing: Invoices and payments"));
+
+        let (node, _) = router.route(&llm, "test-model", "x").await.unwrap();
+        assert_eq!(node, "billing");
+        assert!(router.route(&llm, "test-model", "x").await.is_err());
+    }
+
+    #[test]
+    fn test_router_needs_routes() {
+        assert!(LlmRouter::new("", BTreeMap::new()).is_err());
+    }
+}
@@ -238,6 +238,7 @@ mod tests {
             completed_at: Utc::now(),
             tool_calls: vec![],
             thoughts: vec![],
+            metadata: Default::default(),
         }];
 
         let exporter = ExcelExporter;
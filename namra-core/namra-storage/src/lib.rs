@@ -31,5 +31,8 @@ pub mod sqlite;
 // Re-exports
 pub use error::{StorageError, StorageResult};
 pub use export::{CsvExporter, ExcelExporter, ExportOptions, Exporter, JsonExporter};
-pub use models::{RunFilter, RunRecord, RunStats, StopReason, ThoughtEntry, ToolCallEntry};
+pub use models::{
+    ApprovalRecord, ApprovalStatus, CacheEntry, RateBucket, RunFilter, RunRecord, RunStats,
+    StopReason, ThoughtEntry, ToolCallEntry,
+};
 pub use sqlite::SqliteStorage;
@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Represents a complete agent run stored in the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +36,10 @@ pub struct RunRecord {
     /// Reasoning steps/thoughts
     #[serde(default)]
     pub thoughts: Vec<ThoughtEntry>,
+
+    /// Strategy- and middleware-specific details (e.g. the executed plan)
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
 }
 
 /// Why the agent execution stopped
@@ -46,6 +51,9 @@ pub enum StopReason {
     Timeout,
     Error,
     UserStop,
+    Truncated,
+    BudgetExceeded,
+    AwaitingApproval,
 }
 
 impl std::fmt::Display for StopReason {
@@ -56,6 +64,9 @@ impl std::fmt::Display for StopReason {
             StopReason::Timeout => write!(f, "timeout"),
             StopReason::Error => write!(f, "error"),
             StopReason::UserStop => write!(f, "user_stop"),
+            StopReason::Truncated => write!(f, "truncated"),
+            StopReason::BudgetExceeded => write!(f, "budget_exceeded"),
+            StopReason::AwaitingApproval => write!(f, "awaiting_approval"),
         }
     }
 }
@@ -70,6 +81,9 @@ impl std::str::FromStr for StopReason {
             "timeout" => Ok(StopReason::Timeout),
             "error" => Ok(StopReason::Error),
             "user_stop" => Ok(StopReason::UserStop),
+            "truncated" => Ok(StopReason::Truncated),
+            "budget_exceeded" => Ok(StopReason::BudgetExceeded),
+            "awaiting_approval" => Ok(StopReason::AwaitingApproval),
             _ => Err(format!("Unknown stop reason: {}", s)),
         }
     }
@@ -111,6 +125,76 @@ pub struct RunFilter {
     pub offset: Option<u32>,
 }
 
+/// A tool call that needs, or received, a human decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub id: String,
+    pub run_id: String,
+    pub agent_name: String,
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+    pub status: ApprovalStatus,
+    /// Why the call was denied, if given
+    pub reason: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+/// Where an approval request stands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Denied,
+}
+
+impl std::fmt::Display for ApprovalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalStatus::Pending => write!(f, "pending"),
+            ApprovalStatus::Approved => write!(f, "approved"),
+            ApprovalStatus::Denied => write!(f, "denied"),
+        }
+    }
+}
+
+impl std::str::FromStr for ApprovalStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(ApprovalStatus::Pending),
+            "approved" => Ok(ApprovalStatus::Approved),
+            "denied" => Ok(ApprovalStatus::Denied),
+            _ => Err(format!("Unknown approval status: {}", s)),
+        }
+    }
+}
+
+/// LLM response kept by the response cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Hash of the normalized request
+    pub key: String,
+    /// Hash of the request settings a semantic match must share
+    pub scope: String,
+    /// Embedding of the conversation, for semantic lookups
+    pub embedding: Option<Vec<f32>>,
+    pub response: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Token bucket a rate limit takes from
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateBucket {
+    pub key: String,
+    /// Most tokens the bucket holds; it starts full
+    pub capacity: f64,
+    pub refill_per_sec: f64,
+}
+
 /// Summary statistics for runs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunStats {
@@ -156,5 +240,13 @@ mod tests {
             "max_iterations".parse::<StopReason>().unwrap(),
             StopReason::MaxIterations
         );
+        assert_eq!(
+            "truncated".parse::<StopReason>().unwrap(),
+            StopReason::Truncated
+        );
+        assert_eq!(
+            "budget_exceeded".parse::<StopReason>().unwrap(),
+            StopReason::BudgetExceeded
+        );
     }
 }
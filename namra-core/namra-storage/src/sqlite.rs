@@ -1,7 +1,10 @@
 //! SQLite storage implementation
 
 use crate::error::{StorageError, StorageResult};
-use crate::models::{RunFilter, RunRecord, RunStats, StopReason, ThoughtEntry, ToolCallEntry};
+use crate::models::{
+    ApprovalRecord, ApprovalStatus, CacheEntry, RateBucket, RunFilter, RunRecord, RunStats,
+    StopReason, ThoughtEntry, ToolCallEntry,
+};
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
 use std::path::{Path, PathBuf};
@@ -24,7 +27,8 @@ CREATE TABLE IF NOT EXISTS runs (
     llm_provider TEXT,
     llm_model TEXT,
     started_at TEXT NOT NULL,
-    completed_at TEXT NOT NULL
+    completed_at TEXT NOT NULL,
+    metadata TEXT
 );
 
 CREATE TABLE IF NOT EXISTS tool_calls (
@@ -50,13 +54,70 @@ CREATE TABLE IF NOT EXISTS thoughts (
     FOREIGN KEY (run_id) REFERENCES runs(id) ON DELETE CASCADE
 );
 
+CREATE TABLE IF NOT EXISTS approvals (
+    id TEXT PRIMARY KEY,
+    run_id TEXT NOT NULL,
+    agent_name TEXT NOT NULL,
+    tool_name TEXT NOT NULL,
+    arguments TEXT NOT NULL,
+    status TEXT NOT NULL,
+    reason TEXT,
+    requested_at TEXT NOT NULL,
+    decided_at TEXT
+);
+
+CREATE TABLE IF NOT EXISTS paused_runs (
+    run_id TEXT PRIMARY KEY,
+    agent_name TEXT NOT NULL,
+    state TEXT NOT NULL,
+    paused_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS workflow_checkpoints (
+    run_id TEXT PRIMARY KEY,
+    workflow_name TEXT NOT NULL,
+    state TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS rate_limits (
+    key TEXT PRIMARY KEY,
+    tokens REAL NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS llm_cache (
+    key TEXT PRIMARY KEY,
+    scope TEXT NOT NULL,
+    embedding TEXT,
+    response TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS memory_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session TEXT NOT NULL,
+    message TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
 CREATE INDEX IF NOT EXISTS idx_runs_agent_name ON runs(agent_name);
 CREATE INDEX IF NOT EXISTS idx_runs_started_at ON runs(started_at);
 CREATE INDEX IF NOT EXISTS idx_runs_success ON runs(success);
 CREATE INDEX IF NOT EXISTS idx_tool_calls_run_id ON tool_calls(run_id);
 CREATE INDEX IF NOT EXISTS idx_thoughts_run_id ON thoughts(run_id);
+CREATE INDEX IF NOT EXISTS idx_approvals_run_id ON approvals(run_id);
+CREATE INDEX IF NOT EXISTS idx_llm_cache_scope ON llm_cache(scope);
+CREATE INDEX IF NOT EXISTS idx_memory_messages_session ON memory_messages(session);
 "#;
 
+/// Columns selected when loading runs, in the order read by `row_to_run`
+const RUN_COLUMNS: &str = r#"id, agent_name, agent_version, input_prompt, response,
+                      success, stop_reason, error_message, iterations,
+                      total_tokens, total_cost, execution_time_ms,
+                      llm_provider, llm_model, started_at, completed_at, metadata"#;
+
 /// SQLite-based storage for agent runs
 pub struct SqliteStorage {
     conn: Connection,
@@ -93,6 +154,7 @@ impl SqliteStorage {
 
         // Initialize schema
         conn.execute_batch(SCHEMA)?;
+        Self::migrate(&conn)?;
 
         Ok(Self { conn })
     }
@@ -102,12 +164,41 @@ impl SqliteStorage {
         let conn = Connection::open_in_memory()?;
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
         conn.execute_batch(SCHEMA)?;
+        Self::migrate(&conn)?;
         Ok(Self { conn })
     }
 
+    /// Bring databases created by older versions up to the current schema
+    fn migrate(conn: &Connection) -> StorageResult<()> {
+        if !Self::has_column(conn, "runs", "metadata")? {
+            conn.execute_batch("ALTER TABLE runs ADD COLUMN metadata TEXT;")
+                .map_err(|e| StorageError::Migration(format!("runs.metadata: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    fn has_column(conn: &Connection, table: &str, column: &str) -> StorageResult<bool> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(names.iter().any(|name| name == column))
+    }
+
     /// Save a run record with its tool calls and thoughts
+    ///
+    /// Updates an earlier record with the same ID, such as a paused run that
+    /// was later resumed, keeping its start time.
     pub fn save_run(&self, run: &RunRecord) -> StorageResult<()> {
         let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM tool_calls WHERE run_id = ?1", params![run.id])?;
+        tx.execute("DELETE FROM thoughts WHERE run_id = ?1", params![run.id])?;
+
+        let metadata = if run.metadata.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&run.metadata)?)
+        };
 
         // Insert run
         tx.execute(
@@ -115,8 +206,25 @@ impl SqliteStorage {
                 id, agent_name, agent_version, input_prompt, response,
                 success, stop_reason, error_message, iterations,
                 total_tokens, total_cost, execution_time_ms,
-                llm_provider, llm_model, started_at, completed_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)"#,
+                llm_provider, llm_model, started_at, completed_at, metadata
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            ON CONFLICT(id) DO UPDATE SET
+                agent_name = excluded.agent_name,
+                agent_version = excluded.agent_version,
+                input_prompt = excluded.input_prompt,
+                response = excluded.response,
+                success = excluded.success,
+                stop_reason = excluded.stop_reason,
+                error_message = excluded.error_message,
+                iterations = excluded.iterations,
+                total_tokens = excluded.total_tokens,
+                total_cost = excluded.total_cost,
+                execution_time_ms = excluded.execution_time_ms,
+                llm_provider = excluded.llm_provider,
+                llm_model = excluded.llm_model,
+                started_at = MIN(runs.started_at, excluded.started_at),
+                completed_at = excluded.completed_at,
+                metadata = excluded.metadata"#,
             params![
                 run.id,
                 run.agent_name,
@@ -134,6 +242,7 @@ impl SqliteStorage {
                 run.llm_model,
                 run.started_at.to_rfc3339(),
                 run.completed_at.to_rfc3339(),
+                metadata,
             ],
         )?;
 
@@ -178,45 +287,11 @@ impl SqliteStorage {
 
     /// Get a run by ID, including tool calls and thoughts
     pub fn get_run(&self, id: &str) -> StorageResult<Option<RunRecord>> {
-        let mut stmt = self.conn.prepare(
-            r#"SELECT id, agent_name, agent_version, input_prompt, response,
-                      success, stop_reason, error_message, iterations,
-                      total_tokens, total_cost, execution_time_ms,
-                      llm_provider, llm_model, started_at, completed_at
-               FROM runs WHERE id = ?1"#,
-        )?;
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM runs WHERE id = ?1", RUN_COLUMNS))?;
 
-        let run = stmt
-            .query_row(params![id], |row| {
-                Ok(RunRecord {
-                    id: row.get(0)?,
-                    agent_name: row.get(1)?,
-                    agent_version: row.get(2)?,
-                    input_prompt: row.get(3)?,
-                    response: row.get(4)?,
-                    success: row.get::<_, i32>(5)? != 0,
-                    stop_reason: row
-                        .get::<_, String>(6)?
-                        .parse()
-                        .unwrap_or(StopReason::Error),
-                    error_message: row.get(7)?,
-                    iterations: row.get(8)?,
-                    total_tokens: row.get(9)?,
-                    total_cost: row.get(10)?,
-                    execution_time_ms: row.get::<_, i64>(11)? as u64,
-                    llm_provider: row.get(12)?,
-                    llm_model: row.get(13)?,
-                    started_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(14)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    completed_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(15)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    tool_calls: vec![],
-                    thoughts: vec![],
-                })
-            })
-            .optional()?;
+        let run = stmt.query_row(params![id], row_to_run).optional()?;
 
         if let Some(mut run) = run {
             // Load tool calls
@@ -286,13 +361,7 @@ impl SqliteStorage {
 
     /// List runs with optional filtering
     pub fn list_runs(&self, filter: &RunFilter) -> StorageResult<Vec<RunRecord>> {
-        let mut sql = String::from(
-            r#"SELECT id, agent_name, agent_version, input_prompt, response,
-                      success, stop_reason, error_message, iterations,
-                      total_tokens, total_cost, execution_time_ms,
-                      llm_provider, llm_model, started_at, completed_at
-               FROM runs WHERE 1=1"#,
-        );
+        let mut sql = format!("SELECT {} FROM runs WHERE 1=1", RUN_COLUMNS);
 
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
 
@@ -330,35 +399,7 @@ impl SqliteStorage {
         let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
         let runs = stmt
-            .query_map(param_refs.as_slice(), |row| {
-                Ok(RunRecord {
-                    id: row.get(0)?,
-                    agent_name: row.get(1)?,
-                    agent_version: row.get(2)?,
-                    input_prompt: row.get(3)?,
-                    response: row.get(4)?,
-                    success: row.get::<_, i32>(5)? != 0,
-                    stop_reason: row
-                        .get::<_, String>(6)?
-                        .parse()
-                        .unwrap_or(StopReason::Error),
-                    error_message: row.get(7)?,
-                    iterations: row.get(8)?,
-                    total_tokens: row.get(9)?,
-                    total_cost: row.get(10)?,
-                    execution_time_ms: row.get::<_, i64>(11)? as u64,
-                    llm_provider: row.get(12)?,
-                    llm_model: row.get(13)?,
-                    started_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(14)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    completed_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(15)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    tool_calls: vec![],
-                    thoughts: vec![],
-                })
-            })?
+            .query_map(param_refs.as_slice(), row_to_run)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(runs)
@@ -369,8 +410,8 @@ impl SqliteStorage {
         let mut sql = String::from(
             r#"SELECT
                 COUNT(*) as total,
-                SUM(CASE WHEN success = 1 THEN 1 ELSE 0 END) as successful,
-                SUM(CASE WHEN success = 0 THEN 1 ELSE 0 END) as failed,
+                COALESCE(SUM(CASE WHEN success = 1 THEN 1 ELSE 0 END), 0) as successful,
+                COALESCE(SUM(CASE WHEN success = 0 THEN 1 ELSE 0 END), 0) as failed,
                 COALESCE(SUM(total_tokens), 0) as tokens,
                 COALESCE(SUM(total_cost), 0.0) as cost,
                 COALESCE(AVG(execution_time_ms), 0.0) as avg_time
@@ -411,6 +452,410 @@ impl SqliteStorage {
         Ok(stats)
     }
 
+    /// Total cost of an agent's runs started at or after `since`
+    ///
+    /// `except_run` leaves out one run, such as a paused run whose cost is
+    /// already counted by the run resuming it.
+    pub fn cost_since(
+        &self,
+        agent_name: &str,
+        since: DateTime<Utc>,
+        except_run: Option<&str>,
+    ) -> StorageResult<f64> {
+        let cost = self.conn.query_row(
+            r#"SELECT COALESCE(SUM(total_cost), 0.0) FROM runs
+               WHERE agent_name = ?1 AND started_at >= ?2 AND id IS NOT ?3"#,
+            params![agent_name, since.to_rfc3339(), except_run],
+            |row| row.get(0),
+        )?;
+        Ok(cost)
+    }
+
+    /// Save an approval request, replacing one with the same ID
+    pub fn save_approval(&self, approval: &ApprovalRecord) -> StorageResult<()> {
+        self.conn.execute(
+            r#"INSERT OR REPLACE INTO approvals (
+                id, run_id, agent_name, tool_name, arguments,
+                status, reason, requested_at, decided_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+            params![
+                approval.id,
+                approval.run_id,
+                approval.agent_name,
+                approval.tool_name,
+                serde_json::to_string(&approval.arguments)?,
+                approval.status.to_string(),
+                approval.reason,
+                approval.requested_at.to_rfc3339(),
+                approval.decided_at.map(|t| t.to_rfc3339()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get an approval request by ID
+    pub fn get_approval(&self, id: &str) -> StorageResult<Option<ApprovalRecord>> {
+        let sql = format!("SELECT {} FROM approvals WHERE id = ?1", APPROVAL_COLUMNS);
+        Ok(self
+            .conn
+            .query_row(&sql, params![id], row_to_approval)
+            .optional()?)
+    }
+
+    /// Latest approval request for a run's call of `tool_name` with `arguments`
+    pub fn find_approval(
+        &self,
+        run_id: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+    ) -> StorageResult<Option<ApprovalRecord>> {
+        let sql = format!(
+            "SELECT {} FROM approvals WHERE run_id = ?1 AND tool_name = ?2 AND arguments = ?3
+             ORDER BY requested_at DESC LIMIT 1",
+            APPROVAL_COLUMNS
+        );
+        Ok(self
+            .conn
+            .query_row(
+                &sql,
+                params![run_id, tool_name, serde_json::to_string(arguments)?],
+                row_to_approval,
+            )
+            .optional()?)
+    }
+
+    /// List approval requests, newest first, optionally only those with `status`
+    pub fn list_approvals(
+        &self,
+        status: Option<ApprovalStatus>,
+    ) -> StorageResult<Vec<ApprovalRecord>> {
+        let mut sql = format!("SELECT {} FROM approvals", APPROVAL_COLUMNS);
+        let mut params: Vec<String> = vec![];
+        if let Some(status) = status {
+            sql.push_str(" WHERE status = ?1");
+            params.push(status.to_string());
+        }
+        sql.push_str(" ORDER BY requested_at DESC");
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let approvals = stmt
+            .query_map(rusqlite::params_from_iter(params), row_to_approval)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(approvals)
+    }
+
+    /// Record a decision on an approval request; false if it doesn't exist
+    pub fn decide_approval(
+        &self,
+        id: &str,
+        status: ApprovalStatus,
+        reason: Option<&str>,
+    ) -> StorageResult<bool> {
+        let rows = self.conn.execute(
+            "UPDATE approvals SET status = ?2, reason = ?3, decided_at = ?4 WHERE id = ?1",
+            params![id, status.to_string(), reason, Utc::now().to_rfc3339()],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Save the state of a run paused on an approval
+    pub fn save_paused_run(
+        &self,
+        run_id: &str,
+        agent_name: &str,
+        state: &serde_json::Value,
+    ) -> StorageResult<()> {
+        self.conn.execute(
+            r#"INSERT OR REPLACE INTO paused_runs (run_id, agent_name, state, paused_at)
+               VALUES (?1, ?2, ?3, ?4)"#,
+            params![
+                run_id,
+                agent_name,
+                serde_json::to_string(state)?,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Saved state of a paused run
+    pub fn get_paused_run(&self, run_id: &str) -> StorageResult<Option<serde_json::Value>> {
+        let state: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT state FROM paused_runs WHERE run_id = ?1",
+                params![run_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(state.map(|s| serde_json::from_str(&s)).transpose()?)
+    }
+
+    /// Remove the saved state of a paused run
+    pub fn delete_paused_run(&self, run_id: &str) -> StorageResult<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM paused_runs WHERE run_id = ?1", params![run_id])?;
+        Ok(deleted > 0)
+    }
+
+    /// Save the checkpoint of a workflow run, replacing any earlier one
+    pub fn save_workflow_checkpoint(
+        &self,
+        run_id: &str,
+        workflow_name: &str,
+        state: &serde_json::Value,
+    ) -> StorageResult<()> {
+        self.conn.execute(
+            r#"INSERT OR REPLACE INTO workflow_checkpoints (run_id, workflow_name, state, updated_at)
+               VALUES (?1, ?2, ?3, ?4)"#,
+            params![
+                run_id,
+                workflow_name,
+                serde_json::to_string(state)?,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Latest checkpoint of the workflow run whose ID is or starts with `id`
+    pub fn get_workflow_checkpoint(&self, id: &str) -> StorageResult<Option<serde_json::Value>> {
+        let state: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT state FROM workflow_checkpoints
+                 WHERE run_id = ?1 OR substr(run_id, 1, length(?1)) = ?1
+                 ORDER BY run_id = ?1 DESC, updated_at DESC LIMIT 1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        state
+            .map(|state| serde_json::from_str(&state))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Delete the checkpoint of a workflow run; false if it has none
+    pub fn delete_workflow_checkpoint(&self, run_id: &str) -> StorageResult<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM workflow_checkpoints WHERE run_id = ?1",
+            params![run_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Take a token from each of `buckets`, or from none of them
+    ///
+    /// A bucket holds up to `capacity` tokens, refills at `refill_per_sec`
+    /// tokens per second, and starts full. Tokens are only taken when every
+    /// bucket has one; otherwise returns the bucket with the longest wait and
+    /// how long until it has a token.
+    pub fn take_rate_tokens<'a>(
+        &self,
+        buckets: &'a [RateBucket],
+        now: DateTime<Utc>,
+    ) -> StorageResult<Option<(&'a RateBucket, std::time::Duration)>> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut levels = Vec::with_capacity(buckets.len());
+        for bucket in buckets {
+            let stored: Option<(f64, String)> = tx
+                .query_row(
+                    "SELECT tokens, updated_at FROM rate_limits WHERE key = ?1",
+                    params![bucket.key],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+
+            let tokens = match stored {
+                Some((tokens, updated_at)) => {
+                    let updated_at = DateTime::parse_from_rfc3339(&updated_at)
+                        .map(|t| t.with_timezone(&Utc))
+                        .unwrap_or(now);
+                    let elapsed = (now - updated_at).to_std().unwrap_or_default();
+                    (tokens + elapsed.as_secs_f64() * bucket.refill_per_sec).min(bucket.capacity)
+                }
+                None => bucket.capacity,
+            };
+            levels.push(tokens);
+        }
+
+        let wait = buckets
+            .iter()
+            .zip(&levels)
+            .filter(|(_, tokens)| **tokens < 1.0)
+            .map(|(bucket, tokens)| {
+                let secs = (1.0 - tokens) / bucket.refill_per_sec;
+                (bucket, std::time::Duration::from_secs_f64(secs))
+            })
+            .max_by_key(|(_, wait)| *wait);
+
+        for (bucket, tokens) in buckets.iter().zip(levels) {
+            let tokens = if wait.is_none() { tokens - 1.0 } else { tokens };
+            tx.execute(
+                "INSERT OR REPLACE INTO rate_limits (key, tokens, updated_at) VALUES (?1, ?2, ?3)",
+                params![bucket.key, tokens, now.to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(wait)
+    }
+
+    /// Store a cached response, replacing any entry with the same key
+    ///
+    /// Expired entries are removed at the same time.
+    pub fn put_cache_entry(&self, entry: &CacheEntry) -> StorageResult<()> {
+        let embedding = entry
+            .embedding
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        self.conn.execute(
+            "DELETE FROM llm_cache WHERE expires_at <= ?1",
+            params![entry.created_at.to_rfc3339()],
+        )?;
+        self.conn.execute(
+            r#"INSERT OR REPLACE INTO llm_cache (key, scope, embedding, response, created_at, expires_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+            params![
+                entry.key,
+                entry.scope,
+                embedding,
+                serde_json::to_string(&entry.response)?,
+                entry.created_at.to_rfc3339(),
+                entry.expires_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Cached response for `key`, unless it has expired by `now`
+    pub fn get_cache_entry(
+        &self,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> StorageResult<Option<CacheEntry>> {
+        self.conn
+            .query_row(
+                r#"SELECT key, scope, embedding, response, created_at, expires_at
+                   FROM llm_cache WHERE key = ?1 AND expires_at > ?2"#,
+                params![key, now.to_rfc3339()],
+                row_to_cache_entry,
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Unexpired cached responses in `scope` that have an embedding
+    pub fn cache_entries_in_scope(
+        &self,
+        scope: &str,
+        now: DateTime<Utc>,
+    ) -> StorageResult<Vec<CacheEntry>> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT key, scope, embedding, response, created_at, expires_at
+               FROM llm_cache
+               WHERE scope = ?1 AND expires_at > ?2 AND embedding IS NOT NULL"#,
+        )?;
+        let entries = stmt
+            .query_map(params![scope, now.to_rfc3339()], row_to_cache_entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Append messages to a conversation memory session, in order
+    pub fn append_memory_messages(
+        &self,
+        session: &str,
+        messages: &[serde_json::Value],
+        created_at: DateTime<Utc>,
+    ) -> StorageResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        Self::insert_memory_messages(&tx, session, messages, created_at)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace every message of a memory session, in one transaction
+    pub fn replace_memory_session(
+        &self,
+        session: &str,
+        messages: &[serde_json::Value],
+        created_at: DateTime<Utc>,
+    ) -> StorageResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM memory_messages WHERE session = ?1",
+            params![session],
+        )?;
+        Self::insert_memory_messages(&tx, session, messages, created_at)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn insert_memory_messages(
+        conn: &Connection,
+        session: &str,
+        messages: &[serde_json::Value],
+        created_at: DateTime<Utc>,
+    ) -> StorageResult<()> {
+        for message in messages {
+            conn.execute(
+                r#"INSERT INTO memory_messages (session, message, created_at)
+                   VALUES (?1, ?2, ?3)"#,
+                params![
+                    session,
+                    serde_json::to_string(message)?,
+                    created_at.to_rfc3339()
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Messages of a memory session stored at or after `since`, oldest first
+    pub fn memory_messages(
+        &self,
+        session: &str,
+        since: DateTime<Utc>,
+    ) -> StorageResult<Vec<serde_json::Value>> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT message FROM memory_messages
+               WHERE session = ?1 AND created_at >= ?2
+               ORDER BY id"#,
+        )?;
+        let messages = stmt
+            .query_map(params![session, since.to_rfc3339()], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(messages
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect())
+    }
+
+    /// Delete every message of a memory session
+    pub fn clear_memory_session(&self, session: &str) -> StorageResult<u64> {
+        let rows = self.conn.execute(
+            "DELETE FROM memory_messages WHERE session = ?1",
+            params![session],
+        )?;
+        Ok(rows as u64)
+    }
+
+    /// Delete memory messages stored before `before`, in every session
+    pub fn delete_memory_before(&self, before: DateTime<Utc>) -> StorageResult<u64> {
+        let rows = self.conn.execute(
+            "DELETE FROM memory_messages WHERE created_at < ?1",
+            params![before.to_rfc3339()],
+        )?;
+        Ok(rows as u64)
+    }
+
     /// Delete a run and all related data
     pub fn delete_run(&self, id: &str) -> StorageResult<bool> {
         let rows = self
@@ -437,9 +882,93 @@ impl SqliteStorage {
     }
 }
 
+/// Map a row selected with `RUN_COLUMNS` to a run record (without tool calls or thoughts)
+fn row_to_run(row: &rusqlite::Row<'_>) -> rusqlite::Result<RunRecord> {
+    Ok(RunRecord {
+        id: row.get(0)?,
+        agent_name: row.get(1)?,
+        agent_version: row.get(2)?,
+        input_prompt: row.get(3)?,
+        response: row.get(4)?,
+        success: row.get::<_, i32>(5)? != 0,
+        stop_reason: row
+            .get::<_, String>(6)?
+            .parse()
+            .unwrap_or(StopReason::Error),
+        error_message: row.get(7)?,
+        iterations: row.get(8)?,
+        total_tokens: row.get(9)?,
+        total_cost: row.get(10)?,
+        execution_time_ms: row.get::<_, i64>(11)? as u64,
+        llm_provider: row.get(12)?,
+        llm_model: row.get(13)?,
+        started_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(14)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        completed_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(15)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        tool_calls: vec![],
+        thoughts: vec![],
+        metadata: row
+            .get::<_, Option<String>>(16)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    })
+}
+
+/// Convert a database row to a CacheEntry
+fn row_to_cache_entry(row: &rusqlite::Row) -> rusqlite::Result<CacheEntry> {
+    let parse_time = |value: String| {
+        DateTime::parse_from_rfc3339(&value)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now())
+    };
+
+    Ok(CacheEntry {
+        key: row.get(0)?,
+        scope: row.get(1)?,
+        embedding: row
+            .get::<_, Option<String>>(2)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        response: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+        created_at: parse_time(row.get(4)?),
+        expires_at: parse_time(row.get(5)?),
+    })
+}
+
+/// Columns selected when loading approvals, in the order read by `row_to_approval`
+const APPROVAL_COLUMNS: &str = r#"id, run_id, agent_name, tool_name, arguments,
+                      status, reason, requested_at, decided_at"#;
+
+/// Convert a database row to an ApprovalRecord
+fn row_to_approval(row: &rusqlite::Row) -> rusqlite::Result<ApprovalRecord> {
+    let parse_time = |value: String| {
+        DateTime::parse_from_rfc3339(&value)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now())
+    };
+
+    Ok(ApprovalRecord {
+        id: row.get(0)?,
+        run_id: row.get(1)?,
+        agent_name: row.get(2)?,
+        tool_name: row.get(3)?,
+        arguments: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+        status: row
+            .get::<_, String>(5)?
+            .parse()
+            .unwrap_or(ApprovalStatus::Pending),
+        reason: row.get(6)?,
+        requested_at: parse_time(row.get(7)?),
+        decided_at: row.get::<_, Option<String>>(8)?.map(parse_time),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     fn create_test_run() -> RunRecord {
         RunRecord {
@@ -478,6 +1007,7 @@ mod tests {
                 content: "Thinking about the problem...".to_string(),
                 timestamp: Utc::now(),
             }],
+            metadata: HashMap::new(),
         }
     }
 
@@ -555,6 +1085,311 @@ mod tests {
         assert!((stats.total_cost - 0.1).abs() < 0.001);
     }
 
+    #[test]
+    fn test_cost_since() {
+        let storage = SqliteStorage::open_memory().unwrap();
+
+        let mut old = create_test_run();
+        old.started_at = Utc::now() - chrono::Duration::hours(2);
+        old.total_cost = 5.0;
+        storage.save_run(&old).unwrap();
+
+        let mut other_agent = create_test_run();
+        other_agent.agent_name = "other".to_string();
+        other_agent.total_cost = 3.0;
+        storage.save_run(&other_agent).unwrap();
+
+        for _ in 0..2 {
+            let mut recent = create_test_run();
+            recent.total_cost = 0.25;
+            storage.save_run(&recent).unwrap();
+        }
+
+        let hour_ago = Utc::now() - chrono::Duration::hours(1);
+        let spent = storage.cost_since("test_agent", hour_ago, None).unwrap();
+        assert!((spent - 0.5).abs() < 1e-9);
+        assert_eq!(storage.cost_since("nobody", hour_ago, None).unwrap(), 0.0);
+
+        let excepted = storage
+            .cost_since("test_agent", hour_ago, Some(&other_agent.id))
+            .unwrap();
+        assert!((excepted - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_resaving_a_run_updates_it() {
+        let storage = SqliteStorage::open_memory().unwrap();
+
+        let mut run = create_test_run();
+        run.stop_reason = StopReason::AwaitingApproval;
+        run.total_cost = 0.1;
+        storage.save_run(&run).unwrap();
+        let started_at = run.started_at;
+
+        run.stop_reason = StopReason::Completed;
+        run.total_cost = 0.3;
+        run.started_at = Utc::now();
+        storage.save_run(&run).unwrap();
+
+        let saved = storage.get_run(&run.id).unwrap().unwrap();
+        assert_eq!(saved.stop_reason, StopReason::Completed);
+        assert_eq!(saved.total_cost, 0.3);
+        assert_eq!(saved.started_at, started_at);
+        assert_eq!(saved.tool_calls.len(), run.tool_calls.len());
+        assert_eq!(storage.list_runs(&RunFilter::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_take_rate_tokens() {
+        let storage = SqliteStorage::open_memory().unwrap();
+        let start = Utc::now();
+        // Two tokens, refilling one per second
+        let bucket = |key: &str| RateBucket {
+            key: key.to_string(),
+            capacity: 2.0,
+            refill_per_sec: 1.0,
+        };
+        let a = [bucket("a")];
+        let take = |buckets, now| storage.take_rate_tokens(buckets, now).unwrap();
+
+        assert_eq!(take(&a, start), None);
+        assert_eq!(take(&a, start), None);
+        let (empty, wait) = take(&a, start).unwrap();
+        assert_eq!(empty.key, "a");
+        assert_eq!(wait, std::time::Duration::from_secs(1));
+
+        // Other keys have their own bucket, but a token is only taken when
+        // every bucket has one
+        let both = [bucket("b"), bucket("a")];
+        assert_eq!(take(&both, start).unwrap().0.key, "a");
+        let b = [bucket("b")];
+        assert_eq!(take(&b, start), None);
+        assert_eq!(take(&b, start), None);
+
+        let later = start + chrono::Duration::milliseconds(1500);
+        assert_eq!(take(&a, later), None);
+        let (_, wait) = take(&a, later).unwrap();
+        assert!(wait.as_millis().abs_diff(500) < 5);
+    }
+
+    #[test]
+    fn test_workflow_checkpoints() {
+        let storage = SqliteStorage::open_memory().unwrap();
+        let id = "4f9c2d1e-0000-4000-8000-000000000000";
+
+        storage
+            .save_workflow_checkpoint(id, "pipeline", &serde_json::json!({"step": 1}))
+            .unwrap();
+        storage
+            .save_workflow_checkpoint(id, "pipeline", &serde_json::json!({"step": 2}))
+            .unwrap();
+
+        let state = storage.get_workflow_checkpoint(id).unwrap().unwrap();
+        assert_eq!(state["step"], 2);
+        let state = storage
+            .get_workflow_checkpoint("4f9c2d1e")
+            .unwrap()
+            .unwrap();
+        assert_eq!(state["step"], 2);
+        assert!(storage.get_workflow_checkpoint("ffff").unwrap().is_none());
+
+        assert!(storage.delete_workflow_checkpoint(id).unwrap());
+        assert!(!storage.delete_workflow_checkpoint(id).unwrap());
+        assert!(storage.get_workflow_checkpoint(id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_memory_messages() {
+        let storage = SqliteStorage::open_memory().unwrap();
+        let earlier = Utc::now() - chrono::Duration::hours(2);
+        let now = Utc::now();
+
+        storage
+            .append_memory_messages("support:a", &[serde_json::json!({"n": 1})], earlier)
+            .unwrap();
+        storage
+            .append_memory_messages(
+                "support:a",
+                &[serde_json::json!({"n": 2}), serde_json::json!({"n": 3})],
+                now,
+            )
+            .unwrap();
+        storage
+            .append_memory_messages("support:b", &[serde_json::json!({"n": 4})], now)
+            .unwrap();
+
+        let all = storage.memory_messages("support:a", earlier).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0]["n"], 1);
+
+        let hour_ago = now - chrono::Duration::hours(1);
+        assert_eq!(storage.delete_memory_before(hour_ago).unwrap(), 1);
+        let recent = storage.memory_messages("support:a", earlier).unwrap();
+        assert_eq!(
+            recent,
+            vec![serde_json::json!({"n": 2}), serde_json::json!({"n": 3})]
+        );
+
+        storage
+            .replace_memory_session("support:a", &[serde_json::json!({"n": 5})], now)
+            .unwrap();
+        assert_eq!(
+            storage.memory_messages("support:a", earlier).unwrap(),
+            vec![serde_json::json!({"n": 5})]
+        );
+
+        assert_eq!(storage.clear_memory_session("support:a").unwrap(), 1);
+        assert_eq!(
+            storage.memory_messages("support:b", earlier).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_cache_entries_expire() {
+        let storage = SqliteStorage::open_memory().unwrap();
+        let now = Utc::now();
+        let entry = |key: &str, embedding: Option<Vec<f32>>, ttl_secs: i64| CacheEntry {
+            key: key.to_string(),
+            scope: "scope-a".to_string(),
+            embedding,
+            response: serde_json::json!({"content": key}),
+            created_at: now,
+            expires_at: now + chrono::Duration::seconds(ttl_secs),
+        };
+
+        storage
+            .put_cache_entry(&entry("a", Some(vec![1.0, 0.0]), 60))
+            .unwrap();
+        storage.put_cache_entry(&entry("b", None, 60)).unwrap();
+        storage
+            .put_cache_entry(&entry("c", Some(vec![0.0, 1.0]), 1))
+            .unwrap();
+
+        let found = storage.get_cache_entry("a", now).unwrap().unwrap();
+        assert_eq!(found.embedding, Some(vec![1.0, 0.0]));
+        assert_eq!(found.response["content"], "a");
+
+        // Only unexpired entries with an embedding are semantic candidates
+        let later = now + chrono::Duration::seconds(5);
+        let keys: Vec<String> = storage
+            .cache_entries_in_scope("scope-a", later)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(keys, vec!["a"]);
+        assert!(storage.get_cache_entry("c", later).unwrap().is_none());
+        assert!(storage
+            .cache_entries_in_scope("scope-b", now)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_save_run_replaces_existing() {
+        let storage = SqliteStorage::open_memory().unwrap();
+        let mut run = create_test_run();
+        run.stop_reason = StopReason::AwaitingApproval;
+        storage.save_run(&run).unwrap();
+
+        run.stop_reason = StopReason::Completed;
+        storage.save_run(&run).unwrap();
+
+        let retrieved = storage.get_run(&run.id).unwrap().unwrap();
+        assert_eq!(retrieved.stop_reason, StopReason::Completed);
+        assert_eq!(retrieved.tool_calls.len(), 1);
+        assert_eq!(storage.count_runs().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_approvals() {
+        let storage = SqliteStorage::open_memory().unwrap();
+        let arguments = serde_json::json!({"path": "/etc/hosts"});
+        let approval = ApprovalRecord {
+            id: "approval-1".to_string(),
+            run_id: "run-1".to_string(),
+            agent_name: "test_agent".to_string(),
+            tool_name: "delete_file".to_string(),
+            arguments: arguments.clone(),
+            status: ApprovalStatus::Pending,
+            reason: None,
+            requested_at: Utc::now(),
+            decided_at: None,
+        };
+        storage.save_approval(&approval).unwrap();
+
+        let found = storage
+            .find_approval("run-1", "delete_file", &arguments)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, "approval-1");
+        assert!(storage
+            .find_approval("run-1", "delete_file", &serde_json::json!({}))
+            .unwrap()
+            .is_none());
+
+        assert!(storage
+            .decide_approval("approval-1", ApprovalStatus::Denied, Some("too risky"))
+            .unwrap());
+        assert!(!storage
+            .decide_approval("missing", ApprovalStatus::Approved, None)
+            .unwrap());
+
+        let denied = storage.get_approval("approval-1").unwrap().unwrap();
+        assert_eq!(denied.status, ApprovalStatus::Denied);
+        assert_eq!(denied.reason.as_deref(), Some("too risky"));
+        assert!(denied.decided_at.is_some());
+        assert!(storage
+            .list_approvals(Some(ApprovalStatus::Pending))
+            .unwrap()
+            .is_empty());
+        assert_eq!(storage.list_approvals(None).unwrap().len(), 1);
+
+        let state = serde_json::json!({"messages": []});
+        storage
+            .save_paused_run("run-1", "test_agent", &state)
+            .unwrap();
+        assert_eq!(storage.get_paused_run("run-1").unwrap(), Some(state));
+        assert!(storage.delete_paused_run("run-1").unwrap());
+        assert_eq!(storage.get_paused_run("run-1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let storage = SqliteStorage::open_memory().unwrap();
+        let mut run = create_test_run();
+        run.metadata.insert(
+            "plan".to_string(),
+            serde_json::json!({"steps": [{"id": 1, "description": "Fetch"}]}),
+        );
+
+        storage.save_run(&run).unwrap();
+
+        let retrieved = storage.get_run(&run.id).unwrap().unwrap();
+        assert_eq!(retrieved.metadata["plan"]["steps"][0]["id"], 1);
+
+        let listed = storage.list_runs(&RunFilter::default()).unwrap();
+        assert_eq!(listed[0].metadata, run.metadata);
+    }
+
+    #[test]
+    fn test_migrates_old_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runs.db");
+
+        // Database created before the metadata column existed
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(&SCHEMA.replace(",\n    metadata TEXT", ""))
+            .unwrap();
+        assert!(!SqliteStorage::has_column(&conn, "runs", "metadata").unwrap());
+        drop(conn);
+
+        let storage = SqliteStorage::open(&path).unwrap();
+        storage.save_run(&create_test_run()).unwrap();
+        assert!(SqliteStorage::has_column(&storage.conn, "runs", "metadata").unwrap());
+    }
+
     #[test]
     fn test_delete_run() {
         let storage = SqliteStorage::open_memory().unwrap();
@@ -1,19 +1,30 @@
 //! HTTP tool for making web requests
 
 use async_trait::async_trait;
-use reqwest::{Client, Method};
-use serde_json::{json, Value};
+use reqwest::header::LOCATION;
+use reqwest::{redirect, Client, Method, RequestBuilder, StatusCode, Url};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
 use std::time::Duration;
 
 use crate::error::{Result, ToolError};
 use crate::tool::{Tool, ToolOutput, ToolTimer};
+use crate::url_policy::UrlPolicy;
+
+/// Most redirects followed for one request
+const MAX_REDIRECTS: usize = 10;
+
+/// Headers dropped when a redirect leaves the original host
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "cookie", "proxy-authorization"];
 
 /// HTTP tool for making REST API calls
+///
+/// Every URL, including each redirect, is checked against the tool's
+/// [`UrlPolicy`] before it is requested.
 pub struct HttpTool {
     client: Client,
-    #[allow(dead_code)]
     default_timeout: Duration,
+    policy: UrlPolicy,
 }
 
 impl HttpTool {
@@ -24,15 +35,112 @@ impl HttpTool {
 
     /// Create a new HTTP tool with custom timeout
     pub fn with_timeout(timeout: Duration) -> Self {
-        let client = Client::builder()
+        let policy = UrlPolicy::default();
+        Self {
+            client: Self::client(timeout, &policy),
+            default_timeout: timeout,
+            policy,
+        }
+    }
+
+    /// Check requested URLs against `policy` instead of the default
+    pub fn with_policy(mut self, policy: UrlPolicy) -> Self {
+        self.client = Self::client(self.default_timeout, &policy);
+        self.policy = policy;
+        self
+    }
+
+    fn client(timeout: Duration, policy: &UrlPolicy) -> Client {
+        // Redirects are followed by hand so each one can be checked, and
+        // hosts resolve through the policy so requests reach checked addresses
+        Client::builder()
             .timeout(timeout)
+            .redirect(redirect::Policy::none())
+            .dns_resolver(policy.resolver())
             .build()
-            .expect("Failed to create HTTP client");
+            .expect("Failed to create HTTP client")
+    }
 
-        Self {
-            client,
-            default_timeout: timeout,
+    /// Build one request of a possibly redirected exchange
+    fn build_request(
+        &self,
+        method: Method,
+        url: Url,
+        headers: &Map<String, Value>,
+        body: Option<&str>,
+    ) -> RequestBuilder {
+        let mut request = self.client.request(method, url);
+
+        for (key, value) in headers {
+            if let Some(value_str) = value.as_str() {
+                request = request.header(key, value_str);
+            }
         }
+
+        // Add body if provided (for POST, PUT, PATCH)
+        if let Some(body) = body {
+            request = request.body(body.to_string());
+            // Set content-type if not already set
+            if !headers.contains_key("Content-Type") {
+                request = request.header("Content-Type", "application/json");
+            }
+        }
+
+        request
+    }
+
+    /// Send a request, checking and following redirects
+    ///
+    /// 303 responses, and 301/302 responses to a POST, are followed with a
+    /// GET without a body, as browsers do. Credentials are not sent on to
+    /// other hosts.
+    async fn send(
+        &self,
+        mut method: Method,
+        mut url: Url,
+        mut headers: Map<String, Value>,
+        mut body: Option<String>,
+    ) -> Result<(reqwest::Response, Url)> {
+        for _ in 0..=MAX_REDIRECTS {
+            self.policy.check(&url).await?;
+
+            let response = self
+                .build_request(method.clone(), url.clone(), &headers, body.as_deref())
+                .send()
+                .await
+                .map_err(request_error)?;
+
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|l| l.to_str().ok());
+            let next = match location {
+                Some(location) if response.status().is_redirection() => {
+                    url.join(location).map_err(|e| {
+                        ToolError::HttpError(format!("Invalid redirect to '{}': {}", location, e))
+                    })?
+                }
+                _ => return Ok((response, url)),
+            };
+
+            let status = response.status();
+            if status == StatusCode::SEE_OTHER
+                || (matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+                    && method == Method::POST)
+            {
+                method = Method::GET;
+                body = None;
+            }
+            if next.host_str() != url.host_str() {
+                headers.retain(|key, _| !CREDENTIAL_HEADERS.contains(&key.to_lowercase().as_str()));
+            }
+            url = next;
+        }
+
+        Err(ToolError::HttpError(format!(
+            "Too many redirects (more than {})",
+            MAX_REDIRECTS
+        )))
     }
 
     /// Parse method string to reqwest Method
@@ -118,35 +226,13 @@ impl Tool for HttpTool {
             .ok_or_else(|| ToolError::InvalidInput("Missing 'url' field".to_string()))?;
 
         let method = Self::parse_method(method_str)?;
-
-        // Build request
-        let mut request = self.client.request(method.clone(), url);
-
-        // Add headers if provided
-        if let Some(headers_obj) = input["headers"].as_object() {
-            for (key, value) in headers_obj {
-                if let Some(value_str) = value.as_str() {
-                    request = request.header(key, value_str);
-                }
-            }
-        }
-
-        // Add body if provided (for POST, PUT, PATCH)
-        if let Some(body) = input["body"].as_str() {
-            request = request.body(body.to_string());
-            // Set content-type if not already set
-            if input["headers"].is_null()
-                || !input["headers"]
-                    .as_object()
-                    .map(|h| h.contains_key("Content-Type"))
-                    .unwrap_or(false)
-            {
-                request = request.header("Content-Type", "application/json");
-            }
-        }
+        let parsed_url = Url::parse(url)
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid URL '{}': {}", url, e)))?;
+        let headers = input["headers"].as_object().cloned().unwrap_or_default();
+        let body = input["body"].as_str().map(str::to_string);
 
         // Execute request
-        let response = request.send().await?;
+        let (response, final_url) = self.send(method, parsed_url, headers, body).await?;
 
         // Extract metadata
         let status = response.status();
@@ -164,6 +250,7 @@ impl Tool for HttpTool {
             "status_text": status.canonical_reason().unwrap_or("Unknown"),
             "headers": headers,
             "url": url,
+            "final_url": final_url.as_str(),
             "method": method_str,
         });
 
@@ -173,6 +260,18 @@ impl Tool for HttpTool {
     }
 }
 
+/// The policy's refusal if the resolver rejected the host, else the HTTP error
+fn request_error(err: reqwest::Error) -> ToolError {
+    let mut source = std::error::Error::source(&err);
+    while let Some(cause) = source {
+        if let Some(ToolError::PermissionDenied(reason)) = cause.downcast_ref::<ToolError>() {
+            return ToolError::PermissionDenied(reason.clone());
+        }
+        source = cause.source();
+    }
+    err.into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -268,6 +367,84 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// Serve one canned HTTP response per connection on a local port
+    async fn serve(responses: Vec<String>) -> std::net::SocketAddr {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_http_redirects_are_checked() {
+        let addr = serve(vec![
+            "HTTP/1.1 302 Found\r\nLocation: /next\r\nContent-Length: 0\r\n\r\n".to_string(),
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok".to_string(),
+            "HTTP/1.1 302 Found\r\nLocation: http://localhost/secret\r\nContent-Length: 0\r\n\r\n"
+                .to_string(),
+        ])
+        .await;
+
+        let tool = HttpTool::new().with_policy(
+            UrlPolicy::new()
+                .allow_private_networks(true)
+                .blocked_domains(["localhost"]),
+        );
+
+        let output = tool
+            .execute(json!({"method": "GET", "url": format!("http://{}/start", addr)}))
+            .await
+            .unwrap();
+        assert_eq!(output.content, "ok");
+        assert_eq!(
+            output.metadata.unwrap()["final_url"],
+            format!("http://{}/next", addr)
+        );
+
+        let result = tool
+            .execute(json!({"method": "GET", "url": format!("http://{}/start", addr)}))
+            .await;
+        assert!(matches!(result, Err(ToolError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_http_connects_only_to_checked_addresses() {
+        let addr = serve(vec![]).await;
+
+        // As if the host resolved to a public address for the check and to
+        // a private one for the request
+        let tool = HttpTool {
+            policy: UrlPolicy::new().allow_private_networks(true),
+            ..HttpTool::new()
+        };
+
+        let result = tool
+            .execute(json!({"method": "GET", "url": format!("http://localhost:{}/", addr.port())}))
+            .await;
+        assert!(matches!(result, Err(ToolError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_http_blocks_private_addresses_by_default() {
+        let tool = HttpTool::new();
+        let result = tool
+            .execute(json!({
+                "method": "GET",
+                "url": "http://169.254.169.254/latest/meta-data/"
+            }))
+            .await;
+        assert!(matches!(result, Err(ToolError::PermissionDenied(_))));
+    }
+
     #[tokio::test]
     async fn test_http_with_headers() {
         let tool = HttpTool::new();
@@ -16,7 +16,9 @@
 //! # Built-in Tools
 //!
 //! ## HTTP Tool
-//! Make HTTP requests (GET, POST, PUT, DELETE, PATCH)
+//! Make HTTP requests (GET, POST, PUT, DELETE, PATCH). Requests and
+//! redirects are checked against a [`UrlPolicy`], which blocks private
+//! network addresses by default.
 //!
 //! ```rust,no_run
 //! use namra_tools::{HttpTool, Tool};
@@ -91,6 +93,7 @@ pub mod error;
 pub mod filesystem;
 pub mod http;
 pub mod tool;
+pub mod url_policy;
 
 // Re-export commonly used types
 pub use builtin::{CalculatorTool, StringTool};
@@ -101,6 +104,7 @@ pub use filesystem::{
 };
 pub use http::HttpTool;
 pub use tool::{Tool, ToolOutput, ToolTimer};
+pub use url_policy::UrlPolicy;
 
 // Re-export filesystem backend trait and types for tool factory
 pub use filesystem::backend::FileSystemBackend;
@@ -0,0 +1,370 @@
+//! Checks on the URLs the HTTP tools are allowed to request
+//!
+//! Models choose the URLs they fetch, so a prompt-injected agent can be
+//! pointed at internal services or cloud metadata endpoints. A [`UrlPolicy`]
+//! checks each URL (including every redirect) against domain allow and block
+//! lists and, unless private networks are allowed, rejects hosts that resolve
+//! to loopback, private, link-local, or other non-public addresses.
+//!
+//! Checking a name and then letting the HTTP client resolve it again would
+//! let a host answer with a public address for the check and a private one
+//! for the request (DNS rebinding), so clients resolve hosts through
+//! [`UrlPolicy::resolver`], which applies the same address rules to the
+//! addresses they connect to.
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::Url;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+use crate::error::{Result, ToolError};
+
+/// Domain lists and address rules applied before each HTTP request
+///
+/// Domain patterns are either exact host names (`api.example.com`) or a
+/// wildcard for subdomains (`*.example.com`, which does not match
+/// `example.com` itself). `*` matches every host. Blocked domains take
+/// precedence over allowed ones; an empty allow list allows every host.
+#[derive(Debug, Clone, Default)]
+pub struct UrlPolicy {
+    allowed_domains: Vec<String>,
+    blocked_domains: Vec<String>,
+    allow_private_networks: bool,
+    trusted_hosts: HashSet<String>,
+}
+
+impl UrlPolicy {
+    /// Policy that allows any public host and blocks private addresses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only allow hosts matching one of `domains`
+    pub fn allowed_domains<S: AsRef<str>>(mut self, domains: impl IntoIterator<Item = S>) -> Self {
+        self.allowed_domains = normalize(domains);
+        self
+    }
+
+    /// Reject hosts matching any of `domains`
+    pub fn blocked_domains<S: AsRef<str>>(mut self, domains: impl IntoIterator<Item = S>) -> Self {
+        self.blocked_domains = normalize(domains);
+        self
+    }
+
+    /// Allow hosts that resolve to private, loopback, or link-local addresses
+    pub fn allow_private_networks(mut self, allow: bool) -> Self {
+        self.allow_private_networks = allow;
+        self
+    }
+
+    /// Exempt `host` from the allow list and the private address check
+    ///
+    /// For hosts written by the operator rather than chosen by the model,
+    /// such as the base URL of a configured HTTP tool. Blocked domains still
+    /// apply.
+    pub fn trust_host(mut self, host: &str) -> Self {
+        self.trusted_hosts.insert(host.to_lowercase());
+        self
+    }
+
+    /// Check a URL, resolving its host to check the addresses it points to
+    pub async fn check(&self, url: &Url) -> Result<()> {
+        let host = self.check_name(url)?;
+        if self.allow_private_networks || self.trusted_hosts.contains(&host) {
+            return Ok(());
+        }
+
+        match url.host() {
+            Some(url::Host::Ipv4(ip)) => check_addrs(&host, [IpAddr::V4(ip)]),
+            Some(url::Host::Ipv6(ip)) => check_addrs(&host, [IpAddr::V6(ip)]),
+            _ => {
+                let port = url.port_or_known_default().unwrap_or(80);
+                self.resolve(&host, port).await.map(|_| ())
+            }
+        }
+    }
+
+    /// DNS resolver for HTTP clients that only returns addresses the policy
+    /// allows
+    ///
+    /// Requests then connect to the addresses that were checked, whatever
+    /// the host's DNS answers in between.
+    pub fn resolver(&self) -> Arc<PolicyResolver> {
+        Arc::new(PolicyResolver {
+            policy: self.clone(),
+        })
+    }
+
+    /// Resolve `host`, failing if it points to an address the policy rejects
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(|e| ToolError::HttpError(format!("Cannot resolve host '{}': {}", host, e)))?
+            .collect();
+
+        if !self.allow_private_networks && !self.trusted_hosts.contains(&host) {
+            check_addrs(&host, addrs.iter().map(SocketAddr::ip))?;
+        }
+        Ok(addrs)
+    }
+
+    /// Check the scheme and host name against the domain lists
+    ///
+    /// Returns the lowercased host.
+    fn check_name(&self, url: &Url) -> Result<String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ToolError::PermissionDenied(format!(
+                "Unsupported URL scheme '{}'",
+                url.scheme()
+            )));
+        }
+
+        let host = match url.host() {
+            Some(url::Host::Ipv6(ip)) => ip.to_string(),
+            Some(host) => host.to_string().to_lowercase(),
+            None => {
+                return Err(ToolError::InvalidInput(format!("URL has no host: {}", url)));
+            }
+        };
+        let host = host.trim_end_matches('.').to_string();
+
+        if self
+            .blocked_domains
+            .iter()
+            .any(|pattern| matches_domain(pattern, &host))
+        {
+            return Err(ToolError::PermissionDenied(format!(
+                "Domain '{}' is blocked",
+                host
+            )));
+        }
+
+        let allowed = self.allowed_domains.is_empty()
+            || self.trusted_hosts.contains(&host)
+            || self
+                .allowed_domains
+                .iter()
+                .any(|pattern| matches_domain(pattern, &host));
+        if !allowed {
+            return Err(ToolError::PermissionDenied(format!(
+                "Domain '{}' is not in the allowed domains",
+                host
+            )));
+        }
+
+        Ok(host)
+    }
+}
+
+/// Resolver that applies a [`UrlPolicy`] to the addresses it returns
+#[derive(Debug)]
+pub struct PolicyResolver {
+    policy: UrlPolicy,
+}
+
+impl Resolve for PolicyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            // The client sets the port of the URL on each address
+            let addrs = policy.resolve(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Fail if any of a host's addresses is not public
+fn check_addrs(host: &str, addrs: impl IntoIterator<Item = IpAddr>) -> Result<()> {
+    match addrs.into_iter().find(|ip| !is_public(*ip)) {
+        Some(ip) => Err(ToolError::PermissionDenied(format!(
+            "Host '{}' resolves to non-public address {}",
+            host, ip
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn normalize<S: AsRef<str>>(domains: impl IntoIterator<Item = S>) -> Vec<String> {
+    domains
+        .into_iter()
+        .map(|d| d.as_ref().trim().trim_end_matches('.').to_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect()
+}
+
+/// Whether `host` matches an exact or `*.` wildcard domain pattern
+fn matches_domain(pattern: &str, host: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .strip_suffix(suffix)
+            .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+/// Whether an address is reachable on the public internet
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, _, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // shared address space (CGNAT)
+        || (a == 192 && b == 0 && ip.octets()[2] == 0) // IETF protocol assignments
+        || (a == 198 && (b == 18 || b == 19)) // benchmarking
+        || a >= 240) // reserved
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+
+    let segments = ip.segments();
+    // NAT64 addresses embed an IPv4 address in the last 32 bits
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_public_v4(Ipv4Addr::new(a, b, c, d));
+    }
+
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || segments[..6] == [0; 6] // IPv4-compatible (deprecated)
+        || segments[0] == 0x2002 // 6to4, which embeds any IPv4 address
+        || (segments[0] & 0xfe00) == 0xfc00 // unique local
+        || (segments[0] & 0xffc0) == 0xfe80 // link-local
+        || segments[0] == 0x2001 && segments[1] == 0x0db8) // documentation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_domain_patterns() {
+        assert!(matches_domain("*.example.com", "api.example.com"));
+        assert!(matches_domain("*.example.com", "a.b.example.com"));
+        assert!(!matches_domain("*.example.com", "example.com"));
+        assert!(!matches_domain("*.example.com", "badexample.com"));
+        assert!(matches_domain("example.com", "example.com"));
+        assert!(!matches_domain("example.com", "api.example.com"));
+        assert!(matches_domain("*", "anything.test"));
+    }
+
+    #[test]
+    fn test_non_public_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+            "::a9fe:a9fe",
+            "::127.0.0.1",
+            "2002:a9fe:a9fe::1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{} should be blocked", ip);
+        }
+        assert!(is_public("93.184.216.34".parse().unwrap()));
+        assert!(is_public("2606:4700::1111".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_private_addresses_blocked_by_default() {
+        let policy = UrlPolicy::new();
+        assert!(matches!(
+            policy
+                .check(&url("http://169.254.169.254/latest/meta-data/"))
+                .await,
+            Err(ToolError::PermissionDenied(_))
+        ));
+        assert!(policy.check(&url("http://[::1]:8080/")).await.is_err());
+        assert!(policy.check(&url("file:///etc/passwd")).await.is_err());
+
+        let policy = policy.allow_private_networks(true);
+        assert!(policy.check(&url("http://127.0.0.1:8080/")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resolver_applies_policy() {
+        let name = |host: &str| host.parse::<Name>().unwrap();
+
+        let resolver = UrlPolicy::new().resolver();
+        let err = resolver.resolve(name("localhost")).await.err().unwrap();
+        assert!(matches!(
+            err.downcast_ref::<ToolError>(),
+            Some(ToolError::PermissionDenied(_))
+        ));
+
+        for policy in [
+            UrlPolicy::new().allow_private_networks(true),
+            UrlPolicy::new().trust_host("localhost"),
+        ] {
+            let addrs: Vec<SocketAddr> = policy
+                .resolver()
+                .resolve(name("localhost"))
+                .await
+                .unwrap()
+                .collect();
+            assert!(addrs.iter().all(|addr| addr.ip().is_loopback()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_domain_lists() {
+        let policy = UrlPolicy::new()
+            .allowed_domains(["*.Example.com", "10.0.0.5"])
+            .blocked_domains(["admin.example.com"])
+            .allow_private_networks(true);
+
+        assert!(policy
+            .check(&url("https://api.example.com/x"))
+            .await
+            .is_ok());
+        assert!(policy.check(&url("http://10.0.0.5/")).await.is_ok());
+        assert!(policy
+            .check(&url("https://admin.example.com/"))
+            .await
+            .is_err());
+        assert!(policy.check(&url("https://example.org/")).await.is_err());
+
+        // Trusted hosts skip the allow list and address check, not the block list
+        let policy = UrlPolicy::new()
+            .allowed_domains(["*.example.com"])
+            .blocked_domains(["10.0.0.6"])
+            .trust_host("10.0.0.5")
+            .trust_host("10.0.0.6");
+        assert!(policy.check(&url("http://10.0.0.5/")).await.is_ok());
+        assert!(policy.check(&url("http://10.0.0.6/")).await.is_err());
+    }
+}